@@ -73,41 +73,816 @@ async function mockOpAsync(name: string, args: any): Promise<any> {
   return Promise.resolve();
 }
 
-export async function addWindow(title: string): Promise<void> {
-  await mockOpAsync("op_set_label", { text: `Window titled "${title}"` });
-  console.log(`Simulated window creation: "${title}"`);
+export type PanelSide = "left" | "right" | "top" | "bottom";
+
+export type ContainerKind =
+  | {
+    kind: "window";
+    movable?: boolean;
+    collapsible?: boolean;
+    resizable?: boolean;
+    closable?: boolean;
+    open?: boolean;
+    default_pos?: [number, number];
+    default_size?: [number, number];
+  }
+  | { kind: "panel"; side?: PanelSide; resizable?: boolean; default_size?: number }
+  | { kind: "horizontal" }
+  | { kind: "vertical" }
+  | { kind: "grid"; columns: number; striped?: boolean }
+  | { kind: "collapsing"; default_open?: boolean }
+  | { kind: "scroll_area"; horizontal?: boolean; vertical?: boolean; max_height?: number }
+  | { kind: "tabs"; selected?: string };
+
+export async function setContainer(
+  id: string,
+  container: ContainerKind,
+  options: { title?: string; parent?: string } = {},
+): Promise<void> {
+  await mockOpAsync("op_set_container", { id, ...options, ...container });
+}
+
+export async function addWindow(id: string, title: string, parent?: string): Promise<void> {
+  await setContainer(id, { kind: "window" }, { title, parent });
+}
+
+export interface NativeWindowOptions {
+  width?: number;
+  height?: number;
+  x?: number;
+  y?: number;
+}
+
+export async function openWindow(id: string, title: string, options: NativeWindowOptions = {}): Promise<void> {
+  await mockOpAsync("op_open_window", { id, title, ...options });
+}
+
+export async function closeWindow(id: string): Promise<void> {
+  await mockOpAsync("op_close_window", { id });
+}
+
+export async function setWindowTitle(id: string, title: string): Promise<void> {
+  await mockOpAsync("op_set_window_title", { id, title });
+}
+
+export async function setWindowSize(id: string, width: number, height: number): Promise<void> {
+  await mockOpAsync("op_set_window_size", { id, width, height });
+}
+
+export async function setWindowPosition(id: string, x: number, y: number): Promise<void> {
+  await mockOpAsync("op_set_window_position", { id, x, y });
+}
+
+export interface TextSpan {
+  text?: string;
+  /** sRGB */
+  color?: [number, number, number];
+  background?: [number, number, number];
+  bold?: boolean;
+  italic?: boolean;
+  monospace?: boolean;
+  code?: boolean;
+  underline?: boolean;
+  strikethrough?: boolean;
+  size?: number;
+  /** Reported in "label_link" events when clicked. */
+  link?: string;
+  /** Id of an image loaded with loadImage. */
+  image?: string;
+  /** Icon name, e.g. "save", or a glyph. */
+  icon?: string;
+}
+
+/** A catalog message and the values of its `{ $name }` placeables. */
+export interface Message {
+  key: string;
+  args?: Record<string, string | number>;
+}
+
+export interface LabelOptions {
+  spans?: TextSpan[];
+  /** Parses the text as headings, bullets, **bold**, *italic*, `code`, [links](x) and ![images](id). */
+  markdown?: boolean;
+  /** Translates the text from a catalog message, again on every locale change. */
+  message?: Message;
+  parent?: string;
+}
+
+/** Without `id`, sets the text of the built-in label. */
+export async function setLabel(text: string, id?: string, options: LabelOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_label", { text, id, ...options });
+}
+
+/** Loads a PNG or JPEG from a file path or from encoded bytes. */
+export async function loadImage(id: string, source: string | Uint8Array): Promise<void> {
+  const args = typeof source === "string" ? { path: source } : { bytes: Array.from(source) };
+  await mockOpAsync("op_load_image", { id, ...args });
+}
+
+export interface ImageOptions {
+  max_size?: [number, number];
+  tooltip?: string;
+  /** Reports clicks as "image_click" events. */
+  clickable?: boolean;
+  parent?: string;
+}
+
+export async function setImage(id: string, image: string, options: ImageOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_image", { id, image, ...options });
+}
+
+export interface ButtonOptions {
+  enabled?: boolean;
+  tooltip?: string;
+  /** Icon name, e.g. "save", or a glyph. */
+  icon?: string;
+  /** Translates the label from a catalog message instead. */
+  message?: Message;
+  parent?: string;
+}
+
+export async function setButton(id: string, label: string, options: ButtonOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_button", { id, label, ...options });
+}
+
+export interface SliderOptions {
+  min?: number;
+  max?: number;
+  step?: number;
+  logarithmic?: boolean;
+  integer?: boolean;
+  prefix?: string;
+  suffix?: string;
+  text?: string;
+  parent?: string;
+}
+
+/** Without an `id`, sets the built-in slider. */
+export async function setSlider(value: number, id?: string, options: SliderOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_slider", { id, value, ...options });
+}
+
+export type InputValidation =
+  | { kind: "numeric"; integer?: boolean; min?: number; max?: number }
+  | { kind: "regex"; pattern: string };
+
+export interface InputOptions {
+  multiline?: boolean;
+  password?: boolean;
+  hint?: string;
+  max_length?: number;
+  validation?: InputValidation;
+  parent?: string;
+}
+
+/** Without an `id`, sets the built-in input. */
+export async function setInput(text: string, id?: string, options: InputOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_input", { id, text, ...options });
+}
+
+export async function setCheckbox(id: string, checked: boolean, parent?: string): Promise<void> {
+  await mockOpAsync("op_set_checkbox", { id, checked, parent });
+}
+
+export async function setComboBox(id: string, selected: string, options: string[], parent?: string): Promise<void> {
+  await mockOpAsync("op_set_combo_box", { id, selected, options, parent });
+}
+
+export async function setRadio(id: string, selected: string, options: string[] = [], parent?: string): Promise<void> {
+  await mockOpAsync("op_set_radio", { id, selected, options, parent });
+}
+
+export async function setProgress(id: string, value: number, parent?: string): Promise<void> {
+  await mockOpAsync("op_set_progress", { id, value, parent });
+}
+
+export interface TableColumn {
+  name: string;
+  width?: number;
+  sortable?: boolean;
+  resizable?: boolean;
+}
+
+export type TableCell = string | number | boolean | null;
+
+export interface TableOptions {
+  selection?: "none" | "single" | "multi";
+  filterable?: boolean;
+  striped?: boolean;
+  row_height?: number;
+  /** Rows can be dragged onto drop targets. */
+  draggable?: boolean;
+  parent?: string;
+}
+
+export async function setTable(
+  id: string,
+  columns: TableColumn[],
+  rows: TableCell[][],
+  options: TableOptions = {},
+): Promise<void> {
+  await mockOpAsync("op_set_table", { id, columns, rows, ...options });
+}
+
+export async function appendTableRows(id: string, rows: TableCell[][]): Promise<void> {
+  await mockOpAsync("op_append_table_rows", { id, rows });
+}
+
+export interface PlotOptions {
+  x_label?: string;
+  y_label?: string;
+  legend?: boolean;
+  height?: number;
+  /** Keeps only the latest `max_points` points of each series. */
+  max_points?: number;
+  allow_zoom?: boolean;
+  allow_drag?: boolean;
+  parent?: string;
+}
+
+export type SeriesKind = "line" | "scatter" | "bar" | "histogram";
+
+/** `[x, y]`, or a bare `y` placed after the previous sample. */
+export type PlotSample = [number, number] | number;
+
+export interface SeriesOptions {
+  kind?: SeriesKind;
+  color?: [number, number, number];
+  bins?: number;
+}
+
+export async function setPlot(id: string, options: PlotOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_plot", { id, ...options });
+}
+
+export async function setPlotSeries(
+  id: string,
+  name: string,
+  points: PlotSample[],
+  options: SeriesOptions = {},
+): Promise<void> {
+  await mockOpAsync("op_set_plot_series", { id, name, points, ...options });
+}
+
+export async function appendPlotPoints(id: string, series: string, points: PlotSample[]): Promise<void> {
+  await mockOpAsync("op_append_plot_points", { id, series, points });
+}
+
+export interface TreeNode {
+  id: string;
+  label: string;
+  icon?: string;
+  children?: TreeNode[];
+  /** Children are requested with a `tree_load` event on first expand. */
+  lazy?: boolean;
+}
+
+export interface TreeOptions {
+  multi_select?: boolean;
+  draggable?: boolean;
+  /** Shows the 3D scene objects instead of `nodes`. */
+  outliner?: boolean;
+  parent?: string;
+}
+
+export async function setTree(id: string, nodes: TreeNode[], options: TreeOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_tree", { id, nodes, ...options });
+}
+
+export async function setTreeChildren(id: string, node: string, children: TreeNode[]): Promise<void> {
+  await mockOpAsync("op_set_tree_children", { id, node, children });
+}
+
+/** Linear RGBA, each component in 0..1. */
+export type Rgba = [number, number, number, number];
+
+export interface ColorEditOptions {
+  label?: string;
+  color?: Rgba;
+  mode?: "rgb" | "rgba" | "hsv";
+  /** Edits the material color of this 3D object. */
+  object?: string;
+  parent?: string;
+}
+
+export async function setColorEdit(id: string, options: ColorEditOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_color_edit", { id, ...options });
+}
+
+export interface DragValueOptions {
+  label?: string;
+  speed?: number;
+  min?: number;
+  max?: number;
+  decimals?: number;
+  prefix?: string;
+  /** Unit shown after the value, e.g. " m". */
+  suffix?: string;
+  parent?: string;
+}
+
+export async function setDragValue(id: string, value: number, options: DragValueOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_drag_value", { id, value, ...options });
+}
+
+export interface VectorOptions {
+  label?: string;
+  speed?: number;
+  suffix?: string;
+  parent?: string;
+}
+
+export async function setVector(id: string, value: number[], options: VectorOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_vector", { id, value, ...options });
+}
+
+/** `value` is `YYYY-MM-DD`, or `YYYY-MM-DDTHH:MM:SS` with `with_time`. */
+export async function setDateTime(
+  id: string,
+  value: string,
+  options: { label?: string; with_time?: boolean; parent?: string } = {},
+): Promise<void> {
+  await mockOpAsync("op_set_date_time", { id, value, ...options });
+}
+
+// ----- Menus, toolbars and shortcuts -----
+// Picking an entry or pressing a shortcut sends a "command" event whose id is
+// the entry id, with `source` and, for context menus, `target`.
+
+export interface MenuItem {
+  id?: string;
+  label?: string;
+  icon?: string;
+  /**
+   * e.g. "Ctrl+S"; also triggers the command while the menu or toolbar is set.
+   * Needs an `id`.
+   */
+  shortcut?: string;
+  enabled?: boolean;
+  /** Makes the entry a toggle. */
+  checked?: boolean;
+  children?: MenuItem[];
+  separator?: boolean;
+}
+
+/** Without `window` the menu bar belongs to the main window. */
+export async function setMenuBar(menus: MenuItem[], window?: string): Promise<void> {
+  await mockOpAsync("op_set_menu_bar", { menus, window });
+}
+
+/**
+ * `target` is a widget id or a 3D object id. No items removes the menu.
+ * An object's menu opens on its outliner rows and, while it is selected, on
+ * right-clicks over it in the 3D viewport; not on the empty space around it.
+ */
+export async function setContextMenu(target: string, items: MenuItem[]): Promise<void> {
+  await mockOpAsync("op_set_context_menu", { target, items });
+}
+
+export async function setToolbar(id: string, items: MenuItem[], parent?: string): Promise<void> {
+  await mockOpAsync("op_set_toolbar", { id, items, parent });
+}
+
+export async function registerShortcut(command: string, keys: string): Promise<void> {
+  await mockOpAsync("op_register_shortcut", { command, keys });
+}
+
+export async function unregisterShortcut(command: string): Promise<void> {
+  await mockOpAsync("op_unregister_shortcut", { command });
+}
+
+// ----- Dialogs and toasts -----
+// Each resolves once the user answers; closing a dialog resolves with null.
+
+/** Resolves with the label of the clicked button. */
+export async function confirm(title: string, message = "", buttons: string[] = []): Promise<string | null> {
+  return await mockOpAsync("op_confirm", { title, message, buttons });
+}
+
+export async function prompt(
+  title: string,
+  options: { message?: string; text?: string; hint?: string } = {},
+): Promise<string | null> {
+  return await mockOpAsync("op_prompt", { title, ...options });
+}
+
+export type ToastLevel = "info" | "success" | "warning" | "error";
+
+/** `duration` is in seconds; 0 keeps the toast until it is clicked. */
+export async function toast(
+  message: string,
+  options: { level?: ToastLevel; duration?: number } = {},
+): Promise<{ dismissed: boolean }> {
+  return await mockOpAsync("op_toast", { message, ...options });
+}
+
+export interface FileDialogOptions {
+  title?: string;
+  /** Starts in the working directory if unset. */
+  directory?: string;
+  file_name?: string;
+  /** Extensions without the dot, e.g. ["json"]. */
+  extensions?: string[];
+}
+
+export async function openFileDialog(options: FileDialogOptions = {}): Promise<string | null> {
+  return await mockOpAsync("op_file_dialog", { mode: "open", ...options });
+}
+
+export async function saveFileDialog(options: FileDialogOptions = {}): Promise<string | null> {
+  return await mockOpAsync("op_file_dialog", { mode: "save", ...options });
+}
+
+// ----- Theme -----
+
+/** sRGB */
+export type Rgb = [number, number, number];
+
+export interface ThemeColors {
+  panel_fill?: Rgb;
+  window_fill?: Rgb;
+  /** Background of text edits and scroll bars. */
+  extreme_bg?: Rgb;
+  /** Background of striped rows. */
+  faint_bg?: Rgb;
+  text?: Rgb;
+  hyperlink?: Rgb;
+  selection?: Rgb;
+  warn?: Rgb;
+  error?: Rgb;
+}
+
+export type ThemeBase = "dark" | "light" | "system";
+
+export interface Spacing {
+  item_spacing?: [number, number];
+  button_padding?: [number, number];
+  window_margin?: number;
+  indent?: number;
+  slider_width?: number;
+}
+
+export interface Theme {
+  base?: ThemeBase;
+  colors?: ThemeColors;
+  fonts?: { name: string; path: string; family?: string; first?: boolean }[];
+  text_styles?: Record<string, { size: number; family?: string }>;
+  spacing?: Spacing;
+  rounding?: number;
+  ui_scale?: number;
+}
+
+/** Unset fields keep their current value. */
+export async function setVisuals(options: { base?: ThemeBase; colors?: ThemeColors; rounding?: number }): Promise<void> {
+  await mockOpAsync("op_set_visuals", options);
+}
+
+/**
+ * Adds a TTF/OTF font to `family` ("proportional", "monospace" or a custom
+ * name), before the built-in fonts unless `first` is false.
+ */
+export async function loadFont(name: string, path: string, family = "proportional", first = true): Promise<void> {
+  await mockOpAsync("op_load_font", { name, path, family, first });
+}
+
+/**
+ * `name` is "small", "body", "monospace", "button", "heading" or a custom style.
+ * A `family` other than "proportional" or "monospace" needs a font loaded into it first.
+ */
+export async function setTextStyle(name: string, size: number, family = "proportional"): Promise<void> {
+  await mockOpAsync("op_set_text_style", { name, size, family });
+}
+
+export async function setSpacing(spacing: Spacing): Promise<void> {
+  await mockOpAsync("op_set_spacing", spacing);
+}
+
+/** Multiplies the OS scale factor; 0.25 to 4. */
+export async function setUiScale(scale: number): Promise<void> {
+  await mockOpAsync("op_set_ui_scale", { scale });
+}
+
+export async function getTheme(): Promise<Theme> {
+  return await mockOpAsync("op_get_theme", {});
+}
+
+export async function saveTheme(path: string): Promise<void> {
+  await mockOpAsync("op_save_theme", { path });
 }
 
-export async function setLabel(text: string): Promise<void> {
-  await mockOpAsync("op_set_label", { text });
+export async function loadTheme(path: string): Promise<void> {
+  await mockOpAsync("op_load_theme", { path });
 }
 
-export async function setButton(id: string, label: string): Promise<void> {
-  console.log(`Set button: ${id} with label: ${label} (rust side is a no-op unless extended)`);
+// ----- Drag and drop -----
+
+/** Payload of `file_hover` and `file_drop` events, sent with the window id. */
+export interface FileDropEvent {
+  id: string;
+  paths: string[];
+  x: number;
+  y: number;
+  /** Widget under the pointer, `"viewport"` for the 3D scene, or null. */
+  target: string | null;
+}
+
+/** Payload of `item_drop` events, sent with the id of the drop target. */
+export interface ItemDropEvent {
+  id: string;
+  source: string;
+  /** Node id or row index in `source`. */
+  item: string;
+  /** Part of the target the item was dropped on, e.g. a node id or `"row/3"`. */
+  over: string | null;
+}
+
+/**
+ * Lets items of the widgets in `accept` be dropped on widget `id`; `"*"`
+ * accepts any widget. Pass `null` to stop accepting drops.
+ */
+export async function setDropTarget(id: string, accept: string[] | null): Promise<void> {
+  await mockOpAsync("op_set_drop_target", { id, accept });
+}
+
+export function onFileDrop(handler: (event: FileDropEvent) => void): void {
+  onEvent("file_drop", handler);
+}
+
+export function onItemDrop(handler: (event: ItemDropEvent) => void): void {
+  onEvent("item_drop", handler);
+}
+
+// ----- Clipboard -----
+
+export interface ClipboardImage {
+  width: number;
+  height: number;
+  /** `width * height` RGBA pixels, row by row. */
+  rgba: Uint8Array | number[];
+}
+
+export async function getClipboardText(): Promise<string | null> {
+  return await mockOpAsync("op_get_clipboard_text", {});
+}
+
+export async function setClipboardText(text: string): Promise<void> {
+  await mockOpAsync("op_set_clipboard_text", { text });
+}
+
+/** Resolves to null if the clipboard holds no image. */
+export async function getClipboardImage(): Promise<ClipboardImage | null> {
+  return await mockOpAsync("op_get_clipboard_image", {});
+}
+
+export async function setClipboardImage(image: ClipboardImage): Promise<void> {
+  await mockOpAsync("op_set_clipboard_image", { ...image, rgba: Array.from(image.rgba) });
+}
+
+/** Payload of `copy` and `paste` events, sent with the window id. */
+export interface ClipboardEvent {
+  id: string;
+  /** The focused widget, if it is known. */
+  widget: string | null;
+  text: string;
+  /** Set on `copy` events if the text was cut. */
+  cut?: boolean;
+}
+
+export function onCopy(handler: (event: ClipboardEvent) => void): void {
+  onEvent("copy", handler);
+}
+
+export function onPaste(handler: (event: ClipboardEvent) => void): void {
+  onEvent("paste", handler);
+}
+
+// ----- Accessibility -----
+
+export type AccessRole =
+  | "alert" | "button" | "cell" | "checkbox" | "color_well" | "column_header" | "dialog" | "group"
+  | "heading" | "image" | "link" | "list" | "list_item" | "menu" | "menu_bar" | "menu_item"
+  | "paragraph" | "popup_button" | "progress_indicator" | "radio_button" | "row" | "slider"
+  | "spin_button" | "static_text" | "status" | "tab" | "tab_list" | "table" | "text_field"
+  | "toggle_button" | "toolbar" | "tree" | "tree_item" | "unknown" | "window";
+
+/** Unset fields keep what egui derives from the widget. */
+export interface AccessInfo {
+  label?: string;
+  description?: string;
+  role?: AccessRole;
+}
+
+export interface AccessNode {
+  /** Widget id, for widgets created by scripts. */
+  id: string | null;
+  role: string;
+  name: string | null;
+  description: string | null;
+  value: string | null;
+  /** `[x0, y0, x1, y1]` in points. */
+  bounds: [number, number, number, number] | null;
+  children: AccessNode[];
+}
+
+export async function setAccessibility(id: string, info: AccessInfo): Promise<void> {
+  await mockOpAsync("op_set_accessibility", { id, ...info });
+}
+
+/** The accessibility tree of the main window, e.g. for automated audits. */
+export async function getAccessibilityTree(): Promise<AccessNode | null> {
+  return await mockOpAsync("op_get_accessibility_tree", {});
+}
+
+// ----- Localization -----
+
+/** Adds the messages of a Fluent `.ftl` or a `.json` catalog file to `locale`. */
+export async function loadCatalog(locale: string, path: string): Promise<void> {
+  await mockOpAsync("op_load_catalog", { locale, path });
+}
+
+/**
+ * Shows labels and buttons with messages in `locale`, e.g. "es" or "ja". CJK,
+ * Arabic and Hebrew locales need a font covering their script: `font` if
+ * given, else an installed system font.
+ */
+export async function setLocale(locale: string, font?: string): Promise<void> {
+  await mockOpAsync("op_set_locale", { locale, font });
+}
+
+export interface LocaleInfo {
+  locale: string;
+  /** Locale of the catalog used for messages missing from the current one. */
+  fallback: string;
+  direction: "ltr" | "rtl";
+  /** Locales with a loaded catalog. */
+  locales: string[];
+}
+
+export async function getLocale(): Promise<LocaleInfo> {
+  return await mockOpAsync("op_get_locale", {});
+}
+
+/** The message `key` in the current locale, or the key if no catalog has it. */
+export async function translate(key: string, args: Record<string, string | number> = {}): Promise<string> {
+  return await mockOpAsync("op_translate", { key, args });
+}
+
+/** Sent with the new locale as id. */
+export function onLocaleChange(handler: (locale: string, direction: "ltr" | "rtl") => void): void {
+  onEvent("locale_change", (data: { id: string; direction: "ltr" | "rtl" }) => handler(data.id, data.direction));
+}
+
+// ----- Persistence -----
+
+/**
+ * Opts widget `id` in or out of being restored on the next start. Widgets
+ * without a choice follow `setPersistByDefault`, which is on by default.
+ */
+export async function setPersist(id: string, persist: boolean): Promise<void> {
+  await mockOpAsync("op_set_persist", { id, persist });
+}
+
+export async function setPersistByDefault(persist: boolean): Promise<void> {
+  await mockOpAsync("op_set_persist", { persist });
+}
+
+export interface PersistedState {
+  /** The state file, or null when started with `--no-persist`. */
+  path: string | null;
+  /** Widget values as they would be saved now, keyed by widget id. */
+  widgets: Record<string, any>;
+  /** Windows whose layout (window positions, panel sizes, open sections) is saved. */
+  windows: string[];
+}
+
+export async function getPersistedState(): Promise<PersistedState> {
+  return await mockOpAsync("op_get_persisted_state", {});
+}
+
+/** Forgets the saved values of `ids`, or all saved state so the next start is fresh. */
+export async function clearPersistedState(ids?: string[]): Promise<void> {
+  await mockOpAsync("op_clear_persisted_state", { ids });
+}
+
+/** Sent with the widget id when a saved value is restored into a widget. */
+export function onStateRestore(handler: (id: string, value: any) => void): void {
+  onEvent("state_restore", (data: { id: string; value: any }) => handler(data.id, data.value));
+}
+
+// ----- Storage -----
+// JSON values kept in the data directory (`--data-dir`, "pioneer_data" by
+// default), one file per namespace.
+
+/** Namespace of the running script: its file name without extension. */
+const scriptNamespace = new URL(Deno.mainModule).pathname.split("/").pop()!.replace(/\.[jt]sx?$/, "");
+
+export interface Store {
+  /** Resolves to null if `key` is not stored. */
+  get<T = any>(key: string): Promise<T | null>;
+  set(key: string, value: any): Promise<void>;
+  /** Resolves to whether `key` was stored. */
+  delete(key: string): Promise<boolean>;
+  list(prefix?: string): Promise<string[]>;
+}
+
+/** The store of `namespace`, by default the one of the running script. */
+export function store(namespace = scriptNamespace): Store {
+  return {
+    get: (key) => mockOpAsync("op_storage_get", { namespace, key }),
+    set: (key, value) => mockOpAsync("op_storage_set", { namespace, key, value }),
+    delete: (key) => mockOpAsync("op_storage_delete", { namespace, key }),
+    list: (prefix = "") => mockOpAsync("op_storage_list", { namespace, prefix }),
+  };
+}
+
+export const storage = store();
+
+// ----- Data binding -----
+
+/** Sent once per frame for each observable that changed. */
+export interface ObservableChange<T = any> {
+  /** Observable name. */
+  id: string;
+  value: T;
+  /** The widget whose edit changed the value, or null for script writes. */
+  source: string | null;
 }
 
-export async function setSlider(value: number): Promise<void> {
-  await mockOpAsync("op_set_slider", { value });
+/**
+ * A named value shared with the widgets bound to it. Writes show up in every
+ * bound widget on the next frame; edits in a bound widget update the value
+ * and notify subscribers.
+ */
+export class Observable<T = any> {
+  private subscribers = new Set<(value: T, source: string | null) => void>();
+
+  constructor(public readonly name: string, public value: T) {
+    mockOpAsync("op_declare_observable", { name, value })
+      .then((current) => {
+        if (current !== undefined) {
+          this.value = current;
+        }
+      })
+      .catch(console.error);
+    onEvent("observable_change", (change: ObservableChange<T>) => {
+      if (change.id === this.name) {
+        this.value = change.value;
+        this.subscribers.forEach((handler) => handler(change.value, change.source));
+      }
+    });
+  }
+
+  get(): T {
+    return this.value;
+  }
+
+  async set(value: T): Promise<void> {
+    this.value = value;
+    await mockOpAsync("op_set_observable", { name: this.name, value });
+  }
+
+  /** Returns a function that unsubscribes `handler`. */
+  subscribe(handler: (value: T, source: string | null) => void): () => void {
+    this.subscribers.add(handler);
+    return () => this.subscribers.delete(handler);
+  }
+
+  /** Binds widget `id`: it shows the value and its edits change the value. */
+  async bind(id: string): Promise<void> {
+    await bind(id, this.name);
+  }
 }
 
-export async function setInput(text: string): Promise<void> {
-  await mockOpAsync("op_set_input", { text });
+/** Declares observable `name`, keeping its value if it already exists. */
+export function observable<T>(name: string, initial: T): Observable<T> {
+  return new Observable(name, initial);
 }
 
-export async function setCheckbox(id: string, checked: boolean): Promise<void> {
-  await mockOpAsync("op_set_checkbox", { id, checked });
+/** Binds widget `id` to observable `name`, or unbinds it if `name` is null. */
+export async function bind(id: string, name: string | null): Promise<void> {
+  await mockOpAsync("op_bind", { widget: id, name });
 }
 
-export async function setComboBox(id: string, selected: string, options: string[]): Promise<void> {
-  await mockOpAsync("op_set_combo_box", { id, selected, options });
+// ----- UI documents -----
+
+/**
+ * Loads a `.json`, `.ron` or `.yaml` document describing windows, panels and
+ * widgets, and resolves to the ids of its widgets. With `watch`, the default,
+ * the document is reloaded whenever the file changes; widgets keep their
+ * values across reloads.
+ */
+export async function loadUi(path: string, options: { watch?: boolean } = {}): Promise<string[]> {
+  return await mockOpAsync("op_load_ui", { path, ...options });
 }
 
-export async function setRadio(id: string, selected: string): Promise<void> {
-  await mockOpAsync("op_set_radio", { id, selected });
+/** Sent with the document path each time it is loaded. */
+export function onUiLoad(handler: (path: string, widgets: string[]) => void): void {
+  onEvent("ui_load", (data: { id: string; widgets: string[] }) => handler(data.id, data.widgets));
 }
 
-export async function setProgress(id: string, value: number): Promise<void> {
-  await mockOpAsync("op_set_progress", { id, value });
+/** Sent with the document path when a changed document cannot be reloaded. */
+export function onUiLoadError(handler: (path: string, message: string) => void): void {
+  onEvent("ui_load_error", (data: { id: string; message: string }) => handler(data.id, data.message));
 }
 
 export async function rotate3D(angle: number): Promise<void> {
@@ -123,6 +898,15 @@ export async function add3DObject(sceneId: string, objectId: string, objectType:
   });
 }
 
+export async function setObjectColor(objectId: string, color: Rgba): Promise<void> {
+  await mockOpAsync("op_set_object_color", { object_id: objectId, color });
+}
+
+/** Pass `null` to clear the selection. */
+export async function selectObject(objectId: string | null): Promise<void> {
+  await mockOpAsync("op_select_object", { object_id: objectId });
+}
+
 export async function startRecording(): Promise<void> {
   await mockOpAsync("op_start_recording", {});
 }
@@ -139,6 +923,127 @@ export async function stopPlayback(): Promise<void> {
   await mockOpAsync("op_stop_playback", {});
 }
 
+export async function pausePlayback(): Promise<void> {
+  await mockOpAsync("op_pause_playback", {});
+}
+
+export async function resumePlayback(): Promise<void> {
+  await mockOpAsync("op_resume_playback", {});
+}
+
+/** Moves playback to `timestamp` ms, starting it paused if it is stopped. */
+export async function seekPlayback(timestamp: number): Promise<void> {
+  await mockOpAsync("op_seek_playback", { timestamp });
+}
+
+/** Pauses playback and replays the next event. */
+export async function stepPlayback(): Promise<void> {
+  await mockOpAsync("op_step_playback", {});
+}
+
+/** From 0.1 to 16 times real time. */
+export async function setPlaybackRate(rate: number): Promise<void> {
+  await mockOpAsync("op_set_playback_rate", { rate });
+}
+
+/** Loops playback between `start` and `end` ms; pass `null` to stop looping. */
+export async function setPlaybackLoop(range: { start: number; end: number } | null): Promise<void> {
+  await mockOpAsync("op_set_playback_loop", range ?? {});
+}
+
+export interface PlaybackStatus {
+  state: "stopped" | "playing" | "paused";
+  /** Milliseconds into the recording. */
+  position: number;
+  duration: number;
+  /** Number of events replayed so far. */
+  index: number;
+  events: number;
+  rate: number;
+  loop: [number, number] | null;
+}
+
+export async function getPlaybackState(): Promise<PlaybackStatus> {
+  return await mockOpAsync("op_get_playback_state", {});
+}
+
+/** Sent when playback changes state, seeks or steps, and every 100 ms while playing. */
+export function onPlaybackProgress(handler: (status: PlaybackStatus) => void): void {
+  onEvent("playback_progress", handler);
+}
+
+/**
+ * Sent for each event replayed. Widget values are already restored; scripts
+ * redo any other effects of the event.
+ */
+export function onPlaybackEvent(
+  handler: (event: { id: string; event_type: string; event_data: any; timestamp: number }) => void,
+): void {
+  onEvent("playback_event", handler);
+}
+
+export interface RecordedEvent {
+  eventType: string;
+  componentId: string;
+  eventData: any;
+  /** Milliseconds since the recording started. */
+  timestamp: number;
+}
+
+export async function getRecordedEvents(): Promise<RecordedEvent[]> {
+  return await mockOpAsync("op_get_recorded_events", {});
+}
+
+/** Widget values and 3D scene state at a point in a recording. */
+export interface RecordingSnapshot {
+  timestamp: number;
+  /** Number of events recorded before the snapshot was taken. */
+  events: number;
+  widgets: Record<string, any>;
+  rotation: number;
+  sceneObjects: Record<string, { scene_id: string; object_type: string; size: number; color: Rgba }>;
+  selectedObject: string | null;
+}
+
+/** The state the recording began in, then keyframes taken every few seconds. */
+export async function getRecordingSnapshots(): Promise<RecordingSnapshot[]> {
+  return await mockOpAsync("op_get_recording_snapshots", {});
+}
+
+/** Adds a keyframe with the current state to the recording, e.g. after a script changed it. */
+export async function addSnapshot(): Promise<void> {
+  await mockOpAsync("op_add_snapshot", {});
+}
+
+/** Events are addressed by index into the recording, which is kept in timestamp order. */
+export type RecordingEdit =
+  | { edit: "delete"; indices: number[] }
+  /** Shifts the events by `delta` ms. */
+  | { edit: "move"; indices: number[]; delta: number }
+  /** Inserts copies of the events `delta` ms after them. */
+  | { edit: "duplicate"; indices: number[]; delta?: number }
+  | { edit: "replace"; index: number; event: RecordedEvent };
+
+/** Resolves to the indices of the edited events after the edit. */
+export async function editRecording(edit: RecordingEdit): Promise<number[]> {
+  return await mockOpAsync("op_edit_recording", edit);
+}
+
+/** Sent whenever the recording is edited, from a timeline or a script. */
+export function onRecordingEdit(handler: (edit: RecordingEdit, result: number[]) => void): void {
+  onEvent("recording_edit", (data: RecordingEdit & { result: number[] }) => handler(data, data.result));
+}
+
+export interface TimelineOptions {
+  row_height?: number;
+  parent?: string;
+}
+
+/** A timeline of the recorded events, one row per component. */
+export async function setTimeline(id: string, options: TimelineOptions = {}): Promise<void> {
+  await mockOpAsync("op_set_timeline", { id, ...options });
+}
+
 export async function saveRecordedEvents(args: { filename: string }): Promise<void> {
   await mockOpAsync("op_save_recorded_events", args);
 }
@@ -146,3 +1051,65 @@ export async function saveRecordedEvents(args: { filename: string }): Promise<vo
 export async function loadRecordedEvents(args: { filename: string }): Promise<void> {
   await mockOpAsync("op_load_recorded_events", args);
 }
+
+// ----- Automation -----
+// Drives the UI like a user would by injecting synthetic input events.
+
+export type AutomationAction =
+  | { action: "click"; id: string }
+  | { action: "type_text"; id: string; text: string }
+  | { action: "drag_slider"; id: string; value: number }
+  | { action: "select_combo"; id: string; option: string }
+  | { action: "select_radio"; id: string; option: string }
+  | { action: "press_key"; key: string; command?: boolean }
+  | { action: "drag"; from: string; to: string }
+  | { action: "context_click"; id: string }
+  | { action: "drop_files"; id: string; paths: string[] }
+  | { action: "copy"; id: string }
+  | { action: "paste"; id: string; text: string };
+
+export interface WidgetQuery {
+  rect: [number, number, number, number];
+  value: any;
+}
+
+async function perform(action: AutomationAction): Promise<void> {
+  await mockOpAsync("op_automation_perform", action);
+  await waitFor(async () => {
+    const status = await mockOpAsync("op_automation_status", {});
+    if (status?.errors?.length) {
+      throw new Error(`Automation failed: ${status.errors.join(", ")}`);
+    }
+    return status?.idle ?? true;
+  });
+}
+
+export async function waitFor(
+  condition: () => boolean | Promise<boolean>,
+  timeoutMs = 5000,
+  intervalMs = 16,
+): Promise<void> {
+  const deadline = Date.now() + timeoutMs;
+  while (!(await condition())) {
+    if (Date.now() > deadline) {
+      throw new Error(`waitFor timed out after ${timeoutMs}ms`);
+    }
+    await new Promise((resolve) => setTimeout(resolve, intervalMs));
+  }
+}
+
+export const automation = {
+  click: (id: string) => perform({ action: "click", id }),
+  typeText: (id: string, text: string) => perform({ action: "type_text", id, text }),
+  dragSlider: (id: string, value: number) => perform({ action: "drag_slider", id, value }),
+  selectCombo: (id: string, option: string) => perform({ action: "select_combo", id, option }),
+  selectRadio: (id: string, option: string) => perform({ action: "select_radio", id, option }),
+  pressKey: (key: string, command = false) => perform({ action: "press_key", key, command }),
+  drag: (from: string, to: string) => perform({ action: "drag", from, to }),
+  contextClick: (id: string) => perform({ action: "context_click", id }),
+  dropFiles: (id: string, paths: string[]) => perform({ action: "drop_files", id, paths }),
+  copy: (id: string) => perform({ action: "copy", id }),
+  paste: (id: string, text: string) => perform({ action: "paste", id, text }),
+  query: (id: string): Promise<WidgetQuery | null> => mockOpAsync("op_automation_query", { id }),
+  waitFor,
+};
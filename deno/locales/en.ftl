@@ -0,0 +1,9 @@
+-app-name = Pioneer eGUI
+
+welcome = Welcome to { -app-name } with Timeline!
+record = Start Recording
+    .description = Records UI events until Stop Recording is pressed
+stop-record = Stop Recording
+load = Load Recording
+playback = Start Playback
+stop-playback = Stop Playback
@@ -0,0 +1,9 @@
+-app-name = Pioneer eGUI
+
+welcome = ¡Bienvenido a { -app-name } con línea de tiempo!
+record = Iniciar grabación
+    .description = Graba los eventos de la interfaz hasta pulsar Detener grabación
+stop-record = Detener grabación
+load = Cargar grabación
+playback = Iniciar reproducción
+stop-playback = Detener reproducción
@@ -0,0 +1,9 @@
+-app-name = Pioneer eGUI
+
+welcome = Bienvenue dans { -app-name } avec la chronologie !
+record = Démarrer l'enregistrement
+    .description = Enregistre les événements de l'interface jusqu'à l'arrêt de l'enregistrement
+stop-record = Arrêter l'enregistrement
+load = Charger un enregistrement
+playback = Démarrer la lecture
+stop-playback = Arrêter la lecture
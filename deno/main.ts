@@ -1,53 +1,168 @@
 import { pioneer } from "./pioneer_egui.ts";
 import * as EguiAPI from "./egui_api.ts";
 
+const recordingFiles = { extensions: ["json"] };
+
+/** The dialogs start in the directory of the last recording saved or loaded. */
+async function recordingDialogOptions(title: string) {
+  const directory = await EguiAPI.storage.get<string>("recordingDirectory") ?? undefined;
+  return { ...recordingFiles, title, directory };
+}
+
+async function rememberDirectory(filename: string): Promise<void> {
+  const directory = filename.substring(0, filename.lastIndexOf("/"));
+  if (directory) {
+    await EguiAPI.storage.set("recordingDirectory", directory);
+  }
+}
+
+async function saveRecording(): Promise<void> {
+  const options = await recordingDialogOptions("Save Recording");
+  const filename = await EguiAPI.saveFileDialog({ ...options, file_name: "timeline.json" });
+  if (!filename) {
+    return;
+  }
+  await EguiAPI.saveRecordedEvents({ filename });
+  await rememberDirectory(filename);
+  await EguiAPI.toast(`Saved to ${filename}`, { level: "success" });
+}
+
+async function loadRecording(): Promise<void> {
+  const filename = await EguiAPI.openFileDialog(await recordingDialogOptions("Load Recording"));
+  if (!filename) {
+    return;
+  }
+  await EguiAPI.loadRecordedEvents({ filename });
+  await rememberDirectory(filename);
+  console.log(`Events loaded from ${filename}`);
+}
+
+const languages: Record<string, string> = { English: "en", Spanish: "es", French: "fr" };
+
+async function loadCatalogs(): Promise<void> {
+  for (const locale of Object.values(languages)) {
+    const path = new URL(`./locales/${locale}.ftl`, import.meta.url).pathname;
+    await EguiAPI.loadCatalog(locale, path);
+  }
+}
+
 function buildUI() {
+  const volume = EguiAPI.observable("volume", 50);
+  volume.subscribe((value, source) => console.log(`Volume is ${value} (changed by ${source ?? "script"})`));
+
   const window = pioneer.egui()
     .addWindow("Timeline Dashboard");
 
   window
     .addLabel("welcomeLabel")
-    .setText("Welcome to Pioneer eGUI with Timeline!")
+    .setMessage("welcome")
     .addButton("recordButton", "Start Recording")
+    .setMessage("record")
+    .setIcon("record")
+    .setAccessible({ description: "Records UI events until Stop Recording is pressed" })
     .onClick(() => {
       console.log("Start Recording clicked.");
       pioneer.egui().add3DScene("mainScene").startRecording();
     })
     .addButton("stopRecordButton", "Stop Recording")
+    .setMessage("stop-record")
+    .setIcon("stop")
     .onClick(async () => {
       console.log("Stop Recording clicked.");
       const recorded = await pioneer.egui().add3DScene("mainScene").stopRecording();
       console.log("Recorded events:", recorded);
-      // Let's save them
-      await EguiAPI.saveRecordedEvents({ filename: "timeline.json" });
-      console.log("Saved to timeline.json");
+      await saveRecording();
     })
     .addButton("loadButton", "Load Recording")
+    .setMessage("load")
+    .setIcon("open")
     .onClick(async () => {
       console.log("Load Recording clicked.");
-      await EguiAPI.loadRecordedEvents({ filename: "timeline.json" });
-      console.log("Events loaded from timeline.json");
+      await loadRecording();
     })
     .addButton("playbackButton", "Start Playback")
+    .setMessage("playback")
+    .setIcon("play")
     .onClick(() => {
       console.log("Start Playback clicked.");
       pioneer.egui().add3DScene("mainScene").startPlayback();
     })
     .addButton("stopPlaybackButton", "Stop Playback")
+    .setMessage("stop-playback")
+    .setIcon("stop")
     .onClick(() => {
       console.log("Stop Playback clicked.");
       pioneer.egui().add3DScene("mainScene").stopPlayback();
-    });
+    })
+    .addButton("pausePlaybackButton", "Pause")
+    .setIcon("pause")
+    .onClick(async () => {
+      const { state } = await EguiAPI.getPlaybackState();
+      if (state === "paused") {
+        await EguiAPI.resumePlayback();
+      } else {
+        await EguiAPI.pausePlayback();
+      }
+    })
+    .addButton("stepPlaybackButton", "Step")
+    .onClick(() => EguiAPI.stepPlayback().catch(console.error));
+
+  window
+    .addComboBox("playbackRateCombo", ["0.25x", "0.5x", "1x", "2x", "4x", "16x"])
+    .setSelected("1x")
+    .onChange((selected) => EguiAPI.setPlaybackRate(parseFloat(selected)).catch(console.error));
+
+  // Transport status drawn from playback progress events
+  window.addLabel("playbackLabel").setText("Playback stopped");
+  EguiAPI.onPlaybackProgress(({ state, position, duration, rate }) => {
+    const seconds = (ms: number) => (ms / 1000).toFixed(1);
+    EguiAPI.setLabel(`Playback ${state} · ${seconds(position)} / ${seconds(duration)} s · ${rate}x`, "playbackLabel")
+      .catch(console.error);
+  });
+
+  window
+    .addTimeline("recordingTimeline")
+    .onSelect(async (selected) => {
+      const events = await EguiAPI.getRecordedEvents();
+      console.log("Selected events:", selected.map((index) => events[index]));
+    })
+    .onSeek((timestamp) => console.log(`Timeline scrubbed to ${timestamp} ms`));
+
+  EguiAPI.onRecordingEdit((edit) => console.log(`Recording edited: ${edit.edit}`));
+
+  window
+    .addLabel("statusLabel")
+    .setRichText([
+      { text: "Status: ", bold: true },
+      { icon: "check", text: "Ready", color: [80, 200, 120] },
+    ])
+    .onLink((link) => console.log(`Status link clicked: ${link}`));
+
+  window
+    .addLabel("helpLabel")
+    .setMarkdown("Press **Ctrl+S** to save a recording, or see the [docs](help).")
+    .onLink((link) => console.log(`Help link clicked: ${link}`));
 
   window
     .addSlider("volumeSlider", [0, 100])
+    .setInteger()
+    .setSuffix("%")
     .setValue(50)
+    .bind(volume)
     .onChange((value) => {
       console.log(`Slider value changed to ${value}`);
     });
 
+  // Shows the volume bound to the slider above
+  window
+    .addLabel("volumeLabel")
+    .bind(volume);
+
   window
     .addInput("usernameInput")
+    .setHint("Username")
+    .setMaxLength(32)
+    .setValidation({ kind: "regex", pattern: "[A-Za-z ]+" })
     .setText("John Doe")
     .onInput((text) => {
       console.log(`Input text changed to "${text}"`);
@@ -63,15 +178,20 @@ function buildUI() {
   window
     .addComboBox("themeCombo", ["Light", "Dark", "System"])
     .setSelected("Dark")
+    .onRestore((selected: string) =>
+      EguiAPI.setVisuals({ base: selected.toLowerCase() as EguiAPI.ThemeBase }).catch(console.error))
     .onChange((selected) => {
       console.log(`ComboBox selected option: ${selected}`);
+      EguiAPI.setVisuals({ base: selected.toLowerCase() as EguiAPI.ThemeBase }).catch(console.error);
     });
 
   window
     .addRadioGroup("languageRadio", ["English", "Spanish", "French"])
     .setSelected("English")
+    .onRestore((selected: string) => EguiAPI.setLocale(languages[selected]).catch(console.error))
     .onChange((selected) => {
       console.log(`Radio group selected: ${selected}`);
+      EguiAPI.setLocale(languages[selected]).catch(console.error);
     });
 
   window
@@ -90,12 +210,57 @@ function buildUI() {
       console.log(`3D scene rotated by ${angle} degrees`);
     });
 
+  pioneer.egui()
+    .setMenuBar([
+      {
+        id: "file",
+        label: "File",
+        children: [
+          { id: "save", label: "Save Recording", shortcut: "Ctrl+S" },
+          { id: "open", label: "Load Recording", shortcut: "Ctrl+O" },
+          { separator: true },
+          {
+            id: "playback",
+            label: "Playback",
+            children: [
+              { id: "play", label: "Start", icon: "▶" },
+              { id: "stop", label: "Stop", icon: "⏹" },
+            ],
+          },
+        ],
+      },
+    ])
+    .onCommand("save", saveRecording)
+    .onCommand("open", loadRecording)
+    .onCommand("play", () => scene.startPlayback())
+    .onCommand("stop", () => scene.stopPlayback());
+
+  scene.setObjectContextMenu("cube1", [{ id: "reset_color", label: "Reset Color" }]);
+  pioneer.egui().onCommand("reset_color", ({ target }) => {
+    if (target) {
+      scene.setObjectColor(target, [1, 1, 1, 1]);
+    }
+  });
+
+  // Recordings dropped from the file manager are loaded right away
+  EguiAPI.onFileDrop(async ({ paths }) => {
+    for (const filename of paths) {
+      if (filename.endsWith(".json")) {
+        await EguiAPI.loadRecordedEvents({ filename });
+        await EguiAPI.toast(`Loaded ${filename}`, { level: "success" });
+      } else {
+        await EguiAPI.toast(`Cannot open ${filename}`, { level: "warning" });
+      }
+    }
+  });
+
   // Periodically rotate the 3D scene
   setInterval(() => {
     pioneer.egui().add3DScene("mainScene").rotate(15);
   }, 5000);
 }
 
+await loadCatalogs();
 buildUI();
 
 // Keep Deno alive
@@ -1,35 +1,97 @@
 import * as EguiAPI from "./egui_api.ts";
 
+function slugify(text: string): string {
+  return text.toLowerCase().replace(/[^a-z0-9]+/g, "_").replace(/^_|_$/g, "");
+}
+
 class EguiComponent {
   constructor(public id: string) {}
+
+  /** Overrides what screen readers are told about this widget. */
+  setAccessible(info: EguiAPI.AccessInfo): this {
+    EguiAPI.setAccessibility(this.id, info).catch(console.error);
+    return this;
+  }
+
+  /** Keeps (or stops keeping) the value of this widget across restarts. */
+  setPersist(persist: boolean): this {
+    EguiAPI.setPersist(this.id, persist).catch(console.error);
+    return this;
+  }
+
+  /** Binds this widget to an observable, or to the observable named `name`. */
+  bind(observable: EguiAPI.Observable | string): this {
+    const name = typeof observable === "string" ? observable : observable.name;
+    EguiAPI.bind(this.id, name).catch(console.error);
+    return this;
+  }
+
+  /** Called with the saved value restored into this widget at startup. */
+  onRestore(handler: (value: any) => void): this {
+    EguiAPI.onStateRestore((id, value) => {
+      if (id === this.id) {
+        handler(value);
+      }
+    });
+    return this;
+  }
 }
 
 class EguiBuilder {
   private components: EguiComponent[] = [];
 
-  addWindow(title: string): WindowBuilder {
+  addWindow(title: string): ContainerBuilder {
     const windowBuilder = new WindowBuilder(title);
     this.components.push(windowBuilder);
     return windowBuilder;
   }
 
+  addNativeWindow(id: string, title: string, options: EguiAPI.NativeWindowOptions = {}): NativeWindowBuilder {
+    const windowBuilder = new NativeWindowBuilder(id, title, options);
+    this.components.push(windowBuilder);
+    return windowBuilder;
+  }
+
   add3DScene(id: string): Scene3DBuilder {
     return new Scene3DBuilder(id);
   }
 
+  setMenuBar(menus: EguiAPI.MenuItem[]): EguiBuilder {
+    EguiAPI.setMenuBar(menus).catch(console.error);
+    return this;
+  }
+
+  /** Opens `items` when the widget `target` is right-clicked. */
+  setContextMenu(target: string, items: EguiAPI.MenuItem[]): EguiBuilder {
+    EguiAPI.setContextMenu(target, items).catch(console.error);
+    return this;
+  }
+
+  registerShortcut(command: string, keys: string): EguiBuilder {
+    EguiAPI.registerShortcut(command, keys).catch(console.error);
+    return this;
+  }
+
+  /** Runs `handler` when `command` is picked from a menu or toolbar or its shortcut is pressed. */
+  onCommand(command: string, handler: (data: { source: string; target?: string; checked?: boolean }) => void): EguiBuilder {
+    EguiAPI.onEvent("command", (data: { id: string; source: string; target?: string; checked?: boolean }) => {
+      if (data.id === command) {
+        handler(data);
+      }
+    });
+    return this;
+  }
+
   async build(): Promise<void> {
     console.log("UI build is complete");
   }
 }
 
-class WindowBuilder extends EguiComponent {
-  constructor(title: string) {
-    super("window");
-    this.initialize(title);
-  }
-
-  private initialize(title: string) {
-    EguiAPI.addWindow(title).catch(console.error);
+/** Something widgets can be added to. Child widgets are parented to `parentId`. */
+abstract class ContainerBuilder extends EguiComponent {
+  /** `undefined` places widgets in the main window. */
+  get parentId(): string | undefined {
+    return this.id;
   }
 
   addLabel(id: string): LabelBuilder {
@@ -40,6 +102,12 @@ class WindowBuilder extends EguiComponent {
     return new ButtonBuilder(id, label, this);
   }
 
+  /** Shows the image `image` loaded with `EguiAPI.loadImage`. */
+  addImage(id: string, image: string, options: EguiAPI.ImageOptions = {}): ContainerBuilder {
+    EguiAPI.setImage(id, image, { parent: this.parentId, ...options }).catch(console.error);
+    return this;
+  }
+
   addSlider(id: string, range: [number, number]): SliderBuilder {
     return new SliderBuilder(id, range, this);
   }
@@ -64,92 +132,426 @@ class WindowBuilder extends EguiComponent {
     return new ProgressBarBuilder(id, this);
   }
 
+  addTable(id: string, columns: (string | EguiAPI.TableColumn)[]): TableBuilder {
+    return new TableBuilder(id, columns, this);
+  }
+
+  addPlot(id: string, options: EguiAPI.PlotOptions = {}): PlotBuilder {
+    return new PlotBuilder(id, options, this);
+  }
+
+  addTree(id: string, nodes: EguiAPI.TreeNode[], options: EguiAPI.TreeOptions = {}): TreeBuilder {
+    return new TreeBuilder(id, nodes, options, this);
+  }
+
+  addColorEdit(id: string, options: EguiAPI.ColorEditOptions = {}): ValueEditorBuilder<EguiAPI.Rgba> {
+    EguiAPI.setColorEdit(id, { parent: this.parentId, ...options }).catch(console.error);
+    return new ValueEditorBuilder(id, "color_change", (data) => data.color, this);
+  }
+
+  addDragValue(id: string, value: number, options: EguiAPI.DragValueOptions = {}): ValueEditorBuilder<number> {
+    EguiAPI.setDragValue(id, value, { parent: this.parentId, ...options }).catch(console.error);
+    return new ValueEditorBuilder(id, "drag_value_change", (data) => data.value, this);
+  }
+
+  addVector(id: string, value: number[], options: EguiAPI.VectorOptions = {}): ValueEditorBuilder<number[]> {
+    EguiAPI.setVector(id, value, { parent: this.parentId, ...options }).catch(console.error);
+    return new ValueEditorBuilder(id, "vector_change", (data) => data.value, this);
+  }
+
+  addDateTime(id: string, value: string, withTime = false): ValueEditorBuilder<string> {
+    EguiAPI.setDateTime(id, value, { with_time: withTime, parent: this.parentId }).catch(console.error);
+    return new ValueEditorBuilder(id, "datetime_change", (data) => data.value, this);
+  }
+
+  /** A row of icon buttons; clicks are reported as "command" events. */
+  addToolbar(id: string, items: EguiAPI.MenuItem[]): ContainerBuilder {
+    EguiAPI.setToolbar(id, items, this.parentId).catch(console.error);
+    return this;
+  }
+
+  /**
+   * The recorded events on a zoomable time axis. Events can be selected,
+   * dragged in time, duplicated and deleted; the ruler is a scrubber.
+   */
+  addTimeline(id: string, options: EguiAPI.TimelineOptions = {}): TimelineBuilder {
+    return new TimelineBuilder(id, options, this);
+  }
+
+  /** A tree listing the objects of every 3D scene; clicking one selects it. */
+  addOutliner(id: string): TreeBuilder {
+    return new TreeBuilder(id, [], { outliner: true }, this);
+  }
+
   add3DScene(id: string): Scene3DBuilder {
     return new Scene3DBuilder(id);
   }
+
+  addPanel(id: string, side: EguiAPI.PanelSide = "left"): LayoutBuilder {
+    return new LayoutBuilder(id, { kind: "panel", side }, this);
+  }
+
+  addHorizontal(id: string): LayoutBuilder {
+    return new LayoutBuilder(id, { kind: "horizontal" }, this);
+  }
+
+  addVertical(id: string): LayoutBuilder {
+    return new LayoutBuilder(id, { kind: "vertical" }, this);
+  }
+
+  addGrid(id: string, columns: number, striped = false): LayoutBuilder {
+    return new LayoutBuilder(id, { kind: "grid", columns, striped }, this);
+  }
+
+  addCollapsing(id: string, title: string, defaultOpen = false): LayoutBuilder {
+    return new LayoutBuilder(id, { kind: "collapsing", default_open: defaultOpen }, this, title);
+  }
+
+  addScrollArea(id: string, maxHeight?: number): LayoutBuilder {
+    return new LayoutBuilder(id, { kind: "scroll_area", max_height: maxHeight }, this);
+  }
+
+  /** Each child added to the tabs container becomes one tab. */
+  addTabs(id: string): LayoutBuilder {
+    return new LayoutBuilder(id, { kind: "tabs" }, this);
+  }
+}
+
+/** A floating egui window inside the main window. */
+class WindowBuilder extends ContainerBuilder {
+  constructor(id: string, title: string) {
+    super(id);
+    this.initialize(title);
+  }
+
+  private initialize(title: string) {
+    EguiAPI.addWindow(this.id, title).catch(console.error);
+  }
+
+  onClose(handler: () => void): WindowBuilder {
+    EguiAPI.onEvent("container_close", (data: { id: string }) => {
+      if (data.id === this.id) {
+        handler();
+      }
+    });
+    return this;
+  }
+}
+
+/** A panel, layout, collapsing header, scroll area or tab set. */
+class LayoutBuilder extends ContainerBuilder {
+  constructor(
+    id: string,
+    container: EguiAPI.ContainerKind,
+    private parent: ContainerBuilder,
+    title?: string,
+  ) {
+    super(id);
+    EguiAPI.setContainer(id, container, { title, parent: parent.parentId }).catch(console.error);
+  }
+
+  /** Returns to the enclosing container. */
+  end(): ContainerBuilder {
+    return this.parent;
+  }
+}
+
+/** A separate OS window with its own widgets. */
+class NativeWindowBuilder extends ContainerBuilder {
+  constructor(id: string, title: string, options: EguiAPI.NativeWindowOptions) {
+    super(id);
+    EguiAPI.openWindow(id, title, options).catch(console.error);
+  }
+
+  setTitle(title: string): NativeWindowBuilder {
+    EguiAPI.setWindowTitle(this.id, title).catch(console.error);
+    return this;
+  }
+
+  setSize(width: number, height: number): NativeWindowBuilder {
+    EguiAPI.setWindowSize(this.id, width, height).catch(console.error);
+    return this;
+  }
+
+  setPosition(x: number, y: number): NativeWindowBuilder {
+    EguiAPI.setWindowPosition(this.id, x, y).catch(console.error);
+    return this;
+  }
+
+  close(): void {
+    EguiAPI.closeWindow(this.id).catch(console.error);
+  }
+
+  private onWindowEvent<T>(type: string, handler: (data: T) => void): NativeWindowBuilder {
+    EguiAPI.onEvent(type, (data: T & { id: string }) => {
+      if (data.id === this.id) {
+        handler(data);
+      }
+    });
+    return this;
+  }
+
+  onClose(handler: () => void): NativeWindowBuilder {
+    return this.onWindowEvent("window_close", handler);
+  }
+
+  onFocus(handler: (focused: boolean) => void): NativeWindowBuilder {
+    return this.onWindowEvent("window_focus", (data: { focused: boolean }) => handler(data.focused));
+  }
+
+  onMove(handler: (x: number, y: number) => void): NativeWindowBuilder {
+    return this.onWindowEvent("window_move", (data: { x: number; y: number }) => handler(data.x, data.y));
+  }
+
+  onResize(handler: (width: number, height: number) => void): NativeWindowBuilder {
+    return this.onWindowEvent("window_resize", (data: { width: number; height: number }) =>
+      handler(data.width, data.height));
+  }
 }
 
 class LabelBuilder extends EguiComponent {
-  constructor(id: string, private builder: WindowBuilder) {
+  constructor(id: string, private builder: ContainerBuilder) {
     super(id);
   }
 
-  setText(text: string): WindowBuilder {
-    EguiAPI.setLabel(text).catch(console.error);
+  setText(text: string): ContainerBuilder {
+    EguiAPI.setLabel(text, this.id, { parent: this.builder.parentId }).catch(console.error);
+    return this.builder;
+  }
+
+  setRichText(spans: EguiAPI.TextSpan[]): LabelBuilder {
+    EguiAPI.setLabel("", this.id, { spans, parent: this.builder.parentId }).catch(console.error);
+    return this;
+  }
+
+  setMarkdown(text: string): LabelBuilder {
+    EguiAPI.setLabel(text, this.id, { markdown: true, parent: this.builder.parentId }).catch(console.error);
+    return this;
+  }
+
+  /** Shows the catalog message `key`, updated whenever the locale changes. */
+  setMessage(key: string, args: Record<string, string | number> = {}, markdown = false): ContainerBuilder {
+    EguiAPI.setLabel("", this.id, { message: { key, args }, markdown, parent: this.builder.parentId })
+      .catch(console.error);
+    return this.builder;
+  }
+
+  onLink(handler: (link: string) => void): ContainerBuilder {
+    EguiAPI.onEvent("label_link", (data: { id: string; link: string }) => {
+      if (data.id === this.id) {
+        handler(data.link);
+      }
+    });
     return this.builder;
   }
 }
 
 class ButtonBuilder extends EguiComponent {
-  constructor(id: string, private label: string, private builder: WindowBuilder) {
+  private enabled = true;
+  private tooltip?: string;
+  private icon?: string;
+  private message?: EguiAPI.Message;
+
+  constructor(id: string, private label: string, private builder: ContainerBuilder) {
     super(id);
-    this.initialize();
+    this.update();
   }
 
-  private initialize() {
-    EguiAPI.setButton(this.id, this.label).catch(console.error);
+  private update() {
+    EguiAPI.setButton(this.id, this.label, {
+      enabled: this.enabled,
+      tooltip: this.tooltip,
+      icon: this.icon,
+      message: this.message,
+      parent: this.builder.parentId,
+    }).catch(console.error);
+  }
+
+  setLabel(label: string): ButtonBuilder {
+    this.label = label;
+    this.message = undefined;
+    this.update();
+    return this;
   }
 
-  onClick(handler: () => void): WindowBuilder {
-    EguiAPI.onEvent("button_click", () => {
-      handler();
+  /** Translates the label from the catalog message `key` instead. */
+  setMessage(key: string, args: Record<string, string | number> = {}): ButtonBuilder {
+    this.message = { key, args };
+    this.update();
+    return this;
+  }
+
+  setEnabled(enabled: boolean): ButtonBuilder {
+    this.enabled = enabled;
+    this.update();
+    return this;
+  }
+
+  setTooltip(tooltip: string): ButtonBuilder {
+    this.tooltip = tooltip;
+    this.update();
+    return this;
+  }
+
+  setIcon(icon: string): ButtonBuilder {
+    this.icon = icon;
+    this.update();
+    return this;
+  }
+
+  onClick(handler: () => void): ContainerBuilder {
+    EguiAPI.onEvent("button_click", (data: { id: string }) => {
+      if (data.id === this.id) {
+        handler();
+      }
     });
     return this.builder;
   }
 }
 
 class SliderBuilder extends EguiComponent {
-  constructor(id: string, private range: [number, number], private builder: WindowBuilder) {
+  private value: number;
+  private options: EguiAPI.SliderOptions;
+
+  constructor(id: string, range: [number, number], private builder: ContainerBuilder) {
     super(id);
-    this.initialize();
+    this.value = range[0];
+    this.options = { min: range[0], max: range[1], parent: builder.parentId };
+    this.update();
   }
 
-  private initialize() {
-    EguiAPI.setSlider(this.range[0]).catch(console.error);
+  private update() {
+    EguiAPI.setSlider(this.value, this.id, this.options).catch(console.error);
+  }
+
+  private configure(options: EguiAPI.SliderOptions): SliderBuilder {
+    this.options = { ...this.options, ...options };
+    this.update();
+    return this;
   }
 
   setValue(value: number): SliderBuilder {
-    EguiAPI.setSlider(value).catch(console.error);
+    this.value = value;
+    this.update();
     return this;
   }
 
-  onChange(handler: (value: number) => void): WindowBuilder {
-    EguiAPI.onEvent("slider_change", (data: { value: number }) => {
-      handler(data.value);
+  setRange(min: number, max: number): SliderBuilder {
+    return this.configure({ min, max });
+  }
+
+  setStep(step: number): SliderBuilder {
+    return this.configure({ step });
+  }
+
+  setLogarithmic(logarithmic = true): SliderBuilder {
+    return this.configure({ logarithmic });
+  }
+
+  setInteger(integer = true): SliderBuilder {
+    return this.configure({ integer });
+  }
+
+  setPrefix(prefix: string): SliderBuilder {
+    return this.configure({ prefix });
+  }
+
+  setSuffix(suffix: string): SliderBuilder {
+    return this.configure({ suffix });
+  }
+
+  setText(text: string): SliderBuilder {
+    return this.configure({ text });
+  }
+
+  onChange(handler: (value: number) => void): ContainerBuilder {
+    EguiAPI.onEvent("slider_change", (data: { id: string; value: number }) => {
+      if (data.id === this.id) {
+        this.value = data.value;
+        handler(data.value);
+      }
     });
     return this.builder;
   }
 }
 
 class InputBuilder extends EguiComponent {
-  constructor(id: string, private builder: WindowBuilder) {
+  private text = "";
+  private options: EguiAPI.InputOptions;
+
+  constructor(id: string, private builder: ContainerBuilder) {
     super(id);
+    this.options = { parent: builder.parentId };
+    this.update();
+  }
+
+  private update() {
+    EguiAPI.setInput(this.text, this.id, this.options).catch(console.error);
+  }
+
+  private configure(options: EguiAPI.InputOptions): InputBuilder {
+    this.options = { ...this.options, ...options };
+    this.update();
+    return this;
   }
 
   setText(text: string): InputBuilder {
-    EguiAPI.setInput(text).catch(console.error);
+    this.text = text;
+    this.update();
     return this;
   }
 
-  onInput(handler: (text: string) => void): WindowBuilder {
-    EguiAPI.onEvent("input_change", (data: { text: string }) => {
-      handler(data.text);
+  setMultiline(multiline = true): InputBuilder {
+    return this.configure({ multiline });
+  }
+
+  setPassword(password = true): InputBuilder {
+    return this.configure({ password });
+  }
+
+  setHint(hint: string): InputBuilder {
+    return this.configure({ hint });
+  }
+
+  setMaxLength(maxLength: number): InputBuilder {
+    return this.configure({ max_length: maxLength });
+  }
+
+  setValidation(validation: EguiAPI.InputValidation): InputBuilder {
+    return this.configure({ validation });
+  }
+
+  onInput(handler: (text: string, valid: boolean) => void): ContainerBuilder {
+    EguiAPI.onEvent("input_change", (data: { id: string; text: string; valid: boolean }) => {
+      if (data.id === this.id) {
+        this.text = data.text;
+        handler(data.text, data.valid);
+      }
     });
     return this.builder;
   }
+
+  onInvalid(handler: (error: string, text: string) => void): InputBuilder {
+    EguiAPI.onEvent("input_invalid", (data: { id: string; text: string; error: string }) => {
+      if (data.id === this.id) {
+        handler(data.error, data.text);
+      }
+    });
+    return this;
+  }
 }
 
 class CheckboxBuilder extends EguiComponent {
-  constructor(id: string, private builder: WindowBuilder) {
+  constructor(id: string, private builder: ContainerBuilder) {
     super(id);
   }
 
   setChecked(checked: boolean): CheckboxBuilder {
-    EguiAPI.setCheckbox(this.id, checked).catch(console.error);
+    EguiAPI.setCheckbox(this.id, checked, this.builder.parentId).catch(console.error);
     return this;
   }
 
-  onToggle(handler: (checked: boolean) => void): WindowBuilder {
+  onToggle(handler: (checked: boolean) => void): ContainerBuilder {
     EguiAPI.onEvent(`checkbox_${this.id}`, (data: { checked: boolean }) => {
       handler(data.checked);
     });
@@ -158,21 +560,21 @@ class CheckboxBuilder extends EguiComponent {
 }
 
 class ComboBoxBuilder extends EguiComponent {
-  constructor(id: string, private options: string[], private builder: WindowBuilder) {
+  constructor(id: string, private options: string[], private builder: ContainerBuilder) {
     super(id);
     this.initialize();
   }
 
   private initialize() {
-    EguiAPI.setComboBox(this.id, this.options[0], this.options).catch(console.error);
+    EguiAPI.setComboBox(this.id, this.options[0], this.options, this.builder.parentId).catch(console.error);
   }
 
   setSelected(selected: string): ComboBoxBuilder {
-    EguiAPI.setComboBox(this.id, selected, this.options).catch(console.error);
+    EguiAPI.setComboBox(this.id, selected, this.options, this.builder.parentId).catch(console.error);
     return this;
   }
 
-  onChange(handler: (selected: string) => void): WindowBuilder {
+  onChange(handler: (selected: string) => void): ContainerBuilder {
     EguiAPI.onEvent(`combo_${this.id}`, (data: { selected: string }) => {
       handler(data.selected);
     });
@@ -181,21 +583,21 @@ class ComboBoxBuilder extends EguiComponent {
 }
 
 class RadioGroupBuilder extends EguiComponent {
-  constructor(id: string, private options: string[], private builder: WindowBuilder) {
+  constructor(id: string, private options: string[], private builder: ContainerBuilder) {
     super(id);
     this.initialize();
   }
 
   private initialize() {
-    EguiAPI.setRadio(this.id, this.options[0]).catch(console.error);
+    EguiAPI.setRadio(this.id, this.options[0], this.options, this.builder.parentId).catch(console.error);
   }
 
   setSelected(selected: string): RadioGroupBuilder {
-    EguiAPI.setRadio(this.id, selected).catch(console.error);
+    EguiAPI.setRadio(this.id, selected, this.options, this.builder.parentId).catch(console.error);
     return this;
   }
 
-  onChange(handler: (selected: string) => void): WindowBuilder {
+  onChange(handler: (selected: string) => void): ContainerBuilder {
     EguiAPI.onEvent(`radio_${this.id}`, (data: { selected: string }) => {
       handler(data.selected);
     });
@@ -204,16 +606,16 @@ class RadioGroupBuilder extends EguiComponent {
 }
 
 class ProgressBarBuilder extends EguiComponent {
-  constructor(id: string, private builder: WindowBuilder) {
+  constructor(id: string, private builder: ContainerBuilder) {
     super(id);
   }
 
   setProgress(value: number): ProgressBarBuilder {
-    EguiAPI.setProgress(this.id, value).catch(console.error);
+    EguiAPI.setProgress(this.id, value, this.builder.parentId).catch(console.error);
     return this;
   }
 
-  onUpdate(handler: (value: number) => void): WindowBuilder {
+  onUpdate(handler: (value: number) => void): ContainerBuilder {
     EguiAPI.onEvent(`progress_${this.id}`, (data: { value: number }) => {
       handler(data.value);
     });
@@ -221,6 +623,213 @@ class ProgressBarBuilder extends EguiComponent {
   }
 }
 
+class TableBuilder extends EguiComponent {
+  private columns: EguiAPI.TableColumn[];
+  private rows: EguiAPI.TableCell[][] = [];
+  private options: EguiAPI.TableOptions;
+
+  constructor(id: string, columns: (string | EguiAPI.TableColumn)[], private builder: ContainerBuilder) {
+    super(id);
+    this.columns = columns.map((column) => typeof column === "string" ? { name: column } : column);
+    this.options = { parent: builder.parentId };
+    this.update();
+  }
+
+  private update() {
+    EguiAPI.setTable(this.id, this.columns, this.rows, this.options).catch(console.error);
+  }
+
+  setRows(rows: EguiAPI.TableCell[][]): TableBuilder {
+    this.rows = rows;
+    this.update();
+    return this;
+  }
+
+  /** Adds rows without resending the ones already in the table. */
+  appendRows(rows: EguiAPI.TableCell[][]): TableBuilder {
+    this.rows.push(...rows);
+    EguiAPI.appendTableRows(this.id, rows).catch(console.error);
+    return this;
+  }
+
+  setSelection(selection: "none" | "single" | "multi"): TableBuilder {
+    this.options = { ...this.options, selection };
+    this.update();
+    return this;
+  }
+
+  setFilterable(filterable: boolean): TableBuilder {
+    this.options = { ...this.options, filterable };
+    this.update();
+    return this;
+  }
+
+  /** Lets rows be dragged onto drop targets; they arrive as the row index. */
+  setDraggable(draggable: boolean): TableBuilder {
+    this.options = { ...this.options, draggable };
+    this.update();
+    return this;
+  }
+
+  /** Accepts items dragged out of the widgets in `sources`; `"*"` accepts any. */
+  acceptDrops(sources: string[], handler: (source: string, item: string, over: string | null) => void): TableBuilder {
+    EguiAPI.setDropTarget(this.id, sources).catch(console.error);
+    EguiAPI.onItemDrop((data) => {
+      if (data.id === this.id) {
+        handler(data.source, data.item, data.over);
+      }
+    });
+    return this;
+  }
+
+  /** `selected` holds indices into the rows as given, independent of sorting. */
+  onSelect(handler: (selected: number[], rows: EguiAPI.TableCell[][]) => void): ContainerBuilder {
+    EguiAPI.onEvent("table_select", (data: { id: string; selected: number[]; rows: EguiAPI.TableCell[][] }) => {
+      if (data.id === this.id) {
+        handler(data.selected, data.rows);
+      }
+    });
+    return this.builder;
+  }
+}
+
+class PlotBuilder extends EguiComponent {
+  private ready: Promise<void>;
+
+  constructor(id: string, options: EguiAPI.PlotOptions, private builder: ContainerBuilder) {
+    super(id);
+    this.ready = EguiAPI.setPlot(id, { parent: builder.parentId, ...options }).catch(console.error);
+  }
+
+  addSeries(name: string, points: EguiAPI.PlotSample[] = [], options: EguiAPI.SeriesOptions = {}): PlotBuilder {
+    this.ready = this.ready
+      .then(() => EguiAPI.setPlotSeries(this.id, name, points, options))
+      .catch(console.error);
+    return this;
+  }
+
+  /** Streams samples into an existing series. */
+  append(series: string, points: EguiAPI.PlotSample[]): PlotBuilder {
+    this.ready = this.ready
+      .then(() => EguiAPI.appendPlotPoints(this.id, series, points))
+      .catch(console.error);
+    return this;
+  }
+
+  onClick(handler: (x: number, y: number) => void): ContainerBuilder {
+    EguiAPI.onEvent("plot_click", (data: { id: string; x: number; y: number }) => {
+      if (data.id === this.id) {
+        handler(data.x, data.y);
+      }
+    });
+    return this.builder;
+  }
+}
+
+class TreeBuilder extends EguiComponent {
+  constructor(
+    id: string,
+    nodes: EguiAPI.TreeNode[],
+    options: EguiAPI.TreeOptions,
+    private builder: ContainerBuilder,
+  ) {
+    super(id);
+    EguiAPI.setTree(id, nodes, { parent: builder.parentId, ...options }).catch(console.error);
+  }
+
+  private on<T extends { id: string }>(event: string, handler: (data: T) => void): TreeBuilder {
+    EguiAPI.onEvent(event, (data: T) => {
+      if (data.id === this.id) {
+        handler(data);
+      }
+    });
+    return this;
+  }
+
+  onSelect(handler: (selected: string[]) => void): TreeBuilder {
+    return this.on("tree_select", (data: { id: string; selected: string[] }) => handler(data.selected));
+  }
+
+  onExpand(handler: (node: string, expanded: boolean) => void): TreeBuilder {
+    return this.on("tree_expand", (data: { id: string; node: string; expanded: boolean }) =>
+      handler(data.node, data.expanded));
+  }
+
+  /** Supplies the children of lazy nodes when they are first expanded. */
+  onLoad(loader: (node: string) => EguiAPI.TreeNode[] | Promise<EguiAPI.TreeNode[]>): TreeBuilder {
+    return this.on("tree_load", async (data: { id: string; node: string }) => {
+      const children = await loader(data.node);
+      await EguiAPI.setTreeChildren(this.id, data.node, children);
+    });
+  }
+
+  onReparent(handler: (node: string, parent: string) => void): TreeBuilder {
+    return this.on("tree_reparent", (data: { id: string; node: string; parent: string }) =>
+      handler(data.node, data.parent));
+  }
+
+  /** Accepts items dragged out of the widgets in `sources`; `"*"` accepts any. */
+  acceptDrops(sources: string[], handler: (source: string, item: string, over: string | null) => void): TreeBuilder {
+    EguiAPI.setDropTarget(this.id, sources).catch(console.error);
+    return this.on("item_drop", (data: EguiAPI.ItemDropEvent) => handler(data.source, data.item, data.over));
+  }
+
+  end(): ContainerBuilder {
+    return this.builder;
+  }
+}
+
+class TimelineBuilder extends EguiComponent {
+  constructor(id: string, options: EguiAPI.TimelineOptions, private builder: ContainerBuilder) {
+    super(id);
+    EguiAPI.setTimeline(id, { parent: builder.parentId, ...options }).catch(console.error);
+  }
+
+  private on<T extends { id: string }>(event: string, handler: (data: T) => void): TimelineBuilder {
+    EguiAPI.onEvent(event, (data: T) => {
+      if (data.id === this.id) {
+        handler(data);
+      }
+    });
+    return this;
+  }
+
+  /** `selected` holds indices into the recorded events. */
+  onSelect(handler: (selected: number[]) => void): TimelineBuilder {
+    return this.on("timeline_select", (data: { id: string; selected: number[] }) => handler(data.selected));
+  }
+
+  /** Called with the time in ms when the scrubber is moved. */
+  onSeek(handler: (timestamp: number) => void): TimelineBuilder {
+    return this.on("timeline_seek", (data: { id: string; timestamp: number }) => handler(data.timestamp));
+  }
+
+  end(): ContainerBuilder {
+    return this.builder;
+  }
+}
+
+/** Color edits, drag values, vectors and date pickers: one value, one change event. */
+class ValueEditorBuilder<T> extends EguiComponent {
+  constructor(
+    id: string,
+    private event: string,
+    private read: (data: any) => T,
+    private builder: ContainerBuilder,
+  ) {
+    super(id);
+  }
+
+  onChange(handler: (value: T) => void): ContainerBuilder {
+    EguiAPI.onEvent(this.event, (data: { id: string }) => {
+      if (data.id === this.id) {
+        handler(this.read(data));
+      }
+    });
+    return this.builder;
+  }
+}
+
 class Scene3DBuilder extends EguiComponent {
   constructor(id: string) {
     super(id);
@@ -241,6 +850,31 @@ class Scene3DBuilder extends EguiComponent {
     return this;
   }
 
+  setObjectColor(objectId: string, color: EguiAPI.Rgba): Scene3DBuilder {
+    EguiAPI.setObjectColor(objectId, color).catch(console.error);
+    return this;
+  }
+
+  selectObject(objectId: string | null): Scene3DBuilder {
+    EguiAPI.selectObject(objectId).catch(console.error);
+    return this;
+  }
+
+  /** Opens `items` when the object is right-clicked in an outliner, or in the 3D viewport while it is selected. */
+  setObjectContextMenu(objectId: string, items: EguiAPI.MenuItem[]): Scene3DBuilder {
+    EguiAPI.setContextMenu(objectId, items).catch(console.error);
+    return this;
+  }
+
+  onObjectSelect(handler: (objectId: string) => void): Scene3DBuilder {
+    EguiAPI.onEvent("object_select", (data: { id: string; scene_id: string }) => {
+      if (data.scene_id === this.id) {
+        handler(data.id);
+      }
+    });
+    return this;
+  }
+
   onRotate(handler: (angle: number) => void): Scene3DBuilder {
     EguiAPI.onEvent("rotate_3d", (data: { angle: number }) => {
       handler(data.angle);
@@ -266,6 +900,21 @@ class Scene3DBuilder extends EguiComponent {
     EguiAPI.stopPlayback().catch(console.error);
     return this;
   }
+
+  pausePlayback(): Scene3DBuilder {
+    EguiAPI.pausePlayback().catch(console.error);
+    return this;
+  }
+
+  resumePlayback(): Scene3DBuilder {
+    EguiAPI.resumePlayback().catch(console.error);
+    return this;
+  }
+
+  onPlaybackProgress(handler: (status: EguiAPI.PlaybackStatus) => void): Scene3DBuilder {
+    EguiAPI.onPlaybackProgress(handler);
+    return this;
+  }
 }
 
 // Export the fluent interface
@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU64;
+
+use egui::accesskit::{self, NodeId, Role, TreeUpdate};
+use egui_winit::accesskit_winit::ActionRequestEvent;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::automation::TrackedWidget;
+use crate::EguiApp;
+
+/// Events sent to the winit event loop from outside of it.
+#[derive(Debug)]
+pub enum UserEvent {
+    /// A screen reader asked a widget to do something, e.g. to click it.
+    AccessKit(ActionRequestEvent),
+}
+
+impl From<ActionRequestEvent> for UserEvent {
+    fn from(event: ActionRequestEvent) -> Self {
+        UserEvent::AccessKit(event)
+    }
+}
+
+/// Roles scripts can give widgets, by name.
+const ROLES: &[(&str, Role)] = &[
+    ("alert", Role::Alert),
+    ("button", Role::Button),
+    ("cell", Role::Cell),
+    ("checkbox", Role::CheckBox),
+    ("color_well", Role::ColorWell),
+    ("column_header", Role::ColumnHeader),
+    ("dialog", Role::Dialog),
+    ("group", Role::Group),
+    ("heading", Role::Heading),
+    ("image", Role::Image),
+    ("link", Role::Link),
+    ("list", Role::List),
+    ("list_item", Role::ListItem),
+    ("menu", Role::Menu),
+    ("menu_bar", Role::MenuBar),
+    ("menu_item", Role::MenuItem),
+    ("paragraph", Role::Paragraph),
+    ("popup_button", Role::PopupButton),
+    ("progress_indicator", Role::ProgressIndicator),
+    ("radio_button", Role::RadioButton),
+    ("row", Role::Row),
+    ("slider", Role::Slider),
+    ("spin_button", Role::SpinButton),
+    ("static_text", Role::StaticText),
+    ("status", Role::Status),
+    ("tab", Role::Tab),
+    ("tab_list", Role::TabList),
+    ("table", Role::Table),
+    ("text_field", Role::TextField),
+    ("toggle_button", Role::ToggleButton),
+    ("toolbar", Role::Toolbar),
+    ("tree", Role::Tree),
+    ("tree_item", Role::TreeItem),
+    ("unknown", Role::Unknown),
+    ("window", Role::Window),
+];
+
+pub fn parse_role(name: &str) -> Result<Role, String> {
+    ROLES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, role)| *role)
+        .ok_or_else(|| format!("Unknown role '{}'", name))
+}
+
+/// The name of `role` as accepted by [`parse_role`], or its AccessKit name
+/// for roles scripts cannot set.
+pub fn role_name(role: Role) -> String {
+    ROLES
+        .iter()
+        .find(|(_, known)| *known == role)
+        .map_or_else(|| format!("{:?}", role), |(name, _)| name.to_string())
+}
+
+/// What assistive technology is told about a widget, replacing what egui
+/// derives from the widget itself.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AccessInfo {
+    pub label: Option<String>,
+    pub description: Option<String>,
+    /// A name from [`parse_role`].
+    pub role: Option<String>,
+}
+
+/// The AccessKit node egui creates for the widget with `id`. egui keeps the
+/// conversion private, but node ids are the raw value of the egui id, which
+/// is what `Id` feeds to a hasher.
+fn node_id(id: egui::Id) -> Option<NodeId> {
+    #[derive(Default)]
+    struct RawId(u64);
+
+    impl Hasher for RawId {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0 = (self.0 << 8) | u64::from(*byte);
+            }
+        }
+
+        fn write_u64(&mut self, value: u64) {
+            self.0 = value;
+        }
+    }
+
+    let mut raw = RawId::default();
+    id.hash(&mut raw);
+    NonZeroU64::new(raw.0).map(NodeId::from)
+}
+
+/// The accessibility tree of `update` as nested JSON nodes with `role`,
+/// `name`, `description`, `value`, `bounds` and `children`. Nodes of widgets
+/// of `ctx` tracked in `widgets` also carry their widget `id`.
+pub fn tree_json(update: &TreeUpdate, ctx: &egui::Context, widgets: &BTreeMap<String, TrackedWidget>) -> serde_json::Value {
+    let nodes: HashMap<NodeId, &accesskit::Node> = update.nodes.iter().map(|(id, node)| (*id, node)).collect();
+    let ids: HashMap<NodeId, &str> = widgets
+        .iter()
+        .filter(|(_, widget)| widget.response.ctx == *ctx)
+        .filter_map(|(id, widget)| Some((node_id(widget.response.id)?, id.as_str())))
+        .collect();
+
+    fn node_json(id: NodeId, nodes: &HashMap<NodeId, &accesskit::Node>, ids: &HashMap<NodeId, &str>) -> serde_json::Value {
+        let Some(node) = nodes.get(&id) else { return serde_json::Value::Null };
+        let children: Vec<_> = node.children().iter().map(|child| node_json(*child, nodes, ids)).collect();
+        serde_json::json!({
+            "id": ids.get(&id),
+            "role": role_name(node.role()),
+            "name": node.name(),
+            "description": node.description(),
+            "value": node.value(),
+            "bounds": node.bounds().map(|rect| [rect.x0, rect.y0, rect.x1, rect.y1]),
+            "children": children,
+        })
+    }
+
+    match &update.tree {
+        Some(tree) => node_json(tree.root, &nodes, &ids),
+        None => serde_json::Value::Null,
+    }
+}
+
+impl EguiApp {
+    /// Applies the accessibility info scripts set to the widgets of `ctx`.
+    /// Does nothing unless AccessKit is enabled for `ctx`.
+    pub(crate) fn accessibility_ui(&self, ctx: &egui::Context) {
+        for (id, info) in &self.accessibility {
+            let Some(widget) = self.widgets.get(id).filter(|widget| widget.response.ctx == *ctx) else { continue };
+            let role = info.role.as_deref().and_then(|role| parse_role(role).ok());
+            ctx.accesskit_node_builder(widget.response.id, |builder| {
+                if let Some(label) = &info.label {
+                    builder.set_name(label.as_str());
+                }
+                if let Some(description) = &info.description {
+                    builder.set_description(description.as_str());
+                }
+                if let Some(role) = role {
+                    builder.set_role(role);
+                }
+            });
+        }
+    }
+
+    /// Queues a request for the accessibility tree of the next frame.
+    pub fn request_accessibility_tree(&mut self) -> oneshot::Receiver<serde_json::Value> {
+        let (reply, answer) = oneshot::channel();
+        self.accessibility_requests.push(reply);
+        answer
+    }
+
+    /// Answers the pending requests with the tree `ctx` output this frame.
+    pub fn answer_accessibility_requests(&mut self, ctx: &egui::Context, update: Option<&TreeUpdate>) {
+        if self.accessibility_requests.is_empty() {
+            return;
+        }
+        let tree = update.map_or(serde_json::Value::Null, |update| tree_json(update, ctx, &self.widgets));
+        for reply in self.accessibility_requests.drain(..) {
+            let _ = reply.send(tree.clone());
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use crate::EguiApp;
+
+/// How many frames a step waits for its target widget to appear before the
+/// action is abandoned.
+const MAX_WAIT_FRAMES: u32 = 30;
+
+/// A widget laid out in the last frame, as seen by the automation driver.
+#[derive(Debug, Clone)]
+pub struct TrackedWidget {
+    pub rect: egui::Rect,
+    pub kind: WidgetKind,
+    pub response: egui::Response,
+}
+
+#[derive(Debug, Clone)]
+pub enum WidgetKind {
+    Label,
+    Button,
+    Slider {
+        rail: egui::Rect,
+        range: RangeInclusive<f64>,
+        logarithmic: bool,
+    },
+    TextInput,
+    Checkbox,
+    ComboBox,
+    ComboOption,
+    Radio,
+    ProgressBar,
+    Container,
+    Tab,
+    TableHeader,
+    TableRow,
+    Plot,
+    TreeNode,
+    ColorEdit,
+    DragValue,
+    DatePicker,
+    /// Opens a submenu.
+    Menu,
+    MenuItem,
+    Image,
+    /// An event on a timeline.
+    TimelineEvent,
+}
+
+/// The current value of the widget `id`, as reported to scripts.
+pub fn widget_value(app: &EguiApp, id: &str) -> Option<serde_json::Value> {
+    let value = match id {
+        "label" => serde_json::json!(app.label_text),
+        "slider" => serde_json::json!(app.slider_value),
+        "input" => serde_json::json!(app.input_text),
+        _ => {
+            if let Some(label) = app.labels.get(id) {
+                serde_json::json!(label.plain_text())
+            } else if let Some(view) = app.image_views.get(id) {
+                serde_json::json!({ "image": view.image, "loaded": app.images.contains_key(&view.image) })
+            } else if let Some(button) = app.buttons.get(id) {
+                serde_json::json!({ "label": button.text(&app.i18n), "enabled": button.enabled })
+            } else if let Some(slider) = app.sliders.get(id) {
+                serde_json::json!(slider.value)
+            } else if let Some(input) = app.text_inputs.get(id) {
+                serde_json::json!({ "text": input.text, "error": input.error })
+            } else if let Some(table) = app.tables.get(id) {
+                serde_json::json!({ "rows": table.rows.len(), "selected": table.selected })
+            } else if let Some(plot) = app.plots.get(id) {
+                let series: serde_json::Map<_, _> = plot.series.iter()
+                    .map(|(name, series)| (name.clone(), serde_json::json!(series.points.len())))
+                    .collect();
+                serde_json::json!({ "series": series })
+            } else if let Some(tree) = app.tree_views.get(id) {
+                serde_json::json!({ "selected": tree.selected, "expanded": tree.expanded })
+            } else if let Some(edit) = app.color_edits.get(id) {
+                serde_json::json!(edit.color)
+            } else if let Some(drag) = app.drag_values.get(id) {
+                serde_json::json!(drag.value)
+            } else if let Some(vector) = app.vectors.get(id) {
+                serde_json::json!(vector.values)
+            } else if let Some(picker) = app.date_times.get(id) {
+                serde_json::json!(picker.value())
+            } else if let Some(timeline) = app.timelines.get(id) {
+                serde_json::json!({ "events": app.recorded_events.len(), "selected": timeline.selected, "cursor": timeline.cursor })
+            } else if let Some(items) = app.toolbars.get(id) {
+                let toggles: serde_json::Map<_, _> = items.iter()
+                    .filter_map(|item| Some((item.id.clone(), serde_json::json!(item.checked?))))
+                    .collect();
+                serde_json::json!({ "checked": toggles })
+            } else if let Some(checked) = app.checkboxes.get(id) {
+                serde_json::json!(checked)
+            } else if let Some((selected, _)) = app.combo_boxes.get(id) {
+                serde_json::json!(selected)
+            } else if let Some((selected, _)) = app.radio_groups.get(id) {
+                serde_json::json!(selected)
+            } else {
+                serde_json::json!(app.progress_bars.get(id)?)
+            }
+        }
+    };
+    Some(value)
+}
+
+/// A user-level interaction. Ids address widgets tracked in
+/// `EguiApp::widgets`; entries of combo boxes and radio groups are addressed
+/// as `"<id>/<option>"`, table rows and headers as `"<id>/row/<index>"` and
+/// `"<id>/column/<name>"`, tree nodes and toolbar buttons as `"<id>/<node>"`,
+/// and menu entries as `"menu/<command>"`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    Click { id: String },
+    TypeText { id: String, text: String },
+    DragSlider { id: String, value: f64 },
+    SelectCombo { id: String, option: String },
+    SelectRadio { id: String, option: String },
+    PressKey { key: String, #[serde(default)] command: bool },
+    /// Right-clicks `id`, e.g. to open its context menu.
+    ContextClick { id: String },
+    /// Presses on `from`, moves to `to` and releases there.
+    Drag { from: String, to: String },
+    /// Drops files from the OS on `id`.
+    DropFiles { id: String, paths: Vec<String> },
+    /// Selects all text of `id` and copies it.
+    Copy { id: String },
+    /// Pastes `text` into `id` as if it came from the clipboard.
+    Paste { id: String, text: String },
+}
+
+const ALL_KEYS: &[egui::Key] = {
+    use egui::Key::*;
+    &[
+        ArrowDown, ArrowLeft, ArrowRight, ArrowUp, Escape, Tab, Backspace, Enter, Space, Insert,
+        Delete, Home, End, PageUp, PageDown, Minus, PlusEquals, Num0, Num1, Num2, Num3, Num4,
+        Num5, Num6, Num7, Num8, Num9, A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S,
+        T, U, V, W, X, Y, Z, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15,
+        F16, F17, F18, F19, F20,
+    ]
+};
+
+/// Looks up a key by its egui name (`"Enter"`, `"A"`, `"F5"`, `"Down"`), ignoring case.
+pub fn key_from_name(name: &str) -> Option<egui::Key> {
+    ALL_KEYS.iter().copied().find(|key| key.name().eq_ignore_ascii_case(name))
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    Center(String),
+    SliderValue(String, f64),
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Move(Target),
+    Press(Target, egui::PointerButton),
+    Release(Target, egui::PointerButton),
+    Key(egui::Key, egui::Modifiers),
+    Text(String),
+    DropFiles(Target, Vec<std::path::PathBuf>),
+    Event(egui::Event),
+}
+
+fn click_steps(id: &str) -> [Step; 3] {
+    button_steps(id, egui::PointerButton::Primary)
+}
+
+fn button_steps(id: &str, button: egui::PointerButton) -> [Step; 3] {
+    let target = Target::Center(id.to_string());
+    [
+        Step::Move(target.clone()),
+        Step::Press(target.clone(), button),
+        Step::Release(target, button),
+    ]
+}
+
+/// Turns queued [`Action`]s into synthetic egui input events, one step per frame.
+#[derive(Default)]
+pub struct Automation {
+    queue: VecDeque<Action>,
+    steps: VecDeque<Step>,
+    waited_frames: u32,
+    pub errors: Vec<String>,
+}
+
+impl Automation {
+    pub fn push(&mut self, action: Action) {
+        self.queue.push_back(action);
+    }
+
+    /// True when no action is queued or in progress.
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty() && self.steps.is_empty()
+    }
+
+    fn plan(action: Action) -> Result<Vec<Step>, String> {
+        let steps = match action {
+            Action::Click { id } => click_steps(&id).to_vec(),
+            Action::TypeText { id, text } => {
+                let mut steps = click_steps(&id).to_vec();
+                steps.push(Step::Key(egui::Key::A, egui::Modifiers::COMMAND));
+                steps.push(Step::Text(text));
+                steps
+            }
+            Action::DragSlider { id, value } => {
+                let target = Target::SliderValue(id, value);
+                vec![
+                    Step::Move(target.clone()),
+                    Step::Press(target.clone(), egui::PointerButton::Primary),
+                    Step::Release(target, egui::PointerButton::Primary),
+                ]
+            }
+            Action::SelectCombo { id, option } => {
+                let mut steps = click_steps(&id).to_vec();
+                steps.extend(click_steps(&format!("{id}/{option}")));
+                steps
+            }
+            Action::SelectRadio { id, option } => click_steps(&format!("{id}/{option}")).to_vec(),
+            Action::Drag { from, to } => {
+                let (from, to) = (Target::Center(from), Target::Center(to));
+                vec![
+                    Step::Move(from.clone()),
+                    Step::Press(from, egui::PointerButton::Primary),
+                    Step::Move(to.clone()),
+                    Step::Release(to, egui::PointerButton::Primary),
+                ]
+            }
+            Action::PressKey { key, command } => {
+                let key = key_from_name(&key).ok_or_else(|| format!("Unknown key '{key}'"))?;
+                let modifiers = if command { egui::Modifiers::COMMAND } else { egui::Modifiers::NONE };
+                vec![Step::Key(key, modifiers)]
+            }
+            Action::ContextClick { id } => button_steps(&id, egui::PointerButton::Secondary).to_vec(),
+            Action::Copy { id } => {
+                let mut steps = click_steps(&id).to_vec();
+                steps.push(Step::Key(egui::Key::A, egui::Modifiers::COMMAND));
+                steps.push(Step::Event(egui::Event::Copy));
+                steps
+            }
+            Action::Paste { id, text } => {
+                let mut steps = click_steps(&id).to_vec();
+                steps.push(Step::Event(egui::Event::Paste(text)));
+                steps
+            }
+            Action::DropFiles { id, paths } => {
+                vec![Step::DropFiles(Target::Center(id), paths.into_iter().map(Into::into).collect())]
+            }
+        };
+        Ok(steps)
+    }
+
+    fn resolve(widgets: &BTreeMap<String, TrackedWidget>, target: &Target) -> Option<egui::Pos2> {
+        match target {
+            Target::Center(id) => widgets.get(id).map(|w| w.rect.center()),
+            Target::SliderValue(id, value) => match &widgets.get(id)?.kind {
+                WidgetKind::Slider { rail, range, logarithmic } => {
+                    // Mirrors the handle inset egui applies to the slider rail.
+                    let handle_radius = rail.height() / 2.5;
+                    let left = rail.left() + handle_radius;
+                    let right = rail.right() - handle_radius;
+                    // Logarithmic sliders are only mapped exactly for positive ranges.
+                    let (start, end, value) = if *logarithmic && *range.start() > 0.0 && *value > 0.0 {
+                        (range.start().log10(), range.end().log10(), value.log10())
+                    } else {
+                        (*range.start(), *range.end(), *value)
+                    };
+                    let span = end - start;
+                    let t = if span == 0.0 { 0.0 } else { ((value - start) / span).clamp(0.0, 1.0) };
+                    Some(egui::pos2(left + (right - left) * t as f32, rail.center().y))
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Appends the events of the next step to `raw_input`. `widgets` is the
+    /// widget layout of the previous frame.
+    pub fn inject(&mut self, widgets: &BTreeMap<String, TrackedWidget>, raw_input: &mut egui::RawInput) {
+        if self.steps.is_empty() {
+            let Some(action) = self.queue.pop_front() else { return };
+            match Self::plan(action) {
+                Ok(steps) => self.steps.extend(steps),
+                Err(e) => {
+                    self.errors.push(e);
+                    return;
+                }
+            }
+        }
+
+        let Some(step) = self.steps.front() else { return };
+        let pointer = |target: &Target| Self::resolve(widgets, target);
+        let event = match step {
+            Step::Move(target) => pointer(target).map(egui::Event::PointerMoved),
+            Step::Press(target, button) | Step::Release(target, button) => pointer(target).map(|pos| egui::Event::PointerButton {
+                pos,
+                button: *button,
+                pressed: matches!(step, Step::Press(..)),
+                modifiers: egui::Modifiers::NONE,
+            }),
+            Step::Key(key, modifiers) => {
+                raw_input.events.push(egui::Event::Key {
+                    key: *key,
+                    pressed: true,
+                    repeat: false,
+                    modifiers: *modifiers,
+                });
+                Some(egui::Event::Key {
+                    key: *key,
+                    pressed: false,
+                    repeat: false,
+                    modifiers: *modifiers,
+                })
+            }
+            Step::Text(text) => Some(egui::Event::Text(text.clone())),
+            Step::Event(event) => Some(event.clone()),
+            Step::DropFiles(target, paths) => pointer(target).map(|pos| {
+                raw_input.dropped_files.extend(paths.iter().map(|path| egui::DroppedFile {
+                    path: Some(path.clone()),
+                    ..Default::default()
+                }));
+                egui::Event::PointerMoved(pos)
+            }),
+        };
+
+        match event {
+            Some(event) => {
+                raw_input.events.push(event);
+                self.steps.pop_front();
+                self.waited_frames = 0;
+            }
+            None if self.waited_frames < MAX_WAIT_FRAMES => self.waited_frames += 1,
+            None => {
+                self.errors.push(format!("Widget for step {:?} never appeared", step));
+                self.steps.clear();
+                self.waited_frames = 0;
+            }
+        }
+    }
+}
+
+/// Drives an [`EguiApp`] frame by frame without a window or GPU, for
+/// integration tests.
+pub struct Driver {
+    pub app: EguiApp,
+    pub ctx: egui::Context,
+    pub screen_size: egui::Vec2,
+    events: UnboundedReceiver<String>,
+    emitted: Vec<serde_json::Value>,
+    frame: u64,
+}
+
+impl Driver {
+    pub fn new(app: EguiApp) -> Self {
+        let (tx, rx) = unbounded_channel();
+        let mut driver = Self {
+            app: EguiApp { event_sender: tx, ..app },
+            ctx: egui::Context::default(),
+            screen_size: egui::vec2(1024.0, 768.0),
+            events: rx,
+            emitted: Vec::new(),
+            frame: 0,
+        };
+        // Lay out once so widgets can be found.
+        driver.step();
+        driver
+    }
+
+    /// Runs one frame, injecting the next automation step.
+    pub fn step(&mut self) {
+        self.run_frame();
+    }
+
+    /// Runs one frame with AccessKit enabled and returns the accessibility
+    /// tree as JSON, see [`crate::accessibility::tree_json`].
+    pub fn accessibility_tree(&mut self) -> serde_json::Value {
+        self.ctx.enable_accesskit();
+        let output = self.run_frame();
+        output.platform_output.accesskit_update.map_or(serde_json::Value::Null, |update| {
+            crate::accessibility::tree_json(&update, &self.ctx, &self.app.widgets)
+        })
+    }
+
+    fn run_frame(&mut self) -> egui::FullOutput {
+        let mut raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, self.screen_size)),
+            pixels_per_point: Some(1.0),
+            time: Some(self.frame as f64 / 60.0),
+            predicted_dt: 1.0 / 60.0,
+            focused: true,
+            ..Default::default()
+        };
+
+        let app = &mut self.app;
+        app.automation.inject(&app.widgets, &mut raw_input);
+
+        self.ctx.begin_frame(raw_input);
+        app.ui(&self.ctx);
+        let output = self.ctx.end_frame();
+        self.frame += 1;
+
+        while let Ok(message) = self.events.try_recv() {
+            if let Ok(value) = serde_json::from_str(&message) {
+                self.emitted.push(value);
+            }
+        }
+        output
+    }
+
+    /// Queues `action` and runs frames until it has been performed.
+    pub fn perform(&mut self, action: Action) -> &mut Self {
+        self.app.automation.push(action);
+        while !self.app.automation.is_idle() {
+            self.step();
+        }
+        // One more frame so the UI reflects the last input.
+        self.step();
+        let errors = std::mem::take(&mut self.app.automation.errors);
+        if !errors.is_empty() {
+            panic!("Automation failed: {}", errors.join("; "));
+        }
+        self
+    }
+
+    pub fn click(&mut self, id: &str) -> &mut Self {
+        self.perform(Action::Click { id: id.to_string() })
+    }
+
+    pub fn type_text(&mut self, id: &str, text: &str) -> &mut Self {
+        self.perform(Action::TypeText { id: id.to_string(), text: text.to_string() })
+    }
+
+    pub fn drag_slider(&mut self, id: &str, value: f64) -> &mut Self {
+        self.perform(Action::DragSlider { id: id.to_string(), value })
+    }
+
+    pub fn select_combo(&mut self, id: &str, option: &str) -> &mut Self {
+        self.perform(Action::SelectCombo { id: id.to_string(), option: option.to_string() })
+    }
+
+    pub fn select_radio(&mut self, id: &str, option: &str) -> &mut Self {
+        self.perform(Action::SelectRadio { id: id.to_string(), option: option.to_string() })
+    }
+
+    pub fn drag(&mut self, from: &str, to: &str) -> &mut Self {
+        self.perform(Action::Drag { from: from.to_string(), to: to.to_string() })
+    }
+
+    pub fn drop_files(&mut self, id: &str, paths: &[&str]) -> &mut Self {
+        let paths = paths.iter().map(|path| path.to_string()).collect();
+        self.perform(Action::DropFiles { id: id.to_string(), paths })
+    }
+
+    pub fn copy(&mut self, id: &str) -> &mut Self {
+        self.perform(Action::Copy { id: id.to_string() })
+    }
+
+    pub fn paste(&mut self, id: &str, text: &str) -> &mut Self {
+        self.perform(Action::Paste { id: id.to_string(), text: text.to_string() })
+    }
+
+    pub fn context_click(&mut self, id: &str) -> &mut Self {
+        self.perform(Action::ContextClick { id: id.to_string() })
+    }
+
+    /// Runs up to `max_frames` frames until `condition` holds.
+    pub fn wait_until(&mut self, max_frames: u32, condition: impl Fn(&EguiApp) -> bool) -> bool {
+        for _ in 0..max_frames {
+            if condition(&self.app) {
+                return true;
+            }
+            self.step();
+        }
+        condition(&self.app)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&TrackedWidget> {
+        self.app.widgets.get(id)
+    }
+
+    /// Events sent to the script side since the last call.
+    pub fn take_events(&mut self) -> Vec<serde_json::Value> {
+        std::mem::take(&mut self.emitted)
+    }
+}
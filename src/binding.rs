@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::persistence::{restore_widget_state, widget_state};
+use crate::EguiApp;
+
+/// Named values shared by scripts and the widgets bound to them. Widgets
+/// take the value of their observable, and edits made in a widget become
+/// the value of its observable. Changes are reported once per frame.
+#[derive(Debug, Clone, Default)]
+pub struct Bindings {
+    /// Observables keyed by name.
+    pub values: BTreeMap<String, Value>,
+    /// Name of the observable each bound widget shows, keyed by widget id.
+    pub widgets: BTreeMap<String, String>,
+    /// Widgets that do not show the value of their observable yet.
+    stale: BTreeSet<String>,
+    /// Observables changed this frame, with the widget that changed them.
+    changes: BTreeMap<String, Option<String>>,
+}
+
+impl Bindings {
+    /// Sets observable `name`, declaring it if needed. Bound widgets are
+    /// updated on the next frame.
+    pub fn set(&mut self, name: &str, value: Value) {
+        if self.values.get(name).is_some_and(|current| same_value(current, &value)) {
+            return;
+        }
+        self.values.insert(name.to_string(), value);
+        self.changes.insert(name.to_string(), None);
+        self.mark_stale(name, None);
+    }
+
+    /// Declares observable `name` with `value` unless it exists, and returns
+    /// its value.
+    pub fn declare(&mut self, name: &str, value: Value) -> Value {
+        if let Some(current) = self.values.get(name) {
+            return current.clone();
+        }
+        self.set(name, value.clone());
+        value
+    }
+
+    /// Binds `widget` to observable `name`, or unbinds it.
+    pub fn bind(&mut self, widget: &str, name: Option<&str>) -> Result<(), String> {
+        match name {
+            Some(name) => {
+                if !self.values.contains_key(name) {
+                    return Err(format!("No observable named '{}'", name));
+                }
+                self.widgets.insert(widget.to_string(), name.to_string());
+                self.stale.insert(widget.to_string());
+            }
+            None => {
+                self.widgets.remove(widget);
+                self.stale.remove(widget);
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the widgets bound to `name`, except `source`, for an update.
+    fn mark_stale(&mut self, name: &str, source: Option<&str>) {
+        let widgets = self.widgets.iter().filter(|(widget, bound)| *bound == name && Some(widget.as_str()) != source);
+        self.stale.extend(widgets.map(|(widget, _)| widget.clone()));
+    }
+}
+
+/// Whether `a` and `b` are the same value, comparing numbers by value so
+/// that `50` equals `50.0`. Widgets keep numbers as `f32`, so numbers that
+/// round to the same `f32` are equal too.
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a == b || a as f32 == b as f32,
+            _ => a == b,
+        },
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_value(a, b)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| same_value(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Shows `value` in `widget`. Labels show it as text and progress bars as
+/// their progress; other widgets take it as their [`widget_state`]. Returns
+/// `false` if the widget does not exist yet.
+fn show_value(app: &mut EguiApp, widget: &str, value: &Value) -> Result<bool, String> {
+    if let Some(label) = app.labels.get_mut(widget) {
+        match value {
+            Value::String(text) => label.set_text(text),
+            other => label.set_text(&other.to_string()),
+        }
+    } else if let Some(progress) = app.progress_bars.get_mut(widget) {
+        *progress = value.as_f64().ok_or_else(|| format!("Progress bar '{}' needs a number, got {}", widget, value))? as f32;
+    } else if widget_state(app, widget).is_some() {
+        restore_widget_state(app, widget, value)?;
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+impl EguiApp {
+    /// Shows the values scripts wrote since the last frame in the widgets
+    /// bound to them.
+    pub(crate) fn apply_bindings(&mut self) {
+        for widget in std::mem::take(&mut self.bindings.stale) {
+            let Some(value) = self.bindings.widgets.get(&widget).and_then(|name| self.bindings.values.get(name)).cloned() else {
+                continue;
+            };
+            match show_value(self, &widget, &value) {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.bindings.stale.insert(widget);
+                }
+                Err(e) => self.emit("binding_error", &widget, serde_json::json!({ "message": e })),
+            }
+        }
+    }
+
+    /// Takes edits made in bound widgets this frame into their observables
+    /// and reports every observable that changed as one `observable_change`
+    /// event, carrying the widget the change came from, if any.
+    pub(crate) fn sync_bindings(&mut self) {
+        let edits: Vec<(String, String, Value)> = self
+            .bindings
+            .widgets
+            .iter()
+            .filter(|(widget, _)| !self.bindings.stale.contains(*widget))
+            .filter_map(|(widget, name)| {
+                let state = widget_state(self, widget)?;
+                let current = self.bindings.values.get(name);
+                (!current.is_some_and(|current| same_value(current, &state))).then(|| (widget.clone(), name.clone(), state))
+            })
+            .collect();
+        for (widget, name, value) in edits {
+            self.bindings.values.insert(name.clone(), value);
+            self.bindings.mark_stale(&name, Some(&widget));
+            self.bindings.changes.insert(name, Some(widget));
+        }
+
+        for (name, source) in std::mem::take(&mut self.bindings.changes) {
+            let value = self.bindings.values.get(&name).cloned().unwrap_or_default();
+            self.emit("observable_change", &name, serde_json::json!({ "value": value, "source": source }));
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use winit::event_loop::EventLoopWindowTarget;
+
+use crate::EguiApp;
+
+/// An image on the clipboard, as rows of RGBA8 pixels.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ClipboardImage {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+impl ClipboardImage {
+    pub fn check(&self) -> Result<(), String> {
+        let len = self
+            .width
+            .checked_mul(self.height)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| format!("A {}x{} image is too large", self.width, self.height))?;
+        if self.rgba.len() != len {
+            return Err(format!(
+                "A {}x{} image needs {} RGBA bytes, got {}",
+                self.width,
+                self.height,
+                len,
+                self.rgba.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ClipboardAccess {
+    GetText,
+    SetText(String),
+    GetImage,
+    SetImage(ClipboardImage),
+}
+
+/// Clipboard access requested by a script. The event loop serves it, since
+/// the clipboard is bound to the display connection of the main thread.
+#[derive(Debug)]
+pub struct ClipboardRequest {
+    pub access: ClipboardAccess,
+    pub reply: oneshot::Sender<Result<serde_json::Value, String>>,
+}
+
+/// The OS clipboard. Text goes through the egui-winit integration, which
+/// also handles Wayland; images go through arboard, which it is built on.
+pub struct Clipboard {
+    text: egui_winit::clipboard::Clipboard,
+    images: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn new<T>(target: &EventLoopWindowTarget<T>) -> Self {
+        let images = arboard::Clipboard::new()
+            .map_err(|e| eprintln!("No clipboard for images: {}", e))
+            .ok();
+        Self { text: egui_winit::clipboard::Clipboard::new(target), images }
+    }
+
+    fn access(&mut self, access: ClipboardAccess) -> Result<serde_json::Value, String> {
+        match access {
+            ClipboardAccess::GetText => Ok(self.text.get().into()),
+            ClipboardAccess::SetText(text) => {
+                self.text.set(text);
+                Ok(serde_json::Value::Null)
+            }
+            ClipboardAccess::GetImage => {
+                let images = self.images.as_mut().ok_or("No clipboard for images")?;
+                match images.get_image() {
+                    Ok(image) => {
+                        let image = ClipboardImage { width: image.width, height: image.height, rgba: image.bytes.into_owned() };
+                        serde_json::to_value(image).map_err(|e| e.to_string())
+                    }
+                    Err(arboard::Error::ContentNotAvailable) => Ok(serde_json::Value::Null),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            ClipboardAccess::SetImage(image) => {
+                image.check()?;
+                let images = self.images.as_mut().ok_or("No clipboard for images")?;
+                let image = arboard::ImageData { width: image.width, height: image.height, bytes: image.rgba.into() };
+                images.set_image(image).map_err(|e| e.to_string())?;
+                Ok(serde_json::Value::Null)
+            }
+        }
+    }
+
+    pub fn serve(&mut self, request: ClipboardRequest) {
+        let _ = request.reply.send(self.access(request.access));
+    }
+}
+
+impl EguiApp {
+    /// Queues a clipboard access for the event loop.
+    pub fn request_clipboard(&mut self, access: ClipboardAccess) -> oneshot::Receiver<Result<serde_json::Value, String>> {
+        let (reply, answer) = oneshot::channel();
+        self.clipboard_requests.push(ClipboardRequest { access, reply });
+        answer
+    }
+
+    /// Reports copy, cut and paste shortcuts used in widgets of `window`.
+    pub(crate) fn clipboard_ui(&mut self, ctx: &egui::Context, window: &str) {
+        let (copied, cut, pasted) = ctx.input(|i| {
+            let copied = i.events.iter().any(|event| matches!(event, egui::Event::Copy | egui::Event::Cut));
+            let cut = i.events.iter().any(|event| matches!(event, egui::Event::Cut));
+            let pasted = i.events.iter().find_map(|event| match event {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            });
+            (copied, cut, pasted)
+        });
+        if !copied && pasted.is_none() {
+            return;
+        }
+
+        let focus = ctx.memory(|memory| memory.focus());
+        let widget = self
+            .widgets
+            .iter()
+            .find(|(_, widget)| widget.response.ctx == *ctx && Some(widget.response.id) == focus)
+            .map(|(id, _)| id.clone());
+        let text = ctx.output(|output| output.copied_text.clone());
+        if copied && !text.is_empty() {
+            self.emit("copy", window, serde_json::json!({ "widget": widget, "text": text, "cut": cut }));
+        }
+        if let Some(text) = pasted {
+            self.emit("paste", window, serde_json::json!({ "widget": widget, "text": text }));
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+
+use crate::automation::WidgetKind;
+use crate::{default_true, track, EguiApp};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelSide {
+    #[default]
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Layout containers other widgets can be parented to.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ContainerKind {
+    /// A floating `egui::Window`.
+    Window {
+        #[serde(default = "default_true")]
+        movable: bool,
+        #[serde(default = "default_true")]
+        collapsible: bool,
+        #[serde(default = "default_true")]
+        resizable: bool,
+        /// Shows a close button; closing emits `container_close`.
+        #[serde(default)]
+        closable: bool,
+        #[serde(default = "default_true")]
+        open: bool,
+        #[serde(default)]
+        default_pos: Option<[f32; 2]>,
+        #[serde(default)]
+        default_size: Option<[f32; 2]>,
+    },
+    Panel {
+        #[serde(default)]
+        side: PanelSide,
+        #[serde(default = "default_true")]
+        resizable: bool,
+        /// Width for left/right panels, height for top/bottom panels.
+        #[serde(default)]
+        default_size: Option<f32>,
+    },
+    Horizontal,
+    Vertical,
+    Grid {
+        columns: usize,
+        #[serde(default)]
+        striped: bool,
+    },
+    Collapsing {
+        #[serde(default)]
+        default_open: bool,
+    },
+    ScrollArea {
+        #[serde(default)]
+        horizontal: bool,
+        #[serde(default = "default_true")]
+        vertical: bool,
+        #[serde(default)]
+        max_height: Option<f32>,
+    },
+    /// Shows one child at a time, with a tab per child.
+    Tabs {
+        #[serde(default)]
+        selected: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Container {
+    /// Window title, collapsing header text, or tab label.
+    pub title: String,
+    pub kind: ContainerKind,
+}
+
+impl EguiApp {
+    fn is_root_panel(&self, id: &str) -> bool {
+        matches!(
+            self.containers.get(id),
+            Some(Container { kind: ContainerKind::Panel { .. }, .. })
+        )
+    }
+
+    /// Builds the UI of a native window: panels attached to `root` first,
+    /// then a central panel with `add_contents` followed by the other children.
+    pub(crate) fn root_ui(
+        &mut self,
+        ctx: &egui::Context,
+        root: &str,
+        add_contents: impl FnOnce(&mut Self, &mut egui::Ui),
+    ) {
+        let (panels, others): (Vec<String>, Vec<String>) = self
+            .tree
+            .children(root)
+            .iter()
+            .cloned()
+            .partition(|id| self.is_root_panel(id));
+
+        self.menu_bar_ui(ctx, root);
+        for id in &panels {
+            self.panel_ui(PanelHost::Context(ctx), id);
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            add_contents(self, ui);
+            for id in &others {
+                self.widget_ui(ui, id);
+            }
+            if root == crate::tree::MAIN_WINDOW {
+                // The 3D scene shows through the rest of the main window.
+                // Created last so that widgets get their clicks first.
+                let id = ui.id().with(crate::drag_drop::VIEWPORT);
+                let viewport = ui.interact(ui.max_rect(), id, egui::Sense::click());
+                self.viewport_context_menu_ui(&viewport);
+            }
+        });
+    }
+
+    fn tab_label(&self, id: &str) -> String {
+        self.containers
+            .get(id)
+            .map_or_else(|| id.to_string(), |container| container.title.clone())
+    }
+
+    /// Shows container `id` and its children inside `ui`.
+    pub(crate) fn container_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(Container { title, kind }) = self.containers.get(id).cloned() else { return };
+
+        match kind {
+            ContainerKind::Window {
+                movable,
+                collapsible,
+                resizable,
+                closable,
+                mut open,
+                default_pos,
+                default_size,
+            } => {
+                if !open {
+                    return;
+                }
+                let mut window = egui::Window::new(title)
+                    .id(egui::Id::new(("container", id)))
+                    .movable(movable)
+                    .collapsible(collapsible)
+                    .resizable(resizable);
+                if let Some([x, y]) = default_pos {
+                    window = window.default_pos([x, y]);
+                }
+                if let Some([width, height]) = default_size {
+                    window = window.default_size([width, height]);
+                }
+                if closable {
+                    window = window.open(&mut open);
+                }
+                let response = window.show(ui.ctx(), |ui| self.children_ui(ui, id));
+                if let Some(response) = response {
+                    track(&mut self.widgets, id, &response.response, WidgetKind::Container);
+                }
+                if !open {
+                    if let Some(Container { kind: ContainerKind::Window { open: stored, .. }, .. }) =
+                        self.containers.get_mut(id)
+                    {
+                        *stored = false;
+                    }
+                    self.emit("container_close", id, serde_json::json!({}));
+                }
+            }
+            ContainerKind::Panel { .. } => self.panel_ui(PanelHost::Ui(ui), id),
+            ContainerKind::Horizontal => {
+                let response = ui.horizontal(|ui| self.children_ui(ui, id)).response;
+                track(&mut self.widgets, id, &response, WidgetKind::Container);
+            }
+            ContainerKind::Vertical => {
+                let response = ui.vertical(|ui| self.children_ui(ui, id)).response;
+                track(&mut self.widgets, id, &response, WidgetKind::Container);
+            }
+            ContainerKind::Grid { columns, striped } => {
+                let columns = columns.max(1);
+                let children = self.tree.children(id).to_vec();
+                let response = egui::Grid::new(("container", id))
+                    .num_columns(columns)
+                    .striped(striped)
+                    .show(ui, |ui| {
+                        for (index, child) in children.iter().enumerate() {
+                            self.widget_ui(ui, child);
+                            if (index + 1) % columns == 0 {
+                                ui.end_row();
+                            }
+                        }
+                    })
+                    .response;
+                track(&mut self.widgets, id, &response, WidgetKind::Container);
+            }
+            ContainerKind::Collapsing { default_open } => {
+                let response = egui::CollapsingHeader::new(title)
+                    .id_source(("container", id))
+                    .default_open(default_open)
+                    .show(ui, |ui| self.children_ui(ui, id));
+                track(&mut self.widgets, id, &response.header_response, WidgetKind::Container);
+            }
+            ContainerKind::ScrollArea { horizontal, vertical, max_height } => {
+                let mut area = egui::ScrollArea::new([horizontal, vertical]).id_source(("container", id));
+                if let Some(max_height) = max_height {
+                    area = area.max_height(max_height);
+                }
+                let output = area.show(ui, |ui| self.children_ui(ui, id));
+                // Scroll areas have no response of their own; hover sense leaves clicks to the children.
+                let response = ui.interact(output.inner_rect, output.id.with("container"), egui::Sense::hover());
+                track(&mut self.widgets, id, &response, WidgetKind::Container);
+            }
+            ContainerKind::Tabs { selected } => {
+                let children = self.tree.children(id).to_vec();
+                let current = selected
+                    .filter(|selected| children.contains(selected))
+                    .or_else(|| children.first().cloned());
+
+                let labels: Vec<String> = children.iter().map(|child| self.tab_label(child)).collect();
+                let mut clicked = None;
+                ui.horizontal(|ui| {
+                    for (child, label) in children.iter().zip(labels) {
+                        let is_selected = current.as_deref() == Some(child.as_str());
+                        let response = ui.selectable_label(is_selected, label);
+                        track(&mut self.widgets, format!("{id}/{child}"), &response, WidgetKind::Tab);
+                        if response.clicked() && !is_selected {
+                            clicked = Some(child.clone());
+                        }
+                    }
+                });
+                ui.separator();
+
+                let current = clicked.clone().or(current);
+                if let Some(Container { kind: ContainerKind::Tabs { selected }, .. }) = self.containers.get_mut(id) {
+                    selected.clone_from(&current);
+                }
+                if let Some(clicked) = clicked {
+                    self.emit("tab_change", id, serde_json::json!({ "selected": clicked }));
+                }
+                if let Some(current) = current {
+                    self.widget_ui(ui, &current);
+                }
+            }
+        }
+    }
+
+    fn panel_ui(&mut self, host: PanelHost<'_>, id: &str) {
+        let Some(Container { kind: ContainerKind::Panel { side, resizable, default_size }, .. }) =
+            self.containers.get(id).cloned()
+        else {
+            return;
+        };
+
+        let panel_id = egui::Id::new(("container", id));
+        let add_contents = |ui: &mut egui::Ui| self.children_ui(ui, id);
+        let response = match side {
+            PanelSide::Left | PanelSide::Right => {
+                let mut panel = if side == PanelSide::Left {
+                    egui::SidePanel::left(panel_id)
+                } else {
+                    egui::SidePanel::right(panel_id)
+                }
+                .resizable(resizable);
+                if let Some(width) = default_size {
+                    panel = panel.default_width(width);
+                }
+                match host {
+                    PanelHost::Context(ctx) => panel.show(ctx, add_contents).response,
+                    PanelHost::Ui(ui) => panel.show_inside(ui, add_contents).response,
+                }
+            }
+            PanelSide::Top | PanelSide::Bottom => {
+                let mut panel = if side == PanelSide::Top {
+                    egui::TopBottomPanel::top(panel_id)
+                } else {
+                    egui::TopBottomPanel::bottom(panel_id)
+                }
+                .resizable(resizable);
+                if let Some(height) = default_size {
+                    panel = panel.default_height(height);
+                }
+                match host {
+                    PanelHost::Context(ctx) => panel.show(ctx, add_contents).response,
+                    PanelHost::Ui(ui) => panel.show_inside(ui, add_contents).response,
+                }
+            }
+        };
+        track(&mut self.widgets, id, &response, WidgetKind::Container);
+    }
+}
+
+/// Panels attached to a native window are shown on the context; nested ones
+/// inside their parent's `Ui`.
+enum PanelHost<'a> {
+    Context(&'a egui::Context),
+    Ui(&'a mut egui::Ui),
+}
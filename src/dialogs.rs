@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::automation::{TrackedWidget, WidgetKind};
+use crate::{track, EguiApp};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FileMode {
+    #[default]
+    Open,
+    Save,
+}
+
+/// Browses the local filesystem for a file to open or a path to save to.
+#[derive(Debug, Clone)]
+pub struct FileBrowser {
+    pub mode: FileMode,
+    pub dir: PathBuf,
+    /// Names in `dir` and whether they are directories, directories first.
+    pub entries: Vec<(String, bool)>,
+    pub file_name: String,
+    /// Extensions without the dot. Empty shows every file.
+    pub extensions: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl FileBrowser {
+    /// Starts in `dir`, or in the working directory.
+    pub fn new(mode: FileMode, dir: Option<PathBuf>, file_name: String, extensions: Vec<String>) -> Self {
+        let dir = dir.or_else(|| std::env::current_dir().ok()).unwrap_or_else(|| PathBuf::from("."));
+        let mut browser = Self { mode, dir: dir.clone(), entries: Vec::new(), file_name, extensions, error: None };
+        browser.open_dir(dir);
+        browser
+    }
+
+    pub fn open_dir(&mut self, dir: PathBuf) {
+        let read = match std::fs::read_dir(&dir) {
+            Ok(read) => read,
+            Err(e) => {
+                self.error = Some(format!("Cannot open {}: {}", dir.display(), e));
+                return;
+            }
+        };
+        let mut entries: Vec<(String, bool)> = read
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let is_dir = entry.file_type().ok()?.is_dir();
+                let name = entry.file_name().into_string().ok()?;
+                (is_dir || self.accepts(&name)).then_some((name, is_dir))
+            })
+            .collect();
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase())));
+        self.entries = entries;
+        self.dir = dir;
+        self.error = None;
+    }
+
+    fn accepts(&self, name: &str) -> bool {
+        self.extensions.is_empty()
+            || Path::new(name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| self.extensions.iter().any(|wanted| wanted.eq_ignore_ascii_case(ext)))
+    }
+
+    /// The path the dialog would return, if it can be confirmed.
+    pub fn chosen(&self) -> Option<PathBuf> {
+        if self.file_name.is_empty() {
+            return None;
+        }
+        let path = self.dir.join(&self.file_name);
+        match self.mode {
+            FileMode::Open => path.is_file().then_some(path),
+            FileMode::Save => Some(path),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DialogKind {
+    /// Answers with the label of the clicked button.
+    Confirm { buttons: Vec<String> },
+    /// Answers with the entered text.
+    Prompt { text: String, hint: String },
+    /// Answers with the chosen path.
+    File(FileBrowser),
+}
+
+/// A modal dialog. Only the oldest open dialog is shown; the others queue
+/// behind it. Closing it with Escape or Cancel answers `null`.
+#[derive(Debug)]
+pub struct Dialog {
+    pub title: String,
+    pub message: String,
+    pub kind: DialogKind,
+    reply: Option<oneshot::Sender<Value>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ToastLevel {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    fn icon(self) -> (&'static str, egui::Color32) {
+        match self {
+            ToastLevel::Info => ("ℹ", egui::Color32::LIGHT_BLUE),
+            ToastLevel::Success => ("✔", egui::Color32::LIGHT_GREEN),
+            ToastLevel::Warning => ("⚠", egui::Color32::GOLD),
+            ToastLevel::Error => ("✖", egui::Color32::LIGHT_RED),
+        }
+    }
+}
+
+/// A notification in the bottom right corner. It answers `{ dismissed }`
+/// when clicked away or when `duration` seconds have passed; a duration of 0
+/// keeps it until it is clicked.
+#[derive(Debug)]
+pub struct Toast {
+    pub id: u64,
+    pub message: String,
+    pub level: ToastLevel,
+    pub duration: f64,
+    /// egui time of the first frame the toast was shown in.
+    pub shown_at: Option<f64>,
+    reply: Option<oneshot::Sender<Value>>,
+}
+
+fn confirm_ui(ui: &mut egui::Ui, buttons: &[String], widgets: &mut BTreeMap<String, TrackedWidget>) -> Option<Value> {
+    let mut answer = None;
+    ui.horizontal(|ui| {
+        for label in buttons {
+            let response = ui.button(label.as_str());
+            track(widgets, format!("dialog/button/{label}"), &response, WidgetKind::Button);
+            if response.clicked() {
+                answer = Some(serde_json::json!(label));
+            }
+        }
+    });
+    answer
+}
+
+fn ok_cancel_ui(ui: &mut egui::Ui, ok: &str, enabled: bool, widgets: &mut BTreeMap<String, TrackedWidget>) -> (bool, bool) {
+    ui.horizontal(|ui| {
+        let response = ui.add_enabled(enabled, egui::Button::new(ok));
+        track(widgets, "dialog/ok", &response, WidgetKind::Button);
+        let ok = response.clicked();
+        let response = ui.button("Cancel");
+        track(widgets, "dialog/cancel", &response, WidgetKind::Button);
+        (ok, response.clicked())
+    })
+    .inner
+}
+
+fn prompt_ui(ui: &mut egui::Ui, text: &mut String, hint: &str, widgets: &mut BTreeMap<String, TrackedWidget>) -> Option<Value> {
+    let response = ui.add(egui::TextEdit::singleline(text).hint_text(hint));
+    track(widgets, "dialog/input", &response, WidgetKind::TextInput);
+    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+    let (ok, cancel) = ok_cancel_ui(ui, "OK", true, widgets);
+    if ok || submitted {
+        Some(serde_json::json!(text))
+    } else if cancel {
+        Some(Value::Null)
+    } else {
+        None
+    }
+}
+
+fn file_browser_ui(ui: &mut egui::Ui, browser: &mut FileBrowser, widgets: &mut BTreeMap<String, TrackedWidget>) -> Option<Value> {
+    let mut navigate = None;
+    let mut open_file = false;
+
+    ui.horizontal(|ui| {
+        let response = ui.add_enabled(browser.dir.parent().is_some(), egui::Button::new("⬆"));
+        track(widgets, "dialog/up", &response, WidgetKind::Button);
+        if response.clicked() {
+            navigate = browser.dir.parent().map(Path::to_path_buf);
+        }
+        ui.label(browser.dir.display().to_string());
+    });
+    egui::ScrollArea::vertical().max_height(240.0).auto_shrink([false, true]).show(ui, |ui| {
+        for (name, is_dir) in &browser.entries {
+            let icon = if *is_dir { "📁" } else { "📄" };
+            let selected = !is_dir && *name == browser.file_name;
+            let response = ui.add(egui::SelectableLabel::new(selected, format!("{icon} {name}")));
+            track(widgets, format!("dialog/entry/{name}"), &response, WidgetKind::Button);
+            if response.clicked() {
+                if *is_dir {
+                    navigate = Some(browser.dir.join(name));
+                } else {
+                    browser.file_name = name.clone();
+                }
+            }
+            open_file |= !is_dir && response.double_clicked();
+        }
+    });
+
+    let response = ui.add(egui::TextEdit::singleline(&mut browser.file_name).hint_text("File name"));
+    track(widgets, "dialog/file_name", &response, WidgetKind::TextInput);
+    if let Some(error) = &browser.error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+    let chosen = browser.chosen();
+    if browser.mode == FileMode::Save && chosen.as_ref().is_some_and(|path| path.exists()) {
+        ui.weak("The file will be replaced.");
+    }
+    let ok = if browser.mode == FileMode::Open { "Open" } else { "Save" };
+    let (ok, cancel) = ok_cancel_ui(ui, ok, chosen.is_some(), widgets);
+
+    if let Some(dir) = navigate {
+        browser.open_dir(dir);
+    }
+    if ok || open_file {
+        chosen.map(|path| serde_json::json!(path.display().to_string()))
+    } else if cancel {
+        Some(Value::Null)
+    } else {
+        None
+    }
+}
+
+impl EguiApp {
+    /// Queues a modal dialog. The receiver gets its answer once it is closed.
+    pub fn open_dialog(&mut self, title: &str, message: &str, kind: DialogKind) -> oneshot::Receiver<Value> {
+        let (reply, answer) = oneshot::channel();
+        self.dialogs.push_back(Dialog {
+            title: title.to_string(),
+            message: message.to_string(),
+            kind,
+            reply: Some(reply),
+        });
+        answer
+    }
+
+    pub fn show_toast(&mut self, message: &str, level: ToastLevel, duration: f64) -> oneshot::Receiver<Value> {
+        let (reply, answer) = oneshot::channel();
+        self.next_toast += 1;
+        self.toasts.push(Toast {
+            id: self.next_toast,
+            message: message.to_string(),
+            level,
+            duration,
+            shown_at: None,
+            reply: Some(reply),
+        });
+        answer
+    }
+
+    /// Shows the oldest dialog above a backdrop that blocks the rest of the UI.
+    pub(crate) fn dialogs_ui(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = self.dialogs.front_mut() else { return };
+        let widgets = &mut self.widgets;
+
+        egui::Area::new(egui::Id::new("dialog_backdrop"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(egui::Pos2::ZERO)
+            .show(ctx, |ui| {
+                let rect = ctx.screen_rect();
+                ui.painter().rect_filled(rect, 0.0, egui::Color32::from_black_alpha(96));
+                ui.allocate_rect(rect, egui::Sense::click_and_drag());
+            });
+
+        let area = egui::Area::new(egui::Id::new("dialog"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO);
+        ctx.move_to_top(area.layer());
+        let mut answer = area
+            .show(ctx, |ui| {
+                egui::Frame::window(ui.style())
+                    .show(ui, |ui| {
+                        ui.set_max_width(420.0);
+                        ui.heading(dialog.title.as_str());
+                        if !dialog.message.is_empty() {
+                            ui.label(dialog.message.as_str());
+                        }
+                        ui.add_space(8.0);
+                        match &mut dialog.kind {
+                            DialogKind::Confirm { buttons } => confirm_ui(ui, buttons, widgets),
+                            DialogKind::Prompt { text, hint } => prompt_ui(ui, text, hint, widgets),
+                            DialogKind::File(browser) => file_browser_ui(ui, browser, widgets),
+                        }
+                    })
+                    .inner
+            })
+            .inner;
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            answer = answer.or(Some(Value::Null));
+        }
+
+        if let Some(answer) = answer {
+            let mut dialog = self.dialogs.pop_front().expect("shown above");
+            if let Some(reply) = dialog.reply.take() {
+                // The script may have stopped waiting.
+                let _ = reply.send(answer);
+            }
+        }
+    }
+
+    pub(crate) fn toasts_ui(&mut self, ctx: &egui::Context) {
+        if self.toasts.is_empty() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        let toasts = &mut self.toasts;
+        let widgets = &mut self.widgets;
+        let mut closed = Vec::new();
+
+        egui::Area::new(egui::Id::new("toasts"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .show(ctx, |ui| {
+                for toast in toasts.iter_mut() {
+                    let shown_at = *toast.shown_at.get_or_insert(now);
+                    if toast.duration > 0.0 && now - shown_at >= toast.duration {
+                        closed.push((toast.id, false));
+                        continue;
+                    }
+                    let (icon, color) = toast.level.icon();
+                    let response = egui::Frame::popup(ui.style())
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.colored_label(color, icon);
+                                ui.label(toast.message.as_str());
+                            });
+                        })
+                        .response
+                        .interact(egui::Sense::click());
+                    track(widgets, format!("toast/{}", toast.id), &response, WidgetKind::Button);
+                    if response.clicked() {
+                        closed.push((toast.id, true));
+                    }
+                }
+            });
+        // Keep frames coming so toasts expire on time.
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+
+        for (id, dismissed) in closed {
+            let Some(index) = self.toasts.iter().position(|toast| toast.id == id) else { continue };
+            let mut toast = self.toasts.remove(index);
+            if let Some(reply) = toast.reply.take() {
+                let _ = reply.send(serde_json::json!({ "dismissed": dismissed }));
+            }
+        }
+    }
+}
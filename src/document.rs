@@ -0,0 +1,547 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::persistence::{restore_widget_state, widget_state};
+use crate::windows::WindowCommand;
+use crate::{containers, EguiApp};
+
+/// How often watched documents are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A widget as a script creates it, tagged with its `type`. The fields are
+/// those of the op that creates the widget, e.g. `op_set_slider` for
+/// `"slider"`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Widget {
+    Label(crate::SetLabelArgs),
+    Image(crate::SetImageArgs),
+    Button(crate::SetButtonArgs),
+    Slider(crate::SetSliderArgs),
+    Input(crate::SetInputArgs),
+    Checkbox(crate::SetCheckboxArgs),
+    ComboBox(crate::SetComboBoxArgs),
+    Radio(crate::SetRadioArgs),
+    Progress(crate::SetProgressArgs),
+    Table(crate::SetTableArgs),
+    Plot(crate::SetPlotArgs),
+    Tree(crate::SetTreeArgs),
+    ColorEdit(crate::SetColorEditArgs),
+    DragValue(crate::SetDragValueArgs),
+    Vector(crate::SetVectorArgs),
+    DateTime(crate::SetDateTimeArgs),
+    Toolbar(crate::SetToolbarArgs),
+    Timeline(crate::SetTimelineArgs),
+    Container(crate::SetContainerArgs),
+}
+
+impl Widget {
+    /// `None` for the built-in label, slider and input.
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Widget::Label(args) => args.id.as_deref(),
+            Widget::Slider(args) => args.id.as_deref(),
+            Widget::Input(args) => args.id.as_deref(),
+            Widget::Image(crate::SetImageArgs { id, .. })
+            | Widget::Button(crate::SetButtonArgs { id, .. })
+            | Widget::Checkbox(crate::SetCheckboxArgs { id, .. })
+            | Widget::ComboBox(crate::SetComboBoxArgs { id, .. })
+            | Widget::Radio(crate::SetRadioArgs { id, .. })
+            | Widget::Progress(crate::SetProgressArgs { id, .. })
+            | Widget::Table(crate::SetTableArgs { id, .. })
+            | Widget::Plot(crate::SetPlotArgs { id, .. })
+            | Widget::Tree(crate::SetTreeArgs { id, .. })
+            | Widget::ColorEdit(crate::SetColorEditArgs { id, .. })
+            | Widget::DragValue(crate::SetDragValueArgs { id, .. })
+            | Widget::Vector(crate::SetVectorArgs { id, .. })
+            | Widget::DateTime(crate::SetDateTimeArgs { id, .. })
+            | Widget::Toolbar(crate::SetToolbarArgs { id, .. })
+            | Widget::Timeline(crate::SetTimelineArgs { id, .. })
+            | Widget::Container(crate::SetContainerArgs { id, .. }) => Some(id),
+        }
+    }
+
+    fn parent_mut(&mut self) -> &mut Option<String> {
+        match self {
+            Widget::Label(crate::SetLabelArgs { parent, .. })
+            | Widget::Image(crate::SetImageArgs { parent, .. })
+            | Widget::Button(crate::SetButtonArgs { parent, .. })
+            | Widget::Slider(crate::SetSliderArgs { parent, .. })
+            | Widget::Input(crate::SetInputArgs { parent, .. })
+            | Widget::Checkbox(crate::SetCheckboxArgs { parent, .. })
+            | Widget::ComboBox(crate::SetComboBoxArgs { parent, .. })
+            | Widget::Radio(crate::SetRadioArgs { parent, .. })
+            | Widget::Progress(crate::SetProgressArgs { parent, .. })
+            | Widget::Table(crate::SetTableArgs { parent, .. })
+            | Widget::Plot(crate::SetPlotArgs { parent, .. })
+            | Widget::Tree(crate::SetTreeArgs { parent, .. })
+            | Widget::ColorEdit(crate::SetColorEditArgs { parent, .. })
+            | Widget::DragValue(crate::SetDragValueArgs { parent, .. })
+            | Widget::Vector(crate::SetVectorArgs { parent, .. })
+            | Widget::DateTime(crate::SetDateTimeArgs { parent, .. })
+            | Widget::Toolbar(crate::SetToolbarArgs { parent, .. })
+            | Widget::Timeline(crate::SetTimelineArgs { parent, .. })
+            | Widget::Container(crate::SetContainerArgs { parent, .. }) => parent,
+        }
+    }
+}
+
+/// A widget of a document with the widgets placed inside it.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Node {
+    #[serde(flatten)]
+    pub widget: Widget,
+    /// Only containers and windows show children.
+    #[serde(default)]
+    pub children: Vec<Node>,
+    /// Observable the widget is bound to.
+    #[serde(default)]
+    pub bind: Option<String>,
+    /// Opts the widget in or out of being restored on the next start.
+    #[serde(default)]
+    pub persist: Option<bool>,
+}
+
+/// A native window of a document with the widgets shown in it.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WindowNode {
+    #[serde(flatten)]
+    pub window: crate::OpenWindowArgs,
+    #[serde(default)]
+    pub children: Vec<Node>,
+}
+
+/// A whole UI: observables, native windows and the widgets of the main
+/// window, which include panels and floating windows as containers.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct UiDocument {
+    /// Initial values, kept if the observable already exists.
+    #[serde(default)]
+    pub observables: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub windows: Vec<WindowNode>,
+    #[serde(default)]
+    pub widgets: Vec<Node>,
+}
+
+impl UiDocument {
+    /// Reads a `.json`, `.ron`, `.yaml` or `.yml` document. RON documents
+    /// write nodes as maps, e.g. `{ "type": "button", "id": "ok", "label": "OK" }`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read UI document '{}': {}", path.display(), e))?;
+        let value: Result<Value, String> = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => ron::from_str(&text).map_err(|e| e.to_string()),
+            Some("yaml" | "yml") => serde_yaml::from_str(&text).map_err(|e| e.to_string()),
+            _ => serde_json::from_str(&text).map_err(|e| e.to_string()),
+        };
+        value
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+            .map_err(|e| format!("Invalid UI document '{}': {}", path.display(), e))
+    }
+}
+
+/// A document loaded into the widget tree.
+#[derive(Debug, Clone, Default)]
+pub struct LoadedDocument {
+    pub path: PathBuf,
+    /// Reloads the document when the file changes.
+    pub watch: bool,
+    modified: Option<SystemTime>,
+    /// Ids of the widgets the document created, in document order.
+    pub widgets: Vec<String>,
+    windows: Vec<crate::OpenWindowArgs>,
+}
+
+/// The documents loaded so far.
+#[derive(Debug, Clone, Default)]
+pub struct Documents {
+    pub loaded: Vec<LoadedDocument>,
+    last_check: Option<Instant>,
+}
+
+/// The state a document load changes, kept to undo a load that fails.
+struct SavedWidgets {
+    labels: BTreeMap<String, crate::rich_text::LabelState>,
+    buttons: BTreeMap<String, crate::ButtonState>,
+    image_views: BTreeMap<String, crate::images::ImageView>,
+    sliders: BTreeMap<String, crate::inputs::SliderState>,
+    text_inputs: BTreeMap<String, crate::inputs::TextInputState>,
+    tables: BTreeMap<String, crate::table::TableState>,
+    plots: BTreeMap<String, crate::plot::PlotState>,
+    tree_views: BTreeMap<String, crate::tree_view::TreeViewState>,
+    color_edits: BTreeMap<String, crate::editors::ColorEditState>,
+    drag_values: BTreeMap<String, crate::editors::DragValueState>,
+    vectors: BTreeMap<String, crate::editors::VectorState>,
+    date_times: BTreeMap<String, crate::editors::DateTimeState>,
+    toolbars: BTreeMap<String, Vec<crate::menus::MenuItem>>,
+    timelines: BTreeMap<String, crate::timeline::TimelineState>,
+    checkboxes: BTreeMap<String, bool>,
+    combo_boxes: BTreeMap<String, (String, Vec<String>)>,
+    radio_groups: BTreeMap<String, (String, Vec<String>)>,
+    progress_bars: BTreeMap<String, f32>,
+    containers: BTreeMap<String, containers::Container>,
+    label_text: String,
+    slider_value: f32,
+    input_text: String,
+    scene_objects: BTreeMap<String, crate::scene::SceneObject>,
+    item_shortcuts: BTreeMap<crate::menus::ShortcutOwner, Vec<(String, egui::KeyboardShortcut)>>,
+    tree: crate::tree::WidgetTree,
+    bindings: crate::binding::Bindings,
+    persist_overrides: BTreeMap<String, bool>,
+    persist_cleared: BTreeSet<String>,
+}
+
+impl SavedWidgets {
+    fn take(app: &EguiApp) -> Self {
+        Self {
+            labels: app.labels.clone(),
+            buttons: app.buttons.clone(),
+            image_views: app.image_views.clone(),
+            sliders: app.sliders.clone(),
+            text_inputs: app.text_inputs.clone(),
+            tables: app.tables.clone(),
+            plots: app.plots.clone(),
+            tree_views: app.tree_views.clone(),
+            color_edits: app.color_edits.clone(),
+            drag_values: app.drag_values.clone(),
+            vectors: app.vectors.clone(),
+            date_times: app.date_times.clone(),
+            toolbars: app.toolbars.clone(),
+            timelines: app.timelines.clone(),
+            checkboxes: app.checkboxes.clone(),
+            combo_boxes: app.combo_boxes.clone(),
+            radio_groups: app.radio_groups.clone(),
+            progress_bars: app.progress_bars.clone(),
+            containers: app.containers.clone(),
+            label_text: app.label_text.clone(),
+            slider_value: app.slider_value,
+            input_text: app.input_text.clone(),
+            scene_objects: app.scene_objects.clone(),
+            item_shortcuts: app.item_shortcuts.clone(),
+            tree: app.tree.clone(),
+            bindings: app.bindings.clone(),
+            persist_overrides: app.persistence.overrides.clone(),
+            persist_cleared: app.persistence.cleared.clone(),
+        }
+    }
+
+    fn restore(self, app: &mut EguiApp) {
+        app.labels = self.labels;
+        app.buttons = self.buttons;
+        app.image_views = self.image_views;
+        app.sliders = self.sliders;
+        app.text_inputs = self.text_inputs;
+        app.tables = self.tables;
+        app.plots = self.plots;
+        app.tree_views = self.tree_views;
+        app.color_edits = self.color_edits;
+        app.drag_values = self.drag_values;
+        app.vectors = self.vectors;
+        app.date_times = self.date_times;
+        app.toolbars = self.toolbars;
+        app.timelines = self.timelines;
+        app.checkboxes = self.checkboxes;
+        app.combo_boxes = self.combo_boxes;
+        app.radio_groups = self.radio_groups;
+        app.progress_bars = self.progress_bars;
+        app.containers = self.containers;
+        app.label_text = self.label_text;
+        app.slider_value = self.slider_value;
+        app.input_text = self.input_text;
+        app.scene_objects = self.scene_objects;
+        app.item_shortcuts = self.item_shortcuts;
+        app.tree = self.tree;
+        app.bindings = self.bindings;
+        app.persistence.overrides = self.persist_overrides;
+        app.persistence.cleared = self.persist_cleared;
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+impl EguiApp {
+    /// Creates or updates a widget the way the op of its type does.
+    pub fn set_widget(&mut self, widget: Widget) -> Result<(), String> {
+        match widget {
+            Widget::Label(args) => match &args.id {
+                Some(id) => {
+                    self.labels.insert(id.clone(), crate::rich_text::LabelState::new(&args));
+                    self.place(id, args.parent.as_deref());
+                }
+                None => self.label_text = args.text.clone(),
+            },
+            Widget::Image(args) => {
+                self.image_views.insert(args.id.clone(), crate::images::ImageView {
+                    image: args.image,
+                    max_size: args.max_size,
+                    tooltip: args.tooltip,
+                    clickable: args.clickable,
+                });
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Button(args) => {
+                self.buttons.insert(args.id.clone(), crate::ButtonState {
+                    label: args.label,
+                    enabled: args.enabled,
+                    tooltip: args.tooltip,
+                    icon: args.icon,
+                    message: args.message,
+                });
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Slider(args) => match &args.id {
+                Some(id) => {
+                    self.sliders.insert(id.clone(), crate::inputs::SliderState::new(&args));
+                    self.place(id, args.parent.as_deref());
+                }
+                None => self.slider_value = args.value as f32,
+            },
+            Widget::Input(args) => match &args.id {
+                Some(id) => {
+                    let input = crate::inputs::TextInputState::new(&args)?;
+                    self.text_inputs.insert(id.clone(), input);
+                    self.place(id, args.parent.as_deref());
+                }
+                None => self.input_text = args.text.clone(),
+            },
+            Widget::Checkbox(args) => {
+                self.checkboxes.insert(args.id.clone(), args.checked);
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::ComboBox(args) => {
+                self.combo_boxes.insert(args.id.clone(), (args.selected, args.options));
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Radio(args) => {
+                self.radio_groups.insert(args.id.clone(), (args.selected, args.options));
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Progress(args) => {
+                self.progress_bars.insert(args.id.clone(), args.value);
+                self.place(&args.id, args.parent.as_deref());
+            }
+            // Tables, plots and trees keep what users did with them.
+            Widget::Table(args) => {
+                let mut table = crate::table::TableState::new(&args);
+                if let Some(old) = self.tables.get(&args.id) {
+                    table.sort = old.sort.filter(|sort| sort.column < table.columns.len());
+                    table.set_filter(old.filter.clone());
+                }
+                self.tables.insert(args.id.clone(), table);
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Plot(args) => {
+                let mut plot = crate::plot::PlotState::new(&args);
+                if let Some(old) = self.plots.remove(&args.id) {
+                    plot.series = old.series;
+                    for series in plot.series.values_mut() {
+                        series.append(&[], plot.max_points);
+                    }
+                }
+                self.plots.insert(args.id.clone(), plot);
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Tree(args) => {
+                let mut tree = crate::tree_view::TreeViewState::new(&args);
+                if let Some(old) = self.tree_views.remove(&args.id) {
+                    tree.expanded = old.expanded;
+                    tree.selected = old.selected;
+                }
+                self.tree_views.insert(args.id.clone(), tree);
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::ColorEdit(args) => {
+                self.color_edits.insert(args.id.clone(), crate::editors::ColorEditState {
+                    label: args.label,
+                    color: args.color,
+                    mode: args.mode,
+                    object: args.object,
+                });
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::DragValue(args) => {
+                if let (Some(min), Some(max)) = (args.min, args.max) {
+                    if min > max {
+                        return Err(format!("Drag value '{}' has min {} above max {}", args.id, min, max));
+                    }
+                }
+                self.drag_values.insert(args.id.clone(), crate::editors::DragValueState {
+                    label: args.label,
+                    value: args.value,
+                    speed: args.speed,
+                    min: args.min,
+                    max: args.max,
+                    decimals: args.decimals,
+                    prefix: args.prefix,
+                    suffix: args.suffix,
+                });
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Vector(args) => {
+                if !(2..=4).contains(&args.value.len()) {
+                    return Err(format!("Vector '{}' must have 2 to 4 components, got {}", args.id, args.value.len()));
+                }
+                self.vectors.insert(args.id.clone(), crate::editors::VectorState {
+                    label: args.label,
+                    values: args.value,
+                    speed: args.speed,
+                    suffix: args.suffix,
+                });
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::DateTime(args) => {
+                let picker = crate::editors::DateTimeState::new(&args)?;
+                self.date_times.insert(args.id.clone(), picker);
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Toolbar(args) => {
+                // Shortcuts shown on the toolbar also trigger their commands.
+                self.set_item_shortcuts(crate::menus::ShortcutOwner::Toolbar(args.id.clone()), &args.items)?;
+                self.toolbars.insert(args.id.clone(), args.items);
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Timeline(args) => {
+                // Keeps the view, scrubber and selection.
+                let timeline = crate::timeline::TimelineState::new(&args);
+                match self.timelines.get_mut(&args.id) {
+                    Some(old) => old.row_height = timeline.row_height,
+                    None => {
+                        self.timelines.insert(args.id.clone(), timeline);
+                    }
+                }
+                self.place(&args.id, args.parent.as_deref());
+            }
+            Widget::Container(args) => {
+                let container = containers::Container {
+                    title: args.title.unwrap_or_else(|| args.id.clone()),
+                    kind: args.kind,
+                };
+                self.containers.insert(args.id.clone(), container);
+                self.place(&args.id, args.parent.as_deref());
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates `node` and its children under `parent`. Widgets in `keep`
+    /// keep their current value instead of the one in the document.
+    fn load_node(&mut self, mut node: Node, parent: &str, keep: &BTreeSet<String>, ids: &mut Vec<String>) -> Result<(), String> {
+        *node.widget.parent_mut() = Some(parent.to_string());
+        let id = node.widget.id().map(str::to_string);
+        let kept = id.as_deref().filter(|id| keep.contains(*id)).and_then(|id| widget_state(self, id));
+        self.set_widget(node.widget)?;
+
+        let Some(id) = id else { return Ok(()) };
+        ids.push(id.clone());
+        if let Some(value) = kept {
+            restore_widget_state(self, &id, &value)?;
+        }
+        if let Some(persist) = node.persist {
+            self.persistence.set_persist(&id, persist);
+        }
+        if let Some(name) = &node.bind {
+            self.bindings.bind(&id, Some(name)).map_err(|e| format!("Widget '{}': {}", id, e))?;
+        }
+        for child in node.children {
+            self.load_node(child, &id, keep, ids)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the document at `path` into the widget tree, replacing what
+    /// an earlier load of it created, and returns the ids of its widgets.
+    /// Widgets that were already loaded keep their values; widgets and
+    /// windows the document no longer has are removed. If a widget fails
+    /// to load, all widgets are left as they were before the load.
+    pub fn load_ui_document(&mut self, path: &Path, watch: bool) -> Result<Vec<String>, String> {
+        let modified = modified(path);
+        let document = UiDocument::load(path);
+        let index = self.documents.loaded.iter().position(|loaded| loaded.path == path);
+        if let Some(index) = index {
+            // Errors are reported once per change of the file.
+            self.documents.loaded[index].modified = modified;
+            self.documents.loaded[index].watch = watch;
+        }
+        let document = document?;
+
+        let previous = index.map(|index| self.documents.loaded[index].clone()).unwrap_or_default();
+        let keep: BTreeSet<String> = previous.widgets.iter().cloned().collect();
+        let saved = SavedWidgets::take(self);
+        for id in &keep {
+            // Re-attached below in document order.
+            self.tree.detach(id);
+        }
+
+        for (name, value) in document.observables {
+            self.bindings.declare(&name, value);
+        }
+        let mut nodes = Vec::new();
+        let mut windows = Vec::new();
+        for window in document.windows {
+            nodes.extend(window.children.into_iter().map(|child| (window.window.id.clone(), child)));
+            windows.push(window.window);
+        }
+        nodes.extend(document.widgets.into_iter().map(|node| (crate::tree::MAIN_WINDOW.to_string(), node)));
+        let mut ids = Vec::new();
+        let result = nodes.into_iter().try_for_each(|(parent, node)| self.load_node(node, &parent, &keep, &mut ids));
+        if let Err(e) = result {
+            saved.restore(self);
+            return Err(e);
+        }
+
+        for window in &windows {
+            let opened = previous.windows.iter().find(|opened| opened.id == window.id);
+            let changed = opened.map_or(true, |opened| serde_json::to_value(opened).ok() != serde_json::to_value(window).ok());
+            if changed {
+                self.window_commands.push(WindowCommand::Open(window.clone()));
+            }
+        }
+        for closed in previous.windows.iter().filter(|opened| !windows.iter().any(|window| window.id == opened.id)) {
+            self.window_commands.push(WindowCommand::Close { id: closed.id.clone() });
+        }
+        for id in keep.iter().filter(|id| !ids.contains(id)) {
+            self.remove_widget(id);
+        }
+
+        let loaded = LoadedDocument { path: path.to_path_buf(), watch, modified, widgets: ids.clone(), windows };
+        match index {
+            Some(index) => self.documents.loaded[index] = loaded,
+            None => self.documents.loaded.push(loaded),
+        }
+        self.emit("ui_load", &path.display().to_string(), serde_json::json!({ "widgets": ids }));
+        Ok(ids)
+    }
+
+    /// Reloads the watched documents whose file changed. A document that
+    /// fails to load is reported as a `ui_load_error` event; if it cannot
+    /// be parsed, its widgets stay as they were.
+    pub fn reload_changed_ui_documents(&mut self) {
+        let changed: Vec<(PathBuf, bool)> = self
+            .documents
+            .loaded
+            .iter()
+            .filter(|loaded| loaded.watch && modified(&loaded.path) != loaded.modified)
+            .map(|loaded| (loaded.path.clone(), loaded.watch))
+            .collect();
+        for (path, watch) in changed {
+            if let Err(e) = self.load_ui_document(&path, watch) {
+                self.emit("ui_load_error", &path.display().to_string(), serde_json::json!({ "message": e }));
+            }
+        }
+    }
+
+    pub(crate) fn watch_ui_documents(&mut self) {
+        let now = Instant::now();
+        if self.documents.last_check.is_some_and(|last| now.duration_since(last) < WATCH_INTERVAL) {
+            return;
+        }
+        self.documents.last_check = Some(now);
+        self.reload_changed_ui_documents();
+    }
+}
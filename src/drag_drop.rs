@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::automation::TrackedWidget;
+use crate::{tree, EguiApp};
+
+/// Target reported for drops that hit no widget in the main window, where
+/// the 3D scene is drawn behind the UI.
+pub const VIEWPORT: &str = "viewport";
+
+/// An item being dragged out of a list or tree widget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DragPayload {
+    /// Id of the widget the item comes from.
+    pub source: String,
+    /// Node id or row index of the item within `source`.
+    pub item: String,
+    /// Shown next to the pointer while dragging.
+    pub label: String,
+}
+
+/// A widget that items of other widgets can be dropped on.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct DropTarget {
+    /// Ids of the widgets whose items are accepted; `"*"` accepts any widget.
+    pub accept: Vec<String>,
+}
+
+impl DropTarget {
+    pub fn accepts(&self, source: &str) -> bool {
+        self.accept.iter().any(|accepted| accepted == "*" || accepted == source)
+    }
+}
+
+/// The innermost widget of `ctx` under `pos`, on the topmost layer there.
+pub fn widget_at<'a>(widgets: &'a BTreeMap<String, TrackedWidget>, ctx: &egui::Context, pos: egui::Pos2) -> Option<&'a str> {
+    let layer = ctx.layer_id_at(pos)?;
+    widgets
+        .iter()
+        .filter(|(_, widget)| widget.response.ctx == *ctx && widget.response.layer_id == layer && widget.rect.contains(pos))
+        .min_by(|(_, a), (_, b)| a.rect.area().total_cmp(&b.rect.area()))
+        .map(|(id, _)| id.as_str())
+}
+
+/// Splits the id of a hovered widget into the drop target it belongs to and
+/// the part of the target it is over, e.g. `"files/row/3"` into `"files"` and
+/// `"row/3"`.
+pub fn resolve_target<'a>(targets: &BTreeMap<String, DropTarget>, widget: &'a str) -> Option<(&'a str, Option<&'a str>)> {
+    let mut prefix = widget;
+    loop {
+        if targets.contains_key(prefix) {
+            let over = widget.get(prefix.len() + 1..).filter(|over| !over.is_empty());
+            return Some((prefix, over));
+        }
+        prefix = &prefix[..prefix.rfind('/')?];
+    }
+}
+
+/// Files hovered over a window last frame, and the widget they were over.
+#[derive(Debug, Clone, PartialEq)]
+struct FileHover {
+    target: Option<String>,
+}
+
+fn file_paths<'a>(paths: impl Iterator<Item = (Option<&'a std::path::PathBuf>, &'a str)>) -> Vec<String> {
+    paths
+        .map(|(path, name)| path.map_or_else(|| name.to_string(), |path| path.display().to_string()))
+        .collect()
+}
+
+fn highlight(ctx: &egui::Context, id: &str, rect: egui::Rect, color: egui::Color32) {
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new(("drop_highlight", id))));
+    painter.rect_stroke(rect.expand(2.0), 2.0, egui::Stroke::new(2.0, color));
+}
+
+impl EguiApp {
+    /// The widget under `pos` that files are reported to land on.
+    fn file_target(&self, ctx: &egui::Context, window: &str, pos: Option<egui::Pos2>) -> Option<String> {
+        let widget = pos.and_then(|pos| widget_at(&self.widgets, ctx, pos));
+        match widget {
+            Some(widget) => Some(widget.to_string()),
+            None if window == tree::MAIN_WINDOW && pos.is_some() => Some(VIEWPORT.to_string()),
+            None => None,
+        }
+    }
+
+    /// Reports files dragged over or dropped on `window` from the OS. The
+    /// position is the last one the OS reported, since some platforms do not
+    /// move the pointer during a drag.
+    pub(crate) fn file_drops_ui(&mut self, ctx: &egui::Context, window: &str) {
+        let (hovered, dropped) = ctx.input(|i| {
+            let hovered = file_paths(i.raw.hovered_files.iter().map(|file| (file.path.as_ref(), "")));
+            let dropped = file_paths(i.raw.dropped_files.iter().map(|file| (file.path.as_ref(), file.name.as_str())));
+            (hovered, dropped)
+        });
+        let pos = ctx.input(|i| i.pointer.latest_pos());
+        let hover_id = egui::Id::new(("file_hover", window));
+        let last_hover = ctx.data_mut(|data| data.get_temp::<FileHover>(hover_id));
+
+        if !dropped.is_empty() {
+            ctx.data_mut(|data| data.remove::<FileHover>(hover_id));
+            let target = self.file_target(ctx, window, pos);
+            let pos = pos.unwrap_or_default();
+            self.emit("file_drop", window, serde_json::json!({ "paths": dropped, "x": pos.x, "y": pos.y, "target": target }));
+        } else if !hovered.is_empty() {
+            let target = self.file_target(ctx, window, pos);
+            match target.as_deref() {
+                Some(VIEWPORT) => highlight(ctx, window, ctx.screen_rect().shrink(2.0), ctx.style().visuals.selection.stroke.color),
+                Some(widget) => {
+                    if let Some(tracked) = self.widgets.get(widget) {
+                        highlight(ctx, window, tracked.rect, ctx.style().visuals.selection.stroke.color);
+                    }
+                }
+                None => {}
+            }
+            if last_hover.as_ref().map(|hover| &hover.target) != Some(&target) {
+                ctx.data_mut(|data| data.insert_temp(hover_id, FileHover { target: target.clone() }));
+                let pos = pos.unwrap_or_default();
+                self.emit("file_hover", window, serde_json::json!({ "paths": hovered, "x": pos.x, "y": pos.y, "target": target }));
+            }
+        } else if last_hover.is_some() {
+            ctx.data_mut(|data| data.remove::<FileHover>(hover_id));
+            self.emit("file_hover_cancel", window, serde_json::json!({}));
+        }
+    }
+
+    /// Starts dragging `item` of widget `source`, reporting a `drag_start` event
+    /// the first time.
+    pub(crate) fn drag_item(&mut self, source: &str, item: &str, label: String) {
+        if self.dragging.as_ref().is_some_and(|payload| payload.source == source && payload.item == item) {
+            return;
+        }
+        self.dragging = Some(DragPayload { source: source.to_string(), item: item.to_string(), label });
+        self.emit("drag_start", source, serde_json::json!({ "item": item }));
+    }
+
+    /// Shows where the dragged item would land and reports drops on other
+    /// widgets as `item_drop` events on the target. Drops within the source
+    /// widget are left to the widget, e.g. reparenting in trees.
+    pub(crate) fn drag_drop_ui(&mut self, ctx: &egui::Context) {
+        let Some(payload) = self.dragging.clone() else { return };
+        let (pos, down, released) = ctx.input(|i| (i.pointer.latest_pos(), i.pointer.any_down(), i.pointer.any_released()));
+        let Some(pos) = pos else { return };
+        let hovered = widget_at(&self.widgets, ctx, pos)
+            .and_then(|widget| resolve_target(&self.drop_targets, widget))
+            .filter(|(target, _)| *target != payload.source)
+            .map(|(target, over)| {
+                let accepted = self.drop_targets[target].accepts(&payload.source);
+                (target.to_string(), over.map(str::to_string), accepted)
+            });
+
+        if !down {
+            self.dragging = None;
+            if let Some((target, over, true)) = hovered.filter(|_| released) {
+                self.emit("item_drop", &target, serde_json::json!({ "source": payload.source, "item": payload.item, "over": over }));
+            }
+            return;
+        }
+
+        if let Some((target, _, accepted)) = &hovered {
+            let visuals = ctx.style().visuals.clone();
+            let color = if *accepted { visuals.selection.stroke.color } else { visuals.error_fg_color };
+            if let Some(tracked) = self.widgets.get(target) {
+                highlight(ctx, target, tracked.rect, color);
+            }
+            if !accepted {
+                ctx.set_cursor_icon(egui::CursorIcon::NotAllowed);
+            }
+        }
+        egui::show_tooltip_at(ctx, egui::Id::new("drag_payload"), Some(pos + egui::vec2(12.0, 0.0)), |ui| {
+            ui.label(payload.label.as_str());
+        });
+    }
+}
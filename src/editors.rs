@@ -0,0 +1,228 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use egui::ecolor::Hsva;
+use serde::{Deserialize, Serialize};
+
+use crate::automation::WidgetKind;
+use crate::{track, EguiApp};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    #[default]
+    Rgb,
+    Rgba,
+    Hsv,
+}
+
+/// A color button with a picker. Colors are linear RGBA in `0.0..=1.0`.
+#[derive(Debug, Clone)]
+pub struct ColorEditState {
+    pub label: Option<String>,
+    pub color: [f32; 4],
+    pub mode: ColorMode,
+    /// Scene object whose material color this edits.
+    pub object: Option<String>,
+}
+
+/// A numeric field changed by dragging or typing.
+#[derive(Debug, Clone)]
+pub struct DragValueState {
+    pub label: Option<String>,
+    pub value: f64,
+    pub speed: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub decimals: Option<usize>,
+    pub prefix: String,
+    /// Unit shown after the value, e.g. `" m"`.
+    pub suffix: String,
+}
+
+/// A row of drag values for a 2D or 3D vector.
+#[derive(Debug, Clone)]
+pub struct VectorState {
+    pub label: Option<String>,
+    pub values: Vec<f64>,
+    pub speed: f64,
+    pub suffix: String,
+}
+
+const AXES: [&str; 4] = ["x", "y", "z", "w"];
+
+/// A date picker, optionally with a time of day.
+#[derive(Debug, Clone)]
+pub struct DateTimeState {
+    pub label: Option<String>,
+    pub date: NaiveDate,
+    /// `None` when the picker only edits the date.
+    pub time: Option<NaiveTime>,
+}
+
+fn parse_date_time(value: &str) -> Result<(NaiveDate, Option<NaiveTime>), String> {
+    match NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+        Ok(datetime) => Ok((datetime.date(), Some(datetime.time()))),
+        Err(_) => {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", value, e))?;
+            Ok((date, None))
+        }
+    }
+}
+
+impl DateTimeState {
+    /// Parses `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SS`.
+    pub fn new(args: &crate::SetDateTimeArgs) -> Result<Self, String> {
+        let (date, time) = parse_date_time(&args.value)?;
+        Ok(Self {
+            label: args.label.clone(),
+            date,
+            time: if args.with_time { time.or(NaiveTime::from_hms_opt(0, 0, 0)) } else { None },
+        })
+    }
+
+    /// Sets the date, and the time if the picker has one, from a value in
+    /// the format of [`Self::new`].
+    pub fn set_value(&mut self, value: &str) -> Result<(), String> {
+        let (date, time) = parse_date_time(value)?;
+        self.date = date;
+        if self.time.is_some() {
+            self.time = time.or(NaiveTime::from_hms_opt(0, 0, 0));
+        }
+        Ok(())
+    }
+
+    /// The value in the format scripts send it in.
+    pub fn value(&self) -> String {
+        match self.time {
+            Some(time) => self.date.and_time(time).format("%Y-%m-%dT%H:%M:%S").to_string(),
+            None => self.date.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+fn with_label<R>(ui: &mut egui::Ui, label: Option<&str>, add_contents: impl FnOnce(&mut egui::Ui) -> R) -> R {
+    ui.horizontal(|ui| {
+        if let Some(label) = label {
+            ui.label(label);
+        }
+        add_contents(ui)
+    })
+    .inner
+}
+
+impl EguiApp {
+    pub(crate) fn color_edit_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(edit) = self.color_edits.get_mut(id) else { return };
+        if let Some(color) = edit.object.as_deref().and_then(|object| self.scene_objects.get(object)).map(|o| o.color) {
+            edit.color = color;
+        }
+
+        let response = with_label(ui, edit.label.as_deref(), |ui| match edit.mode {
+            ColorMode::Rgb => {
+                let [r, g, b, a] = edit.color;
+                let mut rgb = [r, g, b];
+                let response = ui.color_edit_button_rgb(&mut rgb);
+                edit.color = [rgb[0], rgb[1], rgb[2], a];
+                response
+            }
+            ColorMode::Rgba => ui.color_edit_button_rgba_unmultiplied(&mut edit.color),
+            ColorMode::Hsv => {
+                let [r, g, b, a] = edit.color;
+                let mut hsva = Hsva::from_rgba_unmultiplied(r, g, b, a);
+                let response = ui.color_edit_button_hsva(&mut hsva);
+                if response.changed() {
+                    edit.color = hsva.to_rgba_unmultiplied();
+                }
+                response
+            }
+        });
+        track(&mut self.widgets, id, &response, WidgetKind::ColorEdit);
+
+        if response.changed() {
+            let color = edit.color;
+            let mut data = serde_json::json!({ "color": color });
+            if edit.mode == ColorMode::Hsv {
+                let [r, g, b, a] = color;
+                let hsva = Hsva::from_rgba_unmultiplied(r, g, b, a);
+                data["hsv"] = serde_json::json!([hsva.h, hsva.s, hsva.v]);
+            }
+            if let Some(object) = edit.object.clone() {
+                if let Some(object) = self.scene_objects.get_mut(&object) {
+                    object.color = color;
+                }
+                data["object"] = serde_json::json!(object);
+            }
+            self.emit("color_change", id, data);
+        }
+    }
+
+    pub(crate) fn drag_value_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(drag) = self.drag_values.get_mut(id) else { return };
+
+        let response = with_label(ui, drag.label.as_deref(), |ui| {
+            let mut widget = egui::DragValue::new(&mut drag.value)
+                .speed(drag.speed)
+                .prefix(&drag.prefix)
+                .suffix(&drag.suffix);
+            if drag.min.is_some() || drag.max.is_some() {
+                widget = widget.clamp_range(drag.min.unwrap_or(f64::NEG_INFINITY)..=drag.max.unwrap_or(f64::INFINITY));
+            }
+            if let Some(decimals) = drag.decimals {
+                widget = widget.fixed_decimals(decimals);
+            }
+            ui.add(widget)
+        });
+        track(&mut self.widgets, id, &response, WidgetKind::DragValue);
+
+        if response.changed() {
+            let value = drag.value;
+            self.emit("drag_value_change", id, serde_json::json!({ "value": value }));
+        }
+    }
+
+    pub(crate) fn vector_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(vector) = self.vectors.get_mut(id) else { return };
+        let widgets = &mut self.widgets;
+
+        let changed = with_label(ui, vector.label.as_deref(), |ui| {
+            let mut changed = false;
+            for (axis, value) in AXES.iter().zip(vector.values.iter_mut()) {
+                let widget = egui::DragValue::new(value)
+                    .speed(vector.speed)
+                    .prefix(format!("{axis}: "))
+                    .suffix(&vector.suffix);
+                let response = ui.add(widget);
+                track(widgets, format!("{id}/{axis}"), &response, WidgetKind::DragValue);
+                changed |= response.changed();
+            }
+            changed
+        });
+
+        if changed {
+            let values = vector.values.clone();
+            self.emit("vector_change", id, serde_json::json!({ "value": values }));
+        }
+    }
+
+    pub(crate) fn date_time_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(picker) = self.date_times.get_mut(id) else { return };
+        let before = (picker.date, picker.time);
+
+        let response = with_label(ui, picker.label.as_deref(), |ui| {
+            let response = ui.add(egui_extras::DatePickerButton::new(&mut picker.date).id_source(id));
+            if let Some(time) = &mut picker.time {
+                let (mut hour, mut minute, mut second) = (time.hour(), time.minute(), time.second());
+                ui.add(egui::DragValue::new(&mut hour).clamp_range(0..=23).suffix("h"));
+                ui.add(egui::DragValue::new(&mut minute).clamp_range(0..=59).suffix("m"));
+                ui.add(egui::DragValue::new(&mut second).clamp_range(0..=59).suffix("s"));
+                *time = NaiveTime::from_hms_opt(hour, minute, second).unwrap_or(*time);
+            }
+            response
+        });
+        track(&mut self.widgets, id, &response, WidgetKind::DatePicker);
+
+        if (picker.date, picker.time) != before {
+            let value = picker.value();
+            self.emit("datetime_change", id, serde_json::json!({ "value": value }));
+        }
+    }
+}
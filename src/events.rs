@@ -1,7 +1,12 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::persistence::write_atomic;
+use crate::snapshot::Snapshot;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RecordedEvent {
     pub event_type: String,
@@ -9,3 +14,36 @@ pub struct RecordedEvent {
     pub event_data: Value,
     pub timestamp: u64,
 }
+
+/// A recording as saved to a file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Recording {
+    /// In timestamp order, starting with the state the recording began in.
+    #[serde(default)]
+    pub snapshots: Vec<Snapshot>,
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Writes `recording` as a JSON object.
+pub fn save(path: &Path, recording: &Recording) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(recording).map_err(|e| e.to_string())?;
+    write_atomic(path, json.as_bytes()).map_err(|e| format!("Cannot write recording '{}': {}", path.display(), e))
+}
+
+/// Reads a recording written by [`save`], or a plain array of events from
+/// before recordings had snapshots, with events in timestamp order.
+pub fn load(path: &Path) -> Result<Recording, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read recording '{}': {}", path.display(), e))?;
+    let invalid = |e: serde_json::Error| format!("Invalid recording '{}': {}", path.display(), e);
+    let value: Value = serde_json::from_str(&json).map_err(invalid)?;
+    let mut recording = if value.is_array() {
+        Recording { events: serde_json::from_value(value).map_err(invalid)?, ..Default::default() }
+    } else {
+        serde_json::from_value::<Recording>(value).map_err(invalid)?
+    };
+    recording.events.sort_by_key(|event| event.timestamp);
+    let events = recording.events.len();
+    recording.snapshots.retain(|snapshot| snapshot.events <= events);
+    recording.snapshots.sort_by_key(|snapshot| snapshot.timestamp);
+    Ok(recording)
+}
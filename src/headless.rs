@@ -0,0 +1,223 @@
+use egui_wgpu::renderer::ScreenDescriptor;
+
+use crate::state_3d::State3D;
+use crate::EguiApp;
+
+/// Renders the 3D scene and the egui UI into an offscreen texture at a fixed
+/// size and scale factor, and reads the result back as an image.
+pub struct HeadlessRenderer {
+    pub state_3d: State3D,
+    pub egui_ctx: egui::Context,
+    pub pixels_per_point: f32,
+    egui_renderer: egui_wgpu::Renderer,
+    target: wgpu::Texture,
+    pending_free: Vec<egui::TextureId>,
+}
+
+impl HeadlessRenderer {
+    /// Returns `None` when the machine has no usable wgpu adapter.
+    pub fn new(width: u32, height: u32, pixels_per_point: f32) -> Option<Self> {
+        let state_3d = pollster::block_on(State3D::new_headless(width, height))?;
+
+        let target = state_3d.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: state_3d.config.format,
+            usage: state_3d.config.usage,
+            view_formats: &[],
+        });
+
+        let egui_renderer = egui_wgpu::Renderer::new(
+            &state_3d.device,
+            state_3d.config.format,
+            None,
+            1,
+        );
+
+        Some(Self {
+            state_3d,
+            egui_ctx: egui::Context::default(),
+            pixels_per_point,
+            egui_renderer,
+            target,
+            pending_free: Vec::new(),
+        })
+    }
+
+    /// An empty `RawInput` covering the whole target, with a fixed time so
+    /// animations are deterministic.
+    pub fn raw_input(&self) -> egui::RawInput {
+        let size = egui::vec2(
+            self.state_3d.config.width as f32,
+            self.state_3d.config.height as f32,
+        ) / self.pixels_per_point;
+
+        egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size)),
+            pixels_per_point: Some(self.pixels_per_point),
+            time: Some(0.0),
+            focused: true,
+            ..Default::default()
+        }
+    }
+
+    /// Runs one egui frame of `app` with `raw_input` without drawing it.
+    /// Texture updates are still uploaded, so later frames render correctly.
+    pub fn run_frame(&mut self, app: &mut EguiApp, raw_input: egui::RawInput) -> egui::FullOutput {
+        for id in self.pending_free.drain(..) {
+            self.egui_renderer.free_texture(&id);
+        }
+
+        self.egui_ctx.begin_frame(raw_input);
+        app.ui(&self.egui_ctx);
+        let output = self.egui_ctx.end_frame();
+
+        for (id, image_delta) in &output.textures_delta.set {
+            self.egui_renderer.update_texture(
+                &self.state_3d.device,
+                &self.state_3d.queue,
+                *id,
+                image_delta,
+            );
+        }
+        self.pending_free.extend(output.textures_delta.free.iter().copied());
+
+        output
+    }
+
+    /// Runs one frame of `app` with AccessKit enabled and returns the
+    /// accessibility tree as JSON, see [`crate::accessibility::tree_json`].
+    pub fn accessibility_tree(&mut self, app: &mut EguiApp, raw_input: egui::RawInput) -> serde_json::Value {
+        self.egui_ctx.enable_accesskit();
+        let output = self.run_frame(app, raw_input);
+        output.platform_output.accesskit_update.map_or(serde_json::Value::Null, |update| {
+            crate::accessibility::tree_json(&update, &self.egui_ctx, &app.widgets)
+        })
+    }
+
+    /// Renders only the 3D scene at the given rotation, without any tint.
+    pub fn render_scene(&mut self, rotation: f32) -> egui::ColorImage {
+        let view = self.target.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.state_3d.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Headless Encoder"),
+        });
+
+        self.state_3d.update_uniforms(rotation, crate::scene::WHITE);
+        self.state_3d.render(&mut encoder, &view);
+
+        self.state_3d.queue.submit(std::iter::once(encoder.finish()));
+        self.read_target()
+    }
+
+    /// Runs one frame of `app` and renders the 3D scene with the UI on top.
+    pub fn render(&mut self, app: &mut EguiApp, raw_input: egui::RawInput) -> egui::ColorImage {
+        let output = self.run_frame(app, raw_input);
+        let paint_jobs = self.egui_ctx.tessellate(output.shapes);
+
+        let view = self.target.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.state_3d.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Headless Encoder"),
+        });
+
+        self.state_3d.update_uniforms(app.rotation, app.scene_tint());
+        self.state_3d.render(&mut encoder, &view);
+
+        let screen_descriptor = ScreenDescriptor {
+            size_in_pixels: [self.state_3d.config.width, self.state_3d.config.height],
+            pixels_per_point: self.pixels_per_point,
+        };
+
+        self.egui_renderer.update_buffers(
+            &self.state_3d.device,
+            &self.state_3d.queue,
+            &mut encoder,
+            &paint_jobs,
+            &screen_descriptor,
+        );
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("eGUI Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            self.egui_renderer.render(&mut rpass, &paint_jobs, &screen_descriptor);
+        }
+
+        self.state_3d.queue.submit(std::iter::once(encoder.finish()));
+        self.read_target()
+    }
+
+    fn read_target(&self) -> egui::ColorImage {
+        let width = self.state_3d.config.width;
+        let height = self.state_3d.config.height;
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = self.state_3d.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Headless Readback Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.state_3d.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.target.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.state_3d.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.state_3d.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .expect("readback callback dropped")
+            .expect("Failed to map readback buffer");
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels)
+    }
+}
@@ -0,0 +1,343 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// A message key with the arguments its placeholders are filled with.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Message {
+    pub key: String,
+    #[serde(default)]
+    pub args: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The messages of one locale, keyed by message id.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    pub messages: BTreeMap<String, String>,
+}
+
+fn flatten_json(prefix: &str, value: &serde_json::Value, messages: &mut BTreeMap<String, String>) {
+    match value {
+        serde_json::Value::Object(entries) => {
+            for (key, value) in entries {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                flatten_json(&key, value, messages);
+            }
+        }
+        serde_json::Value::String(text) => {
+            messages.insert(prefix.to_string(), text.clone());
+        }
+        other => {
+            messages.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+impl Catalog {
+    /// Reads a `.json` catalog of strings, where nested objects give dotted
+    /// keys, or a `.ftl` Fluent catalog.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read catalog '{}': {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ftl") => Ok(Self::parse_fluent(&text)),
+            _ => Self::parse_json(&text).map_err(|e| format!("Invalid catalog '{}': {}", path.display(), e)),
+        }
+    }
+
+    pub fn parse_json(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if !value.is_object() {
+            return Err("a catalog must be a JSON object".to_string());
+        }
+        let mut messages = BTreeMap::new();
+        flatten_json("", &value, &mut messages);
+        Ok(Self { messages })
+    }
+
+    /// Reads the parts of Fluent that labels need: messages and terms,
+    /// multiline values, attributes as `key.attribute`, and placeables.
+    /// Selectors are kept as they are written.
+    pub fn parse_fluent(text: &str) -> Self {
+        let mut messages: BTreeMap<String, String> = BTreeMap::new();
+        let mut message: Option<String> = None;
+        let mut current: Option<String> = None;
+
+        for line in text.lines() {
+            let indented = line.starts_with(' ') || line.starts_with('\t');
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if !indented && trimmed.starts_with('#') {
+                current = None;
+                continue;
+            }
+
+            if indented {
+                if let Some(attribute) = trimmed.strip_prefix('.') {
+                    if let (Some(message), Some((name, value))) = (&message, attribute.split_once('=')) {
+                        let key = format!("{message}.{}", name.trim());
+                        messages.insert(key.clone(), value.trim().to_string());
+                        current = Some(key);
+                    }
+                } else if let Some(value) = current.as_ref().and_then(|key| messages.get_mut(key)) {
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(trimmed);
+                }
+                continue;
+            }
+
+            match trimmed.split_once('=') {
+                Some((key, value)) => {
+                    let key = key.trim().to_string();
+                    messages.insert(key.clone(), value.trim().to_string());
+                    message = Some(key.clone());
+                    current = Some(key);
+                }
+                None => {
+                    message = None;
+                    current = None;
+                }
+            }
+        }
+        Self { messages }
+    }
+}
+
+fn format_arg(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Fills `{ $name }` placeables (or `{name}`) from `args`, `{ -term }` from
+/// `terms` and `{ "literal" }` with the literal. Unknown placeables stay.
+pub fn format(template: &str, args: &serde_json::Map<String, serde_json::Value>, terms: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeable = &rest[start..start + end + 1];
+        let inner = placeable[1..placeable.len() - 1].trim();
+        let value = if let Some(literal) = inner.strip_prefix('"').and_then(|inner| inner.strip_suffix('"')) {
+            Some(literal.to_string())
+        } else if inner.starts_with('-') {
+            terms(inner)
+        } else {
+            args.get(inner.trim_start_matches('$')).map(format_arg)
+        };
+        out.push_str(value.as_deref().unwrap_or(placeable));
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Writing systems that egui's built-in fonts do not cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Latin,
+    Cjk,
+    Arabic,
+    Hebrew,
+}
+
+impl Script {
+    pub fn of_locale(locale: &str) -> Self {
+        match language(locale) {
+            "zh" | "ja" | "ko" => Script::Cjk,
+            "ar" | "fa" | "ur" | "ps" => Script::Arabic,
+            "he" | "yi" => Script::Hebrew,
+            _ => Script::Latin,
+        }
+    }
+
+    pub fn is_rtl(self) -> bool {
+        matches!(self, Script::Arabic | Script::Hebrew)
+    }
+
+    /// Name of the fallback font added for the script.
+    pub fn font_name(self) -> &'static str {
+        match self {
+            Script::Latin => "fallback-latin",
+            Script::Cjk => "fallback-cjk",
+            Script::Arabic => "fallback-arabic",
+            Script::Hebrew => "fallback-hebrew",
+        }
+    }
+
+    /// Fonts covering the script that ship with common systems, best first.
+    fn system_fonts(self) -> &'static [&'static str] {
+        match self {
+            Script::Latin => &[],
+            Script::Cjk => &[
+                "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+                "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+                "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+                "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+                "/System/Library/Fonts/PingFang.ttc",
+                "/System/Library/Fonts/Hiragino Sans GB.ttc",
+                "C:\\Windows\\Fonts\\msyh.ttc",
+                "C:\\Windows\\Fonts\\YuGothM.ttc",
+            ],
+            Script::Arabic => &[
+                "/usr/share/fonts/truetype/noto/NotoSansArabic-Regular.ttf",
+                "/usr/share/fonts/noto/NotoSansArabic-Regular.ttf",
+                "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+                "/System/Library/Fonts/GeezaPro.ttc",
+                "C:\\Windows\\Fonts\\arial.ttf",
+            ],
+            Script::Hebrew => &[
+                "/usr/share/fonts/truetype/noto/NotoSansHebrew-Regular.ttf",
+                "/usr/share/fonts/noto/NotoSansHebrew-Regular.ttf",
+                "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+                "/System/Library/Fonts/ArialHB.ttc",
+                "C:\\Windows\\Fonts\\arial.ttf",
+            ],
+        }
+    }
+
+    /// The first installed system font for the script.
+    pub fn system_font(self) -> Option<&'static str> {
+        self.system_fonts().iter().copied().find(|path| Path::new(path).is_file())
+    }
+}
+
+/// `"pt"` for `"pt-BR"` or `"pt_BR"`.
+fn language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+fn is_rtl_char(c: char) -> bool {
+    matches!(c, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+}
+
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        other => other,
+    }
+}
+
+/// Puts right-to-left text in the order egui has to draw it in, since egui
+/// lays out every line left to right. This is a simplified bidi algorithm:
+/// the first strong character sets the direction of a line, neutral
+/// characters take the direction around them, and numbers stay left to
+/// right. Arabic letters keep their isolated forms, as egui does not shape.
+pub fn visual_order(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(is_rtl_char) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.split('\n').map(visual_line).collect::<Vec<_>>().join("\n"))
+}
+
+fn visual_line(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    // Some(true) for right-to-left, Some(false) for left-to-right, None for neutral.
+    let strong: Vec<Option<bool>> = chars
+        .iter()
+        .map(|c| if is_rtl_char(*c) { Some(true) } else if c.is_alphanumeric() { Some(false) } else { None })
+        .collect();
+    let base = strong.iter().flatten().next().copied().unwrap_or(false);
+
+    let mut before = vec![None; chars.len()];
+    let mut last = None;
+    for (i, dir) in strong.iter().enumerate() {
+        before[i] = last;
+        last = dir.or(last);
+    }
+    let mut after = vec![None; chars.len()];
+    let mut next = None;
+    for (i, dir) in strong.iter().enumerate().rev() {
+        after[i] = next;
+        next = dir.or(next);
+    }
+    let resolved = strong
+        .iter()
+        .enumerate()
+        .map(|(i, dir)| dir.unwrap_or(if before[i].is_some() && before[i] == after[i] { before[i] == Some(true) } else { base }));
+
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for (c, rtl) in chars.iter().zip(resolved) {
+        match runs.last_mut() {
+            Some((run_rtl, run)) if *run_rtl == rtl => run.push(*c),
+            _ => runs.push((rtl, c.to_string())),
+        }
+    }
+    let runs = runs.into_iter().map(|(rtl, run)| if rtl { run.chars().rev().map(mirror).collect() } else { run });
+    if base {
+        runs.rev().collect()
+    } else {
+        runs.collect()
+    }
+}
+
+/// The message catalogs and the locale labels are shown in.
+#[derive(Debug, Clone)]
+pub struct I18n {
+    pub locale: String,
+    /// Used for messages missing from the locale's catalog.
+    pub fallback: String,
+    pub catalogs: BTreeMap<String, Catalog>,
+    /// Bumped on every change, so translated labels know when to update.
+    pub generation: u64,
+}
+
+impl Default for I18n {
+    fn default() -> Self {
+        Self { locale: "en".to_string(), fallback: "en".to_string(), catalogs: BTreeMap::new(), generation: 0 }
+    }
+}
+
+impl I18n {
+    pub fn add_catalog(&mut self, locale: &str, catalog: Catalog) {
+        self.catalogs.entry(locale.to_string()).or_default().messages.extend(catalog.messages);
+        self.generation += 1;
+    }
+
+    pub fn set_locale(&mut self, locale: &str) {
+        self.locale = locale.to_string();
+        self.generation += 1;
+    }
+
+    pub fn script(&self) -> Script {
+        Script::of_locale(&self.locale)
+    }
+
+    /// Looks `key` up in the locale, its language, then the fallback locale.
+    fn lookup(&self, key: &str) -> Option<&str> {
+        [self.locale.as_str(), language(&self.locale), self.fallback.as_str()]
+            .into_iter()
+            .find_map(|locale| self.catalogs.get(locale)?.messages.get(key))
+            .map(String::as_str)
+    }
+
+    /// The message `key` with its placeables filled in, or the key itself if
+    /// no catalog has it.
+    pub fn translate(&self, key: &str, args: &serde_json::Map<String, serde_json::Value>) -> String {
+        match self.lookup(key) {
+            Some(template) => format(template, args, |term| self.lookup(term).map(str::to_string)),
+            None => key.to_string(),
+        }
+    }
+
+    pub fn message(&self, message: &Message) -> String {
+        self.translate(&message.key, &message.args)
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::automation::WidgetKind;
+use crate::{track, EguiApp};
+
+/// Named icons from egui's built-in emoji font, usable wherever an icon is
+/// accepted. Other strings are used as the icon text as they are.
+pub const ICONS: &[(&str, &str)] = &[
+    ("add", "➕"),
+    ("check", "✔"),
+    ("close", "❌"),
+    ("copy", "📋"),
+    ("cube", "⬛"),
+    ("delete", "🗑"),
+    ("edit", "✏"),
+    ("error", "⛔"),
+    ("eye", "👁"),
+    ("file", "📄"),
+    ("folder", "📁"),
+    ("home", "🏠"),
+    ("info", "ℹ"),
+    ("lock", "🔒"),
+    ("open", "📂"),
+    ("pause", "⏸"),
+    ("play", "▶"),
+    ("record", "⏺"),
+    ("redo", "⟳"),
+    ("refresh", "🔄"),
+    ("remove", "➖"),
+    ("save", "💾"),
+    ("search", "🔍"),
+    ("settings", "⚙"),
+    ("star", "⭐"),
+    ("stop", "⏹"),
+    ("undo", "⟲"),
+    ("warning", "⚠"),
+];
+
+/// The glyph of icon `name`, or `name` itself if it is not a known icon.
+pub fn icon(name: &str) -> &str {
+    ICONS.iter().find(|(known, _)| *known == name).map_or(name, |(_, glyph)| glyph)
+}
+
+/// Makes every reload of an image a new uri, since egui caches images by uri.
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(0);
+
+/// An image loaded by a script, drawn through egui's image loaders.
+#[derive(Debug, Clone)]
+pub struct ImageData {
+    pub uri: String,
+    pub bytes: Option<egui::load::Bytes>,
+}
+
+impl ImageData {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let path = std::fs::canonicalize(path).map_err(|e| format!("Cannot open image '{}': {}", path, e))?;
+        Ok(Self { uri: format!("file://{}", path.display()), bytes: None })
+    }
+
+    /// `bytes` holds an encoded PNG or JPEG.
+    pub fn from_bytes(id: &str, bytes: Vec<u8>) -> Self {
+        let version = NEXT_VERSION.fetch_add(1, Ordering::Relaxed);
+        Self { uri: format!("bytes://{version}/{id}"), bytes: Some(bytes.into()) }
+    }
+
+    pub fn source(&self) -> egui::ImageSource<'static> {
+        match &self.bytes {
+            Some(bytes) => egui::ImageSource::Bytes { uri: self.uri.clone().into(), bytes: bytes.clone() },
+            None => egui::ImageSource::Uri(self.uri.clone().into()),
+        }
+    }
+}
+
+/// A placed widget showing the loaded image `image`.
+#[derive(Debug, Clone)]
+pub struct ImageView {
+    pub image: String,
+    /// Scales the image down to fit; its own size is used otherwise.
+    pub max_size: Option<[f32; 2]>,
+    pub tooltip: Option<String>,
+    /// Reports clicks as `image_click` events.
+    pub clickable: bool,
+}
+
+impl EguiApp {
+    pub(crate) fn image_view_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(view) = self.image_views.get(id) else { return };
+        let mut response = match self.images.get(&view.image) {
+            Some(data) => {
+                let mut image = egui::Image::new(data.source());
+                if let Some([width, height]) = view.max_size {
+                    image = image.max_size(egui::vec2(width, height));
+                }
+                if view.clickable {
+                    image = image.sense(egui::Sense::click());
+                }
+                ui.add(image)
+            }
+            None => ui.colored_label(ui.visuals().error_fg_color, format!("Missing image '{}'", view.image)),
+        };
+        if let Some(tooltip) = &view.tooltip {
+            response = response.on_hover_text(tooltip.as_str());
+        }
+        track(&mut self.widgets, id, &response, WidgetKind::Image);
+        if view.clickable && response.clicked() {
+            self.emit("image_click", id, serde_json::json!({}));
+        }
+    }
+}
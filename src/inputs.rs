@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+use crate::automation::WidgetKind;
+use crate::{track, EguiApp};
+
+/// A script-defined slider.
+#[derive(Debug, Clone)]
+pub struct SliderState {
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+    pub step: Option<f64>,
+    pub logarithmic: bool,
+    /// Rounds the value to whole numbers.
+    pub integer: bool,
+    pub prefix: String,
+    pub suffix: String,
+    pub text: Option<String>,
+}
+
+impl SliderState {
+    pub fn new(args: &crate::SetSliderArgs) -> Self {
+        let (min, max) = (args.min.unwrap_or(0.0), args.max.unwrap_or(100.0));
+        let mut value = args.value.clamp(min.min(max), min.max(max));
+        if args.integer {
+            value = value.round();
+        }
+        Self {
+            value,
+            min,
+            max,
+            step: args.step,
+            logarithmic: args.logarithmic,
+            integer: args.integer,
+            prefix: args.prefix.clone(),
+            suffix: args.suffix.clone(),
+            text: args.text.clone(),
+        }
+    }
+}
+
+/// How the text of an input is checked after every edit.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Validation {
+    /// The text must parse as a number, optionally an integer within bounds.
+    Numeric {
+        #[serde(default)]
+        integer: bool,
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+    /// The whole text must match `pattern`.
+    Regex { pattern: String },
+}
+
+/// A script-defined text input.
+#[derive(Debug, Clone)]
+pub struct TextInputState {
+    pub text: String,
+    pub multiline: bool,
+    pub password: bool,
+    pub hint: Option<String>,
+    pub max_length: Option<usize>,
+    pub validation: Option<Validation>,
+    /// Why the current text failed validation, if it did.
+    pub error: Option<String>,
+    pattern: Option<regex::Regex>,
+}
+
+impl TextInputState {
+    /// Fails if the validation pattern is not a valid regex.
+    pub fn new(args: &crate::SetInputArgs) -> Result<Self, String> {
+        let pattern = match &args.validation {
+            Some(Validation::Regex { pattern }) => Some(
+                regex::Regex::new(&format!("^(?:{pattern})$"))
+                    .map_err(|e| format!("Invalid pattern for input '{}': {}", args.id.as_deref().unwrap_or_default(), e))?,
+            ),
+            _ => None,
+        };
+        let mut input = Self {
+            text: args.text.clone(),
+            multiline: args.multiline,
+            password: args.password,
+            hint: args.hint.clone(),
+            max_length: args.max_length,
+            validation: args.validation.clone(),
+            error: None,
+            pattern,
+        };
+        input.error = input.validate().err();
+        Ok(input)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let text = self.text.trim();
+        match &self.validation {
+            None => Ok(()),
+            Some(Validation::Numeric { integer, min, max }) => {
+                let value: f64 = if *integer {
+                    text.parse::<i64>().map(|v| v as f64).map_err(|_| "Expected a whole number".to_string())?
+                } else {
+                    // `parse` accepts "NaN" and "inf", which no bound can reject.
+                    text.parse().ok().filter(|value: &f64| value.is_finite()).ok_or_else(|| "Expected a number".to_string())?
+                };
+                match (min, max) {
+                    (Some(min), _) if value < *min => Err(format!("Must be at least {min}")),
+                    (_, Some(max)) if value > *max => Err(format!("Must be at most {max}")),
+                    _ => Ok(()),
+                }
+            }
+            Some(Validation::Regex { pattern }) => match &self.pattern {
+                Some(regex) if !regex.is_match(&self.text) => Err(format!("Must match {pattern}")),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+impl EguiApp {
+    pub(crate) fn slider_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(slider) = self.sliders.get_mut(id) else { return };
+
+        let mut widget = egui::Slider::new(&mut slider.value, slider.min..=slider.max)
+            .logarithmic(slider.logarithmic)
+            .prefix(&slider.prefix)
+            .suffix(&slider.suffix);
+        if let Some(step) = slider.step {
+            widget = widget.step_by(step);
+        }
+        if slider.integer {
+            widget = widget.integer();
+        }
+        if let Some(text) = &slider.text {
+            widget = widget.text(text.as_str());
+        }
+
+        let response = ui.add(widget);
+        let rail = egui::Rect::from_min_size(
+            response.rect.min,
+            egui::vec2(ui.spacing().slider_width, response.rect.height()),
+        );
+        track(
+            &mut self.widgets,
+            id,
+            &response,
+            WidgetKind::Slider { rail, range: slider.min..=slider.max, logarithmic: slider.logarithmic },
+        );
+        if response.changed() {
+            let value = slider.value;
+            self.emit("slider_change", id, serde_json::json!({ "value": value }));
+        }
+    }
+
+    pub(crate) fn text_input_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(input) = self.text_inputs.get_mut(id) else { return };
+
+        let invalid = input.error.is_some();
+        let mut edit = if input.multiline {
+            egui::TextEdit::multiline(&mut input.text)
+        } else {
+            egui::TextEdit::singleline(&mut input.text)
+        }
+        .id_source(("input", id))
+        .password(input.password);
+        if let Some(hint) = &input.hint {
+            edit = edit.hint_text(hint.as_str());
+        }
+        if let Some(max_length) = input.max_length {
+            edit = edit.char_limit(max_length);
+        }
+        if invalid {
+            edit = edit.text_color(ui.visuals().error_fg_color);
+        }
+
+        let response = ui.add(edit);
+        track(&mut self.widgets, id, &response, WidgetKind::TextInput);
+
+        let changed = response.changed().then(|| {
+            input.error = input.validate().err();
+            (input.text.clone(), input.error.clone())
+        });
+        if let Some(error) = &input.error {
+            ui.colored_label(ui.visuals().error_fg_color, error.as_str());
+        }
+
+        if let Some((text, error)) = changed {
+            self.emit("input_change", id, serde_json::json!({ "text": text, "valid": error.is_none() }));
+            if let Some(error) = error {
+                self.emit("input_invalid", id, serde_json::json!({ "text": text, "error": error }));
+            }
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use std::borrow::Cow;
 use deno_core::{Extension, JsRuntime, RuntimeOptions};
-use egui::{CentralPanel, Slider};
+use egui::Slider;
 use egui_wgpu::renderer::ScreenDescriptor;
 use egui_winit::State as EguiWinitState;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::{Instant, Duration};
 use tokio::sync::mpsc::{UnboundedSender, UnboundedReceiver, unbounded_channel};
@@ -11,34 +12,162 @@ use tokio_tungstenite::{tungstenite::protocol::Message, accept_async};
 use futures_util::{StreamExt, SinkExt};
 use winit::{
     event::{Event, WindowEvent, VirtualKeyCode, ElementState},
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    event_loop::{ControlFlow, EventLoopBuilder},
+    window::{WindowBuilder, WindowId},
 };
 
+pub mod accessibility;
+pub mod automation;
+pub mod binding;
+pub mod clipboard;
+pub mod containers;
+pub mod dialogs;
+pub mod document;
+pub mod drag_drop;
+pub mod editors;
 pub mod events;
+pub mod headless;
+pub mod i18n;
+pub mod images;
+pub mod inputs;
+pub mod menus;
 pub mod ops;
+pub mod persistence;
+pub mod playback;
+pub mod plot;
+pub mod rich_text;
+pub mod scene;
+pub mod snapshot;
 pub mod state_3d;
+pub mod storage;
+pub mod table;
+pub mod theme;
+pub mod timeline;
+pub mod tree;
+pub mod tree_view;
+pub mod windows;
 
 // ----- Arg Structs -----
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SetLabelArgs {
+    /// Without an id, sets the text of the built-in label.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
     pub text: String,
+    /// Styled runs; replaces `text` if not empty.
+    #[serde(default)]
+    pub spans: Vec<rich_text::TextSpan>,
+    /// Parses `text` as markdown-ish markup, see [`rich_text::parse_markdown`].
+    #[serde(default)]
+    pub markdown: bool,
+    /// Translates the text from a catalog message; `text` is shown until
+    /// the label is first drawn.
+    #[serde(default)]
+    pub message: Option<i18n::Message>,
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
+/// `path` names a PNG or JPEG file; `bytes` holds one instead.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LoadImageArgs {
+    pub id: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub bytes: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetImageArgs {
+    pub id: String,
+    /// Id of an image loaded with `op_load_image`.
+    pub image: String,
+    #[serde(default)]
+    pub max_size: Option<[f32; 2]>,
+    #[serde(default)]
+    pub tooltip: Option<String>,
+    #[serde(default)]
+    pub clickable: bool,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+/// Without an `id`, sets the built-in slider; only `value` is used then.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SetSliderArgs {
-    pub value: f32,
+    #[serde(default)]
+    pub id: Option<String>,
+    pub value: f64,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub step: Option<f64>,
+    #[serde(default)]
+    pub logarithmic: bool,
+    #[serde(default)]
+    pub integer: bool,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub suffix: String,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
+/// Without an `id`, sets the built-in input; only `text` is used then.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SetInputArgs {
+    #[serde(default)]
+    pub id: Option<String>,
     pub text: String,
+    #[serde(default)]
+    pub multiline: bool,
+    #[serde(default)]
+    pub password: bool,
+    #[serde(default)]
+    pub hint: Option<String>,
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    #[serde(default)]
+    pub validation: Option<inputs::Validation>,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetButtonArgs {
+    pub id: String,
+    pub label: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub tooltip: Option<String>,
+    /// Icon name from [`images::ICONS`], or a glyph.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Translates the label from a catalog message instead.
+    #[serde(default)]
+    pub message: Option<i18n::Message>,
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SetCheckboxArgs {
     pub id: String,
     pub checked: bool,
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -46,18 +175,26 @@ pub struct SetComboBoxArgs {
     pub id: String,
     pub selected: String,
     pub options: Vec<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SetRadioArgs {
     pub id: String,
     pub selected: String,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SetProgressArgs {
     pub id: String,
     pub value: f32,
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -65,23 +202,602 @@ pub struct Rotate3DArgs {
     pub angle: f32,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Add3DObjectArgs {
+    pub scene_id: String,
+    pub object_id: String,
+    pub object_type: String,
+    pub size: f32,
+    #[serde(default)]
+    pub color: Option<[f32; 4]>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetObjectColorArgs {
+    pub object_id: String,
+    pub color: [f32; 4],
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SelectObjectArgs {
+    /// `None` clears the selection.
+    #[serde(default)]
+    pub object_id: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetContainerArgs {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(flatten)]
+    pub kind: containers::ContainerKind,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OpenWindowArgs {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub width: Option<f64>,
+    #[serde(default)]
+    pub height: Option<f64>,
+    #[serde(default)]
+    pub x: Option<f64>,
+    #[serde(default)]
+    pub y: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WindowIdArgs {
+    pub id: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetWindowTitleArgs {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetWindowSizeArgs {
+    pub id: String,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetWindowPositionArgs {
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AutomationQueryArgs {
+    pub id: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetTableArgs {
+    pub id: String,
+    pub columns: Vec<table::TableColumn>,
+    #[serde(default)]
+    pub rows: Vec<Vec<serde_json::Value>>,
+    #[serde(default)]
+    pub selection: table::SelectionMode,
+    #[serde(default = "default_true")]
+    pub filterable: bool,
+    #[serde(default = "default_true")]
+    pub striped: bool,
+    #[serde(default)]
+    pub row_height: Option<f32>,
+    #[serde(default)]
+    pub draggable: bool,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AppendTableRowsArgs {
+    pub id: String,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetPlotArgs {
+    pub id: String,
+    #[serde(default)]
+    pub x_label: Option<String>,
+    #[serde(default)]
+    pub y_label: Option<String>,
+    #[serde(default = "default_true")]
+    pub legend: bool,
+    #[serde(default)]
+    pub height: Option<f32>,
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    #[serde(default = "default_true")]
+    pub allow_zoom: bool,
+    #[serde(default = "default_true")]
+    pub allow_drag: bool,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetPlotSeriesArgs {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub kind: plot::SeriesKind,
+    #[serde(default)]
+    pub points: Vec<plot::PlotSample>,
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    #[serde(default)]
+    pub bins: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AppendPlotPointsArgs {
+    pub id: String,
+    pub series: String,
+    pub points: Vec<plot::PlotSample>,
+}
+
+/// With `outliner` set, `nodes` is ignored and the tree shows the 3D scene objects.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetTreeArgs {
+    pub id: String,
+    #[serde(default)]
+    pub nodes: Vec<tree_view::TreeNode>,
+    #[serde(default)]
+    pub multi_select: bool,
+    #[serde(default)]
+    pub draggable: bool,
+    #[serde(default)]
+    pub outliner: bool,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetTreeChildrenArgs {
+    pub id: String,
+    pub node: String,
+    pub children: Vec<tree_view::TreeNode>,
+}
+
+/// With `object` set, the edit shows and changes that scene object's material
+/// color, and `color` only applies if the object does not exist.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetColorEditArgs {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "scene::white")]
+    pub color: [f32; 4],
+    #[serde(default)]
+    pub mode: editors::ColorMode,
+    #[serde(default)]
+    pub object: Option<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+fn default_speed() -> f64 {
+    0.1
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetDragValueArgs {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub value: f64,
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub decimals: Option<usize>,
+    #[serde(default)]
+    pub prefix: String,
+    /// Unit shown after the value.
+    #[serde(default)]
+    pub suffix: String,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+/// `value` has 2 to 4 components.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetVectorArgs {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub value: Vec<f64>,
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    #[serde(default)]
+    pub suffix: String,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+/// `value` is `YYYY-MM-DD`, or `YYYY-MM-DDTHH:MM:SS` with a time.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetDateTimeArgs {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub value: String,
+    #[serde(default)]
+    pub with_time: bool,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+/// A timeline of `EguiApp::recorded_events`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetTimelineArgs {
+    pub id: String,
+    #[serde(default)]
+    pub row_height: Option<f32>,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RecordingFileArgs {
+    pub filename: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SeekPlaybackArgs {
+    pub timestamp: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PlaybackRateArgs {
+    pub rate: f64,
+}
+
+/// Without `start` and `end` playback stops looping.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PlaybackLoopArgs {
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+}
+
+/// Without `window` the menu bar belongs to the main window.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetMenuBarArgs {
+    #[serde(default)]
+    pub window: Option<String>,
+    pub menus: Vec<menus::MenuItem>,
+}
+
+/// `target` is a widget id or a scene object id. No items removes the menu.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetContextMenuArgs {
+    pub target: String,
+    pub items: Vec<menus::MenuItem>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetToolbarArgs {
+    pub id: String,
+    pub items: Vec<menus::MenuItem>,
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+/// `keys` is e.g. `"Ctrl+S"`; see [`menus::parse_shortcut`].
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RegisterShortcutArgs {
+    pub command: String,
+    pub keys: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UnregisterShortcutArgs {
+    pub command: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ConfirmArgs {
+    pub title: String,
+    #[serde(default)]
+    pub message: String,
+    /// Defaults to OK and Cancel.
+    #[serde(default)]
+    pub buttons: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PromptArgs {
+    pub title: String,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub hint: String,
+}
+
+fn default_toast_duration() -> f64 {
+    4.0
+}
+
+/// `duration` is in seconds; 0 keeps the toast until it is clicked.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ToastArgs {
+    pub message: String,
+    #[serde(default)]
+    pub level: dialogs::ToastLevel,
+    #[serde(default = "default_toast_duration")]
+    pub duration: f64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct FileDialogArgs {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub mode: dialogs::FileMode,
+    /// Starts in the working directory if unset.
+    #[serde(default)]
+    pub directory: Option<String>,
+    #[serde(default)]
+    pub file_name: String,
+    /// Extensions without the dot, e.g. `["json"]`.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+/// Unset fields keep their current value.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetVisualsArgs {
+    #[serde(default)]
+    pub base: Option<theme::ThemeBase>,
+    #[serde(default)]
+    pub colors: theme::ThemeColors,
+    #[serde(default)]
+    pub rounding: Option<f32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetTextStyleArgs {
+    /// `small`, `body`, `monospace`, `button`, `heading` or a custom style.
+    pub name: String,
+    #[serde(flatten)]
+    pub spec: theme::TextStyleSpec,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetUiScaleArgs {
+    pub scale: f32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ThemeFileArgs {
+    pub path: String,
+}
+
+/// Makes widget `id` a drop target for items of the widgets in `accept`, or
+/// stops it being one if `accept` is null. See [`drag_drop::DropTarget`].
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetDropTargetArgs {
+    pub id: String,
+    #[serde(default)]
+    pub accept: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetClipboardTextArgs {
+    pub text: String,
+}
+
+/// Replaces what assistive technology is told about widget `id`. Unset
+/// fields keep what egui derives from the widget.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetAccessibilityArgs {
+    pub id: String,
+    #[serde(flatten)]
+    pub info: accessibility::AccessInfo,
+}
+
+/// Adds the messages of a `.ftl` or `.json` catalog file to `locale`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LoadCatalogArgs {
+    pub locale: String,
+    pub path: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetLocaleArgs {
+    pub locale: String,
+    /// Font for the script of the locale; an installed system font is
+    /// looked for if not given.
+    #[serde(default)]
+    pub font: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TranslateArgs {
+    pub key: String,
+    #[serde(default)]
+    pub args: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Opts widget `id` in or out of persistence; without an id, sets whether
+/// widgets without a choice of their own are persisted.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetPersistArgs {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub persist: bool,
+}
+
+/// Without ids, clears the whole store.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ClearPersistedArgs {
+    #[serde(default)]
+    pub ids: Option<Vec<String>>,
+}
+
+/// A key in the store of a script. `namespace` keeps the keys of scripts
+/// apart; it names a file in the data directory.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StorageKeyArgs {
+    pub namespace: String,
+    pub key: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StorageSetArgs {
+    pub namespace: String,
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StorageListArgs {
+    pub namespace: String,
+    #[serde(default)]
+    pub prefix: String,
+}
+
+/// Declares or sets the observable `name`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ObservableArgs {
+    pub name: String,
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ObservableNameArgs {
+    pub name: String,
+}
+
+/// Binds `widget` to observable `name`; a null name unbinds it.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BindArgs {
+    pub widget: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// `path` names a `.json`, `.ron` or `.yaml` UI document.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LoadUiArgs {
+    pub path: String,
+    /// Reloads the document whenever the file changes.
+    #[serde(default = "default_true")]
+    pub watch: bool,
+}
+
 // ----- App State -----
+#[derive(Debug, Clone)]
+pub struct ButtonState {
+    pub label: String,
+    pub enabled: bool,
+    pub tooltip: Option<String>,
+    pub icon: Option<String>,
+    pub message: Option<i18n::Message>,
+}
+
+impl ButtonState {
+    /// The label in the current locale.
+    pub fn text(&self, i18n: &i18n::I18n) -> String {
+        match &self.message {
+            Some(message) => i18n.message(message),
+            None => self.label.clone(),
+        }
+    }
+}
+
 pub struct EguiApp {
     pub label_text: String,
     pub slider_value: f32,
     pub input_text: String,
-    pub checkboxes: std::collections::HashMap<String, bool>,
-    pub combo_boxes: std::collections::HashMap<String, (String, Vec<String>)>,
-    pub radio_groups: std::collections::HashMap<String, String>,
-    pub progress_bars: std::collections::HashMap<String, f32>,
+    pub labels: BTreeMap<String, rich_text::LabelState>,
+    pub buttons: BTreeMap<String, ButtonState>,
+    pub sliders: BTreeMap<String, inputs::SliderState>,
+    pub text_inputs: BTreeMap<String, inputs::TextInputState>,
+    pub tables: BTreeMap<String, table::TableState>,
+    pub plots: BTreeMap<String, plot::PlotState>,
+    pub tree_views: BTreeMap<String, tree_view::TreeViewState>,
+    pub color_edits: BTreeMap<String, editors::ColorEditState>,
+    pub drag_values: BTreeMap<String, editors::DragValueState>,
+    pub vectors: BTreeMap<String, editors::VectorState>,
+    pub date_times: BTreeMap<String, editors::DateTimeState>,
+    pub checkboxes: BTreeMap<String, bool>,
+    pub combo_boxes: BTreeMap<String, (String, Vec<String>)>,
+    pub radio_groups: BTreeMap<String, (String, Vec<String>)>,
+    pub progress_bars: BTreeMap<String, f32>,
     pub rotation: f32,
+    /// Objects added to 3D scenes by scripts, keyed by object id.
+    pub scene_objects: BTreeMap<String, scene::SceneObject>,
+    pub selected_object: Option<String>,
     pub is_recording: bool,
     pub recorded_events: Vec<events::RecordedEvent>,
+    /// The state the recording began in, then keyframes in timestamp order.
+    pub recorded_snapshots: Vec<snapshot::Snapshot>,
     pub recording_start: Option<std::time::Instant>,
     pub is_playing: bool,
     pub playback_index: usize,
-    pub playback_start: Option<std::time::Instant>,
+    pub playback: playback::Playback,
     pub event_sender: UnboundedSender<String>,
+    /// Widgets laid out in the last frame, keyed by id.
+    pub widgets: BTreeMap<String, automation::TrackedWidget>,
+    pub automation: automation::Automation,
+    pub tree: tree::WidgetTree,
+    pub containers: BTreeMap<String, containers::Container>,
+    /// Window changes requested by scripts, applied by the event loop.
+    pub window_commands: Vec<windows::WindowCommand>,
+    /// Menu bars keyed by the id of the window they belong to.
+    pub menu_bars: BTreeMap<String, Vec<menus::MenuItem>>,
+    /// Context menus keyed by the widget or scene object they open on.
+    pub context_menus: BTreeMap<String, Vec<menus::MenuItem>>,
+    pub toolbars: BTreeMap<String, Vec<menus::MenuItem>>,
+    pub timelines: BTreeMap<String, timeline::TimelineState>,
+    /// Keyboard shortcuts keyed by the command they trigger.
+    pub shortcuts: BTreeMap<String, egui::KeyboardShortcut>,
+    /// Shortcuts of menu items, keyed by what shows the items.
+    pub item_shortcuts: BTreeMap<menus::ShortcutOwner, Vec<(String, egui::KeyboardShortcut)>>,
+    /// Modal dialogs waiting for an answer; the first one is shown.
+    pub dialogs: VecDeque<dialogs::Dialog>,
+    pub toasts: Vec<dialogs::Toast>,
+    pub next_toast: u64,
+    pub theme: theme::ThemeState,
+    /// Images loaded by scripts, keyed by image id.
+    pub images: BTreeMap<String, images::ImageData>,
+    pub image_views: BTreeMap<String, images::ImageView>,
+    /// Drop targets keyed by widget id.
+    pub drop_targets: BTreeMap<String, drag_drop::DropTarget>,
+    /// The item being dragged between widgets, if any.
+    pub dragging: Option<drag_drop::DragPayload>,
+    /// Clipboard accesses requested by scripts, served by the event loop.
+    pub clipboard_requests: Vec<clipboard::ClipboardRequest>,
+    /// Accessibility info set by scripts, keyed by widget id.
+    pub accessibility: BTreeMap<String, accessibility::AccessInfo>,
+    /// Scripts waiting for the accessibility tree of the next frame.
+    pub accessibility_requests: Vec<tokio::sync::oneshot::Sender<serde_json::Value>>,
+    pub i18n: i18n::I18n,
+    pub persistence: persistence::Persistence,
+    /// Values stored by scripts in the data directory.
+    pub storage: storage::Storage,
+    pub bindings: binding::Bindings,
+    /// UI documents loaded with `op_load_ui` or `--ui`.
+    pub documents: document::Documents,
 }
 
 impl Default for EguiApp {
@@ -91,29 +807,372 @@ impl Default for EguiApp {
             label_text: String::new(),
             slider_value: 0.0,
             input_text: String::new(),
-            checkboxes: std::collections::HashMap::new(),
-            combo_boxes: std::collections::HashMap::new(),
-            radio_groups: std::collections::HashMap::new(),
-            progress_bars: std::collections::HashMap::new(),
+            labels: BTreeMap::new(),
+            buttons: BTreeMap::new(),
+            sliders: BTreeMap::new(),
+            text_inputs: BTreeMap::new(),
+            tables: BTreeMap::new(),
+            plots: BTreeMap::new(),
+            tree_views: BTreeMap::new(),
+            color_edits: BTreeMap::new(),
+            drag_values: BTreeMap::new(),
+            vectors: BTreeMap::new(),
+            date_times: BTreeMap::new(),
+            checkboxes: BTreeMap::new(),
+            combo_boxes: BTreeMap::new(),
+            radio_groups: BTreeMap::new(),
+            progress_bars: BTreeMap::new(),
             rotation: 0.0,
+            scene_objects: BTreeMap::new(),
+            selected_object: None,
             is_recording: false,
             recorded_events: Vec::new(),
+            recorded_snapshots: Vec::new(),
             recording_start: None,
             is_playing: false,
             playback_index: 0,
-            playback_start: None,
+            playback: playback::Playback::default(),
             event_sender: tx,
+            widgets: BTreeMap::new(),
+            automation: automation::Automation::default(),
+            tree: tree::WidgetTree::default(),
+            containers: BTreeMap::new(),
+            window_commands: Vec::new(),
+            menu_bars: BTreeMap::new(),
+            context_menus: BTreeMap::new(),
+            toolbars: BTreeMap::new(),
+            timelines: BTreeMap::new(),
+            shortcuts: BTreeMap::new(),
+            item_shortcuts: BTreeMap::new(),
+            dialogs: VecDeque::new(),
+            toasts: Vec::new(),
+            next_toast: 0,
+            theme: theme::ThemeState::default(),
+            images: BTreeMap::new(),
+            image_views: BTreeMap::new(),
+            drop_targets: BTreeMap::new(),
+            dragging: None,
+            clipboard_requests: Vec::new(),
+            accessibility: BTreeMap::new(),
+            accessibility_requests: Vec::new(),
+            i18n: i18n::I18n::default(),
+            persistence: persistence::Persistence::default(),
+            storage: storage::Storage::new(Options::default().data_dir),
+            bindings: binding::Bindings::default(),
+            documents: document::Documents::default(),
         }
     }
 }
 
-// ----- Extension Creation -----
-pub fn init_ext(sender: UnboundedSender<String>) -> Extension {
-    let app = Arc::new(Mutex::new(EguiApp {
-        event_sender: sender,
-        ..Default::default()
-    }));
+fn track(
+    widgets: &mut BTreeMap<String, automation::TrackedWidget>,
+    id: impl Into<String>,
+    response: &egui::Response,
+    kind: automation::WidgetKind,
+) {
+    widgets.insert(id.into(), automation::TrackedWidget { rect: response.rect, kind, response: response.clone() });
+}
+
+impl EguiApp {
+    /// Sends an event to the script side as a JSON object with `type`, `id`
+    /// and the fields of `event_data`, and records it while recording.
+    pub fn emit(&mut self, event_type: &str, component_id: &str, event_data: serde_json::Value) {
+        if self.is_recording {
+            let timestamp = self
+                .recording_start
+                .map_or(0, |start| start.elapsed().as_millis() as u64);
+            self.recorded_events.push(events::RecordedEvent {
+                event_type: event_type.to_string(),
+                component_id: component_id.to_string(),
+                event_data: event_data.clone(),
+                timestamp,
+            });
+        }
 
+        let mut message = serde_json::json!({ "type": event_type, "id": component_id });
+        if let (Some(message), serde_json::Value::Object(data)) = (message.as_object_mut(), event_data) {
+            message.extend(data);
+        }
+        // Nobody may be listening, e.g. in tests.
+        let _ = self.event_sender.send(message.to_string());
+    }
+
+    /// Attaches widget `id` under `parent`, or under the main window if it has
+    /// no parent yet and none is given.
+    pub fn place(&mut self, id: &str, parent: Option<&str>) {
+        match parent {
+            Some(parent) => {
+                if !self.tree.attach(id, parent) {
+                    eprintln!("Cannot attach '{}' under its own descendant '{}'", id, parent);
+                }
+            }
+            None if !self.tree.contains(id) => {
+                self.tree.attach(id, tree::MAIN_WINDOW);
+            }
+            None => {}
+        }
+    }
+
+    /// Builds the UI for one frame. Must be called between `begin_frame` and `end_frame`.
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        self.widgets.clear();
+        self.watch_ui_documents();
+        self.restore_persisted();
+        self.apply_bindings();
+        self.playback_ui();
+
+        // Does nothing once the loaders are installed.
+        egui_extras::install_image_loaders(ctx);
+        self.theme_ui(ctx);
+        self.shortcuts_ui(ctx);
+        self.root_ui(ctx, tree::MAIN_WINDOW, |app, ui| app.main_contents_ui(ui));
+        self.context_menus_ui(ctx);
+        self.drag_drop_ui(ctx);
+        self.file_drops_ui(ctx, tree::MAIN_WINDOW);
+        self.clipboard_ui(ctx, tree::MAIN_WINDOW);
+        self.dialogs_ui(ctx);
+        self.toasts_ui(ctx);
+        self.accessibility_ui(ctx);
+        self.sync_bindings();
+        self.snapshot_recording();
+    }
+
+    fn main_contents_ui(&mut self, ui: &mut egui::Ui) {
+        use automation::WidgetKind;
+
+        ui.heading("Pioneer eGUI Timeline Example");
+        let response = ui.label(&self.label_text);
+        track(&mut self.widgets, "label", &response, WidgetKind::Label);
+
+        // Additional UI controls
+        ui.horizontal(|ui| {
+            let response = ui.button("Start Recording");
+            track(&mut self.widgets, "startRecording", &response, WidgetKind::Button);
+            if response.clicked() {
+                self.start_recording();
+            }
+            let response = ui.button("Stop Recording");
+            track(&mut self.widgets, "stopRecording", &response, WidgetKind::Button);
+            if response.clicked() {
+                self.is_recording = false;
+                self.recording_start = None;
+            }
+            let response = ui.button("Start Playback");
+            track(&mut self.widgets, "startPlayback", &response, WidgetKind::Button);
+            if response.clicked() {
+                if let Err(e) = self.start_playback() {
+                    self.emit("playback_error", "playback", serde_json::json!({ "message": e }));
+                }
+            }
+            let response = ui.button("Stop Playback");
+            track(&mut self.widgets, "stopPlayback", &response, WidgetKind::Button);
+            if response.clicked() {
+                self.stop_playback();
+            }
+        });
+
+        let range = 0.0..=100.0;
+        let response = ui.add(Slider::new(&mut self.slider_value, range.clone()).text("Value"));
+        let rail = egui::Rect::from_min_size(
+            response.rect.min,
+            egui::vec2(ui.spacing().slider_width, response.rect.height()),
+        );
+        track(&mut self.widgets, "slider", &response, WidgetKind::Slider {
+            rail,
+            range: *range.start() as f64..=*range.end() as f64,
+            logarithmic: false,
+        });
+        if response.changed() {
+            self.emit("slider_change", "slider", serde_json::json!({ "value": self.slider_value }));
+        }
+
+        let response = ui.text_edit_singleline(&mut self.input_text);
+        track(&mut self.widgets, "input", &response, WidgetKind::TextInput);
+        if response.changed() {
+            self.emit("input_change", "input", serde_json::json!({ "text": self.input_text }));
+        }
+
+        // Widgets that were never placed show up in the main window
+        for id in self.unplaced_widgets() {
+            self.widget_ui(ui, &id);
+        }
+    }
+
+    /// Builds the UI of the additional native window `window_id`.
+    pub fn window_ui(&mut self, ctx: &egui::Context, window_id: &str) {
+        egui_extras::install_image_loaders(ctx);
+        self.theme_ui(ctx);
+        self.shortcuts_ui(ctx);
+        self.root_ui(ctx, window_id, |_, _| {});
+        self.context_menus_ui(ctx);
+        self.drag_drop_ui(ctx);
+        self.file_drops_ui(ctx, window_id);
+        self.clipboard_ui(ctx, window_id);
+        self.accessibility_ui(ctx);
+    }
+
+    fn children_ui(&mut self, ui: &mut egui::Ui, parent: &str) {
+        for id in self.tree.children(parent).to_vec() {
+            self.widget_ui(ui, &id);
+        }
+    }
+
+    fn unplaced_widgets(&self) -> Vec<String> {
+        self.labels.keys()
+            .chain(self.buttons.keys())
+            .chain(self.image_views.keys())
+            .chain(self.sliders.keys())
+            .chain(self.text_inputs.keys())
+            .chain(self.tables.keys())
+            .chain(self.plots.keys())
+            .chain(self.tree_views.keys())
+            .chain(self.color_edits.keys())
+            .chain(self.drag_values.keys())
+            .chain(self.vectors.keys())
+            .chain(self.date_times.keys())
+            .chain(self.toolbars.keys())
+            .chain(self.timelines.keys())
+            .chain(self.checkboxes.keys())
+            .chain(self.combo_boxes.keys())
+            .chain(self.radio_groups.keys())
+            .chain(self.progress_bars.keys())
+            .chain(self.containers.keys())
+            .filter(|id| !self.tree.contains(id))
+            .cloned()
+            .collect()
+    }
+
+    /// Deletes widget `id` with its state, saved state, accessibility info
+    /// and binding, and detaches it from the tree.
+    pub fn remove_widget(&mut self, id: &str) {
+        self.labels.remove(id);
+        self.buttons.remove(id);
+        self.image_views.remove(id);
+        self.sliders.remove(id);
+        self.text_inputs.remove(id);
+        self.tables.remove(id);
+        self.plots.remove(id);
+        self.tree_views.remove(id);
+        self.color_edits.remove(id);
+        self.drag_values.remove(id);
+        self.vectors.remove(id);
+        self.date_times.remove(id);
+        self.toolbars.remove(id);
+        self.timelines.remove(id);
+        self.checkboxes.remove(id);
+        self.combo_boxes.remove(id);
+        self.radio_groups.remove(id);
+        self.progress_bars.remove(id);
+        self.containers.remove(id);
+        self.drop_targets.remove(id);
+        self.context_menus.remove(id);
+        self.item_shortcuts.remove(&menus::ShortcutOwner::Toolbar(id.to_string()));
+        self.item_shortcuts.remove(&menus::ShortcutOwner::ContextMenu(id.to_string()));
+        self.accessibility.remove(id);
+        self.persistence.state.widgets.remove(id);
+        self.persistence.pending.remove(id);
+        self.persistence.overrides.remove(id);
+        // Unbinding cannot fail.
+        let _ = self.bindings.bind(id, None);
+        self.tree.detach(id);
+    }
+
+    fn widget_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        use automation::WidgetKind;
+
+        if self.containers.contains_key(id) {
+            self.container_ui(ui, id);
+        } else if self.labels.contains_key(id) {
+            self.label_ui(ui, id);
+        } else if let Some(button) = self.buttons.get(id) {
+            let label = button.text(&self.i18n);
+            let label = i18n::visual_order(&label);
+            let text = match &button.icon {
+                Some(icon) => format!("{} {}", images::icon(icon), label),
+                None => label.into_owned(),
+            };
+            let mut response = ui.add_enabled(button.enabled, egui::Button::new(text));
+            if let Some(tooltip) = &button.tooltip {
+                response = response.on_hover_text(tooltip.as_str());
+            }
+            track(&mut self.widgets, id, &response, WidgetKind::Button);
+            if response.clicked() {
+                self.emit("button_click", id, serde_json::json!({}));
+            }
+        } else if self.image_views.contains_key(id) {
+            self.image_view_ui(ui, id);
+        } else if self.sliders.contains_key(id) {
+            self.slider_ui(ui, id);
+        } else if self.text_inputs.contains_key(id) {
+            self.text_input_ui(ui, id);
+        } else if self.tables.contains_key(id) {
+            self.table_ui(ui, id);
+        } else if self.plots.contains_key(id) {
+            self.plot_ui(ui, id);
+        } else if self.tree_views.contains_key(id) {
+            self.tree_view_ui(ui, id);
+        } else if self.color_edits.contains_key(id) {
+            self.color_edit_ui(ui, id);
+        } else if self.drag_values.contains_key(id) {
+            self.drag_value_ui(ui, id);
+        } else if self.vectors.contains_key(id) {
+            self.vector_ui(ui, id);
+        } else if self.date_times.contains_key(id) {
+            self.date_time_ui(ui, id);
+        } else if self.toolbars.contains_key(id) {
+            self.toolbar_ui(ui, id);
+        } else if self.timelines.contains_key(id) {
+            self.timeline_ui(ui, id);
+        } else if let Some(checked) = self.checkboxes.get_mut(id) {
+            let response = ui.checkbox(checked, id);
+            let checked = *checked;
+            track(&mut self.widgets, id, &response, WidgetKind::Checkbox);
+            if response.changed() {
+                self.emit(&format!("checkbox_{id}"), id, serde_json::json!({ "checked": checked }));
+            }
+        } else if let Some((selected, options)) = self.combo_boxes.get_mut(id) {
+            let widgets = &mut self.widgets;
+            let inner = egui::ComboBox::from_label(id)
+                .selected_text(selected.as_str())
+                .show_ui(ui, |ui| {
+                    let mut changed = false;
+                    for option in options.iter() {
+                        let response = ui.selectable_value(selected, option.clone(), option.as_str());
+                        track(widgets, format!("{id}/{option}"), &response, WidgetKind::ComboOption);
+                        changed |= response.changed();
+                    }
+                    changed
+                });
+            track(widgets, id, &inner.response, WidgetKind::ComboBox);
+            if inner.inner == Some(true) {
+                let selected = selected.clone();
+                self.emit(&format!("combo_{id}"), id, serde_json::json!({ "selected": selected }));
+            }
+        } else if let Some((selected, options)) = self.radio_groups.get_mut(id) {
+            let widgets = &mut self.widgets;
+            let changed = ui.horizontal(|ui| {
+                ui.label(id);
+                let mut changed = false;
+                for option in options.iter() {
+                    let response = ui.radio_value(selected, option.clone(), option.as_str());
+                    track(widgets, format!("{id}/{option}"), &response, WidgetKind::Radio);
+                    changed |= response.changed();
+                }
+                changed
+            }).inner;
+            if changed {
+                let selected = selected.clone();
+                self.emit(&format!("radio_{id}"), id, serde_json::json!({ "selected": selected }));
+            }
+        } else if let Some(value) = self.progress_bars.get(id) {
+            let response = ui.add(egui::ProgressBar::new(value / 100.0).show_percentage());
+            track(&mut self.widgets, id, &response, WidgetKind::ProgressBar);
+        }
+    }
+}
+
+// ----- Extension Creation -----
+pub fn init_ext(app: Arc<Mutex<EguiApp>>) -> Extension {
     Extension {
         name: "pioneer-egui",
         ops: ops::init_ops().into(),
@@ -125,8 +1184,18 @@ pub fn init_ext(sender: UnboundedSender<String>) -> Extension {
 }
 
 // ---- WEBSOCKET SERVER ----
-async fn start_ws_server(tx: UnboundedSender<String>) {
+async fn start_ws_server(tx: UnboundedSender<String>, mut app_events: UnboundedReceiver<String>) {
     let addr = "127.0.0.1:9001";
+
+    // Fan app events out to every connected client
+    let (broadcast_tx, _) = tokio::sync::broadcast::channel::<String>(256);
+    let forward_tx = broadcast_tx.clone();
+    tokio::spawn(async move {
+        while let Some(event) = app_events.recv().await {
+            let _ = forward_tx.send(event);
+        }
+    });
+
     let try_socket = tokio::net::TcpListener::bind(&addr).await;
     let listener = try_socket.expect("Failed to bind WebSocket server");
     println!("WebSocket server listening on ws://{}", addr);
@@ -136,6 +1205,7 @@ async fn start_ws_server(tx: UnboundedSender<String>) {
             Ok((stream, addr)) => {
                 println!("Incoming TCP connection from: {}", addr);
                 let tx = tx.clone();
+                let mut app_events = broadcast_tx.subscribe();
                 tokio::spawn(async move {
                     let ws_stream = match tokio_tungstenite::accept_async(stream).await {
                         Ok(ws_stream) => {
@@ -159,24 +1229,38 @@ async fn start_ws_server(tx: UnboundedSender<String>) {
                         }
                     }
 
-                    while let Some(message) = read.next().await {
-                        match message {
-                            Ok(Message::Text(text)) => {
-                                println!("Received message from {}: {}", addr, text);
-                                if let Err(e) = tx.send(text) {
-                                    eprintln!("Failed to forward message: {}", e);
+                    loop {
+                        tokio::select! {
+                            message = read.next() => match message {
+                                Some(Ok(Message::Text(text))) => {
+                                    println!("Received message from {}: {}", addr, text);
+                                    if let Err(e) = tx.send(text) {
+                                        eprintln!("Failed to forward message: {}", e);
+                                        break;
+                                    }
+                                }
+                                Some(Ok(Message::Close(_))) | None => {
+                                    println!("WebSocket connection closed by client: {}", addr);
                                     break;
                                 }
-                            }
-                            Ok(Message::Close(_)) => {
-                                println!("WebSocket connection closed by client: {}", addr);
-                                break;
-                            }
-                            Err(e) => {
-                                eprintln!("WebSocket error from {}: {}", addr, e);
-                                break;
-                            }
-                            _ => {}
+                                Some(Err(e)) => {
+                                    eprintln!("WebSocket error from {}: {}", addr, e);
+                                    break;
+                                }
+                                _ => {}
+                            },
+                            event = app_events.recv() => match event {
+                                Ok(event) => {
+                                    if let Err(e) = write.send(Message::Text(event)).await {
+                                        eprintln!("Failed to send event to {}: {}", addr, e);
+                                        break;
+                                    }
+                                }
+                                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                                    eprintln!("Client {} lagged, dropped {} events", addr, skipped);
+                                }
+                                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                            },
                         }
                     }
                 });
@@ -190,11 +1274,56 @@ async fn start_ws_server(tx: UnboundedSender<String>) {
 }
 
 // ---- MAIN RUNTIME ----
+/// Command line options.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Where UI state is kept across restarts; `None` with `--no-persist`.
+    pub state_file: Option<std::path::PathBuf>,
+    /// Where scripts store values, one file per namespace.
+    pub data_dir: std::path::PathBuf,
+    /// UI documents loaded at startup and reloaded when they change.
+    pub ui_files: Vec<std::path::PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { state_file: Some("pioneer_state.json".into()), data_dir: "pioneer_data".into(), ui_files: Vec::new() }
+    }
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--state-file" => {
+                    options.state_file = Some(args.next().ok_or("--state-file needs a path")?.into());
+                }
+                "--no-persist" => options.state_file = None,
+                "--data-dir" => {
+                    options.data_dir = args.next().ok_or("--data-dir needs a directory")?.into();
+                }
+                "--ui" => {
+                    options.ui_files.push(args.next().ok_or("--ui needs a path")?.into());
+                }
+                other => return Err(format!("Unknown option '{}'", other)),
+            }
+        }
+        Ok(options)
+    }
+}
+
 pub fn main() {
     println!("Starting Pioneer eGUI Timeline Example");
+    let options = Options::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("Usage: pioneer-egui [--state-file <path> | --no-persist] [--data-dir <dir>] [--ui <path>]...");
+        std::process::exit(2);
+    });
 
-    // Channel to forward events to Deno
+    // Channels for messages from Deno and app events to Deno
     let (tx, rx) = unbounded_channel();
+    let (event_tx, event_rx) = unbounded_channel();
     println!("Created event channels");
 
     // Try to kill any existing process using port 9001
@@ -221,7 +1350,7 @@ pub fn main() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
             println!("Starting WebSocket server...");
-            start_ws_server(tx_ws).await;
+            start_ws_server(tx_ws, event_rx).await;
         });
     });
 
@@ -231,14 +1360,16 @@ pub fn main() {
 
     // Run the eGUI runtime on the main thread
     println!("Starting eGUI runtime on main thread...");
-    run_egui_runtime(rx);
+    run_egui_runtime(rx, event_tx, options);
 }
 
-pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
-    let event_loop = EventLoop::new();
+pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>, event_sender: UnboundedSender<String>, options: Options) {
+    let event_loop = EventLoopBuilder::<accessibility::UserEvent>::with_user_event().build();
+    // AccessKit must be set up before the window is shown.
     let window = WindowBuilder::new()
         .with_title("Pioneer eGUI Timeline Example")
         .with_inner_size(winit::dpi::LogicalSize::new(1024.0, 768.0))
+        .with_visible(false)
         .build(&event_loop)
         .unwrap();
 
@@ -254,22 +1385,45 @@ pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
         1,
     );
 
-    // Create app state
-    let app = Arc::new(Mutex::new(EguiApp::default()));
+    // Create app state, shared with the ops
+    let mut app = EguiApp { event_sender, ..Default::default() };
+    app.theme.system_dark = window.theme() != Some(winit::window::Theme::Light);
+    if let Some(path) = options.state_file {
+        match persistence::Persistence::open(path) {
+            Ok(persistence) => app.persistence = persistence,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    app.persistence.restore_memory(tree::MAIN_WINDOW, &egui_ctx);
+    app.storage = storage::Storage::new(options.data_dir);
+    for path in &options.ui_files {
+        if let Err(e) = app.load_ui_document(path, true) {
+            eprintln!("{}", e);
+        }
+    }
+    let app = Arc::new(Mutex::new(app));
 
     // Deno runtime + extension
-    let (tx_ws, _rx_ws) = unbounded_channel();
-    let ext = init_ext(tx_ws.clone());
+    let ext = init_ext(app.clone());
     let mut js_runtime = JsRuntime::new(RuntimeOptions {
         extensions: vec![ext],
         ..Default::default()
     });
 
     let mut egui_state = EguiWinitState::new(&event_loop);
+    let proxy = event_loop.create_proxy();
+    egui_ctx.enable_accesskit();
+    let placeholder_ctx = egui_ctx.clone();
+    egui_state.init_accesskit(&window, proxy.clone(), move || placeholder_ctx.accesskit_placeholder_tree_update());
+    window.set_visible(true);
+    let mut clipboard = clipboard::Clipboard::new(&event_loop);
     let mut last_update = Instant::now();
 
+    // Additional OS windows opened by scripts
+    let mut native_windows: HashMap<WindowId, windows::NativeWindow> = HashMap::new();
+
     // The winit event loop
-    event_loop.run(move |event, _, control_flow| {
+    event_loop.run(move |event, target, control_flow| {
         *control_flow = ControlFlow::Poll;
 
         // Process any pending events from Deno
@@ -278,7 +1432,18 @@ pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
         }
 
         match event {
+            Event::WindowEvent { window_id, event } if window_id != window.id() => {
+                let Some(native) = native_windows.get_mut(&window_id) else { return };
+                windows::report_event(&mut app.lock().unwrap(), &native.id, &event);
+                if let WindowEvent::CloseRequested = event {
+                    app.lock().unwrap().persistence.remember_memory(&native.id, native.egui_ctx());
+                    native_windows.remove(&window_id);
+                    return;
+                }
+                native.on_event(&state_3d.device, &event);
+            }
             Event::WindowEvent { event, .. } => {
+                windows::report_event(&mut app.lock().unwrap(), tree::MAIN_WINDOW, &event);
                 if !state_3d.input(&event) {
                     match event {
                         WindowEvent::CloseRequested => {
@@ -290,9 +1455,15 @@ pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
                         WindowEvent::ScaleFactorChanged { ref new_inner_size, .. } => {
                             state_3d.resize(**new_inner_size);
                         }
+                        WindowEvent::ThemeChanged(theme) => {
+                            let mut app = app.lock().unwrap();
+                            app.theme.system_dark = theme == winit::window::Theme::Dark;
+                            app.theme.changed();
+                        }
                         WindowEvent::KeyboardInput { input, .. } => {
                             if let Some(VirtualKeyCode::Escape) = input.virtual_keycode {
-                                if input.state == ElementState::Pressed {
+                                // Escape closes an open dialog instead.
+                                if input.state == ElementState::Pressed && app.lock().unwrap().dialogs.is_empty() {
                                     *control_flow = ControlFlow::Exit;
                                 }
                             }
@@ -304,6 +1475,18 @@ pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
                 // Handle egui input
                 let _ = egui_state.on_event(&egui_ctx, &event);
             }
+            Event::UserEvent(accessibility::UserEvent::AccessKit(event)) => {
+                if event.window_id == window.id() {
+                    egui_state.on_accesskit_action_request(event.request);
+                } else if let Some(native) = native_windows.get_mut(&event.window_id) {
+                    native.on_accesskit_action_request(event.request);
+                }
+            }
+            Event::RedrawRequested(window_id) if window_id != window.id() => {
+                if let Some(native) = native_windows.get_mut(&window_id) {
+                    native.redraw(&mut app.lock().unwrap(), &state_3d);
+                }
+            }
             Event::RedrawRequested(_) => {
                 // Limit frame rate
                 let now = Instant::now();
@@ -315,45 +1498,23 @@ pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
 
                 // eGUI pass
                 let mut app = app.lock().unwrap();
-                let raw_input = egui_state.take_egui_input(&window);
+                let pixels_per_point = app.pixels_per_point(window.scale_factor() as f32);
+                egui_state.set_pixels_per_point(pixels_per_point);
+                let mut raw_input = egui_state.take_egui_input(&window);
+                let app_state = &mut *app;
+                app_state.automation.inject(&app_state.widgets, &mut raw_input);
                 egui_ctx.begin_frame(raw_input);
 
-                CentralPanel::default().show(&egui_ctx, |ui| {
-                    ui.heading("Pioneer eGUI Timeline Example");
-                    ui.label(&app.label_text);
-
-                    // Additional UI controls
-                    ui.horizontal(|ui| {
-                        if ui.button("Start Recording").clicked() {
-                            app.is_recording = true;
-                            app.recorded_events.clear();
-                            app.recording_start = Some(Instant::now());
-                        }
-                        if ui.button("Stop Recording").clicked() {
-                            app.is_recording = false;
-                            app.recording_start = None;
-                        }
-                        if ui.button("Start Playback").clicked() {
-                            app.is_playing = true;
-                            app.playback_index = 0;
-                            app.playback_start = Some(Instant::now());
-                        }
-                        if ui.button("Stop Playback").clicked() {
-                            app.is_playing = false;
-                            app.playback_index = 0;
-                            app.playback_start = None;
-                        }
-                    });
-
-                    ui.add(Slider::new(&mut app.slider_value, 0.0..=100.0).text("Value"));
-                    ui.text_edit_singleline(&mut app.input_text);
-                });
+                app.ui(&egui_ctx);
 
                 let output = egui_ctx.end_frame();
+                app.answer_accessibility_requests(&egui_ctx, output.platform_output.accesskit_update.as_ref());
+                egui_state.handle_platform_output(&window, &egui_ctx, output.platform_output);
                 let paint_jobs = egui_ctx.tessellate(output.shapes);
 
                 // Get the next frame
-                let frame = match state_3d.surface.get_current_texture() {
+                let surface = state_3d.surface.as_ref().expect("window state has a surface");
+                let frame = match surface.get_current_texture() {
                     Ok(frame) => frame,
                     Err(wgpu::SurfaceError::Lost) => {
                         state_3d.resize(state_3d.size);
@@ -372,51 +1533,14 @@ pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
                     label: Some("Render Encoder"),
                 });
 
-                // Clear pass
-                {
-                    let _rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        label: Some("Clear Pass"),
-                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
-                            resolve_target: None,
-                            ops: wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                                store: true,
-                            },
-                        })],
-                        depth_stencil_attachment: None,
-                    });
-                }
-
-                // Update 3D state
-                state_3d.update_uniforms(app.rotation);
-
-                // 3D pass
-                {
-                    let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        label: Some("3D Pass"),
-                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
-                            resolve_target: None,
-                            ops: wgpu::Operations {
-                                load: wgpu::LoadOp::Load,
-                                store: true,
-                            },
-                        })],
-                        depth_stencil_attachment: None,
-                    });
-
-                    rpass.set_pipeline(&state_3d.render_pipeline);
-                    rpass.set_bind_group(0, &state_3d.uniform_bind_group, &[]);
-                    rpass.set_vertex_buffer(0, state_3d.vertex_buffer.slice(..));
-                    rpass.set_index_buffer(state_3d.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                    rpass.draw_indexed(0..state_3d.num_indices, 0, 0..1);
-                }
+                // Update 3D state and draw the scene
+                state_3d.update_uniforms(app.rotation, app.scene_tint());
+                state_3d.render(&mut encoder, &view);
 
                 // Render eGUI
                 let screen_descriptor = ScreenDescriptor {
                     size_in_pixels: [state_3d.size.width, state_3d.size.height],
-                    pixels_per_point: window.scale_factor() as f32,
+                    pixels_per_point,
                 };
 
                 for (id, image_delta) in &output.textures_delta.set {
@@ -461,7 +1585,64 @@ pub fn run_egui_runtime(mut rx: UnboundedReceiver<String>) {
                 pollster::block_on(js_runtime.run_event_loop(Default::default())).unwrap();
             }
             Event::MainEventsCleared => {
+                let requests = std::mem::take(&mut app.lock().unwrap().clipboard_requests);
+                for request in requests {
+                    clipboard.serve(request);
+                }
+
+                // Apply window changes requested by scripts
+                let commands = std::mem::take(&mut app.lock().unwrap().window_commands);
+                for command in commands {
+                    if command.id() == tree::MAIN_WINDOW {
+                        match command {
+                            windows::WindowCommand::Close { .. } => *control_flow = ControlFlow::Exit,
+                            _ => command.apply(&window),
+                        }
+                        continue;
+                    }
+
+                    let existing = native_windows.iter()
+                        .find(|(_, native)| native.id == command.id())
+                        .map(|(window_id, _)| *window_id);
+                    match (existing, &command) {
+                        (Some(window_id), windows::WindowCommand::Close { .. }) => {
+                            if let Some(native) = native_windows.remove(&window_id) {
+                                app.lock().unwrap().persistence.remember_memory(&native.id, native.egui_ctx());
+                            }
+                        }
+                        (Some(window_id), _) => command.apply(&native_windows[&window_id].window),
+                        (None, windows::WindowCommand::Open(args)) => {
+                            match windows::NativeWindow::open(target, &proxy, &state_3d, args) {
+                                Ok(native) => {
+                                    app.lock().unwrap().persistence.restore_memory(&native.id, native.egui_ctx());
+                                    native_windows.insert(native.window.id(), native);
+                                }
+                                Err(e) => app.lock().unwrap().emit(
+                                    "window_error",
+                                    &args.id,
+                                    serde_json::json!({ "message": e }),
+                                ),
+                            }
+                        }
+                        (None, _) => eprintln!("No window with id '{}'", command.id()),
+                    }
+                }
+
                 window.request_redraw();
+                for native in native_windows.values() {
+                    native.window.request_redraw();
+                }
+            }
+            Event::LoopDestroyed => {
+                let mut app = app.lock().unwrap();
+                app.persistence.remember_memory(tree::MAIN_WINDOW, &egui_ctx);
+                for native in native_windows.values() {
+                    app.persistence.remember_memory(&native.id, native.egui_ctx());
+                }
+                app.capture_persisted();
+                if let Err(e) = app.persistence.save() {
+                    eprintln!("{}", e);
+                }
             }
             _ => {}
         }
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::automation::{TrackedWidget, WidgetKind};
+use crate::{track, EguiApp};
+
+/// An entry of a menu bar, context menu or toolbar. Entries with children open
+/// a submenu; the others report `id` in a `command` event when clicked.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MenuItem {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Keys that trigger the command, e.g. `"Ctrl+S"`. Shown next to the label.
+    #[serde(default)]
+    pub shortcut: Option<String>,
+    #[serde(default = "crate::default_true")]
+    pub enabled: bool,
+    /// Makes the entry a toggle that reports its new state.
+    #[serde(default)]
+    pub checked: Option<bool>,
+    #[serde(default)]
+    pub children: Vec<MenuItem>,
+    /// Draws a separator line instead of an entry.
+    #[serde(default)]
+    pub separator: bool,
+}
+
+impl MenuItem {
+    fn text(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("{} {}", crate::images::icon(icon), self.label),
+            None => self.label.clone(),
+        }
+    }
+}
+
+/// Parses a shortcut such as `"Ctrl+S"`, `"Ctrl+Shift+Z"` or `"F5"`. `Ctrl`
+/// and `Cmd` both mean the platform command key, i.e. Cmd on macOS.
+pub fn parse_shortcut(keys: &str) -> Result<egui::KeyboardShortcut, String> {
+    let mut modifiers = egui::Modifiers::NONE;
+    let mut key = None;
+    for part in keys.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" | "cmd" | "command" => modifiers = modifiers | egui::Modifiers::COMMAND,
+            "shift" => modifiers = modifiers | egui::Modifiers::SHIFT,
+            "alt" | "option" => modifiers = modifiers | egui::Modifiers::ALT,
+            _ if key.is_none() => {
+                key = Some(crate::automation::key_from_name(part).ok_or_else(|| format!("Unknown key '{part}' in '{keys}'"))?)
+            }
+            _ => return Err(format!("Shortcut '{keys}' has more than one key")),
+        }
+    }
+    let key = key.ok_or_else(|| format!("Shortcut '{keys}' has no key"))?;
+    Ok(egui::KeyboardShortcut::new(modifiers, key))
+}
+
+/// Collects the shortcuts of `items` and their submenus, keyed by command.
+pub fn item_shortcuts(items: &[MenuItem]) -> Result<Vec<(String, egui::KeyboardShortcut)>, String> {
+    let mut shortcuts = Vec::new();
+    for item in items {
+        if let Some(keys) = &item.shortcut {
+            if item.id.is_empty() {
+                return Err(format!("Menu item '{}' has shortcut '{}' but no id to report", item.label, keys));
+            }
+            shortcuts.push((item.id.clone(), parse_shortcut(keys)?));
+        }
+        shortcuts.extend(item_shortcuts(&item.children)?);
+    }
+    Ok(shortcuts)
+}
+
+/// The menu bar, context menu or toolbar showing a menu item, which owns
+/// the item's shortcut.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShortcutOwner {
+    /// The menu bar of a window.
+    MenuBar(String),
+    /// The context menu of a widget or scene object.
+    ContextMenu(String),
+    Toolbar(String),
+}
+
+/// A command picked from a menu or toolbar, with the new state of toggles.
+type Picked = Option<(String, Option<bool>)>;
+
+fn items_ui(ui: &mut egui::Ui, items: &mut [MenuItem], widgets: &mut BTreeMap<String, TrackedWidget>, picked: &mut Picked) {
+    for item in items {
+        if item.separator {
+            ui.separator();
+            continue;
+        }
+        let text = item.text();
+        if !item.children.is_empty() {
+            let children = &mut item.children;
+            let response = ui
+                .add_enabled_ui(item.enabled, |ui| {
+                    ui.menu_button(text, |ui| items_ui(ui, children, widgets, picked)).response
+                })
+                .inner;
+            track(widgets, format!("menu/{}", item.id), &response, WidgetKind::Menu);
+            continue;
+        }
+
+        let mut button = egui::Button::new(text);
+        if let Some(shortcut) = &item.shortcut {
+            button = button.shortcut_text(shortcut.as_str());
+        }
+        if let Some(checked) = item.checked {
+            button = button.selected(checked);
+        }
+        let response = ui.add_enabled(item.enabled, button);
+        track(widgets, format!("menu/{}", item.id), &response, WidgetKind::MenuItem);
+        if response.clicked() {
+            if let Some(checked) = &mut item.checked {
+                *checked = !*checked;
+            }
+            *picked = Some((item.id.clone(), item.checked));
+            ui.close_menu();
+        }
+    }
+}
+
+impl EguiApp {
+    /// Reports `command` to scripts. `source` is `"menu"`, `"context_menu"`,
+    /// `"toolbar"` or `"shortcut"`; `target` is the widget or scene object a
+    /// context menu was opened on.
+    pub fn emit_command(&mut self, command: &str, source: &str, target: Option<&str>, checked: Option<bool>) {
+        let mut data = serde_json::json!({ "source": source });
+        if let Some(target) = target {
+            data["target"] = serde_json::json!(target);
+        }
+        if let Some(checked) = checked {
+            data["checked"] = serde_json::json!(checked);
+        }
+        self.emit("command", command, data);
+    }
+
+    /// Replaces the shortcuts of the items shown by `owner`, so they
+    /// trigger their commands only while `owner` shows them.
+    pub fn set_item_shortcuts(&mut self, owner: ShortcutOwner, items: &[MenuItem]) -> Result<(), String> {
+        let shortcuts = item_shortcuts(items)?;
+        if shortcuts.is_empty() {
+            self.item_shortcuts.remove(&owner);
+        } else {
+            self.item_shortcuts.insert(owner, shortcuts);
+        }
+        Ok(())
+    }
+
+    /// Emits a `command` event for every registered or menu item shortcut
+    /// pressed this frame. Runs before any widget so text fields do not see
+    /// the keys.
+    pub(crate) fn shortcuts_ui(&mut self, ctx: &egui::Context) {
+        let pressed: Vec<String> = self
+            .shortcuts
+            .iter()
+            .chain(self.item_shortcuts.values().flatten().map(|(command, shortcut)| (command, shortcut)))
+            .filter(|(_, shortcut)| ctx.input_mut(|i| i.consume_shortcut(shortcut)))
+            .map(|(command, _)| command.clone())
+            .collect();
+        for command in pressed {
+            self.emit_command(&command, "shortcut", None, None);
+        }
+    }
+
+    /// Shows the menu bar of window `root`, if a script set one.
+    pub(crate) fn menu_bar_ui(&mut self, ctx: &egui::Context, root: &str) {
+        let Some(menus) = self.menu_bars.get_mut(root) else { return };
+        let widgets = &mut self.widgets;
+        let mut picked = None;
+        egui::TopBottomPanel::top(egui::Id::new(("menu_bar", root))).show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| items_ui(ui, menus, widgets, &mut picked));
+        });
+        if let Some((command, checked)) = picked {
+            self.emit_command(&command, "menu", None, checked);
+        }
+    }
+
+    /// Attaches the context menus set by scripts to the widgets laid out in
+    /// `ctx` this frame. A scene object's menu opens on its outliner rows.
+    pub(crate) fn context_menus_ui(&mut self, ctx: &egui::Context) {
+        let targets: Vec<String> = self.context_menus.keys().cloned().collect();
+        for target in targets {
+            let mut tracked = vec![target.clone()];
+            if self.scene_objects.contains_key(&target) {
+                tracked.extend(
+                    self.tree_views
+                        .iter()
+                        .filter(|(_, tree)| tree.outliner)
+                        .map(|(tree, _)| format!("{tree}/{target}")),
+                );
+            }
+            for tracked in tracked {
+                self.context_menu_ui(ctx, &tracked, &target);
+            }
+        }
+    }
+
+    /// Opens the context menu of `target` when the widget tracked as `tracked`
+    /// is right-clicked.
+    fn context_menu_ui(&mut self, ctx: &egui::Context, tracked: &str, target: &str) {
+        let Some(response) = self
+            .widgets
+            .get(tracked)
+            .filter(|widget| widget.response.ctx == *ctx)
+            .map(|widget| widget.response.clone())
+        else {
+            return;
+        };
+        self.show_context_menu(&response, target);
+    }
+
+    /// Right-clicking the selected scene object in the 3D viewport opens
+    /// its context menu. The scene draws one cube for the selected object,
+    /// so the menu opens over the cube and not over the empty space around it.
+    pub(crate) fn viewport_context_menu_ui(&mut self, response: &egui::Response) {
+        let Some(target) = self.selected_object.clone() else { return };
+        let screen = response.ctx.screen_rect();
+        let over_object = response
+            .hover_pos()
+            .is_some_and(|pos| crate::state_3d::cube_contains(self.rotation, screen, pos));
+        // Only a hovered response opens a menu; clicks elsewhere still close it.
+        let mut response = response.clone();
+        response.hovered &= over_object;
+        self.show_context_menu(&response, &target);
+    }
+
+    fn show_context_menu(&mut self, response: &egui::Response, target: &str) {
+        let Some(items) = self.context_menus.get_mut(target) else { return };
+        let widgets = &mut self.widgets;
+        let mut picked = None;
+        response.context_menu(|ui| items_ui(ui, items, widgets, &mut picked));
+        if let Some((command, checked)) = picked {
+            self.emit_command(&command, "context_menu", Some(target), checked);
+        }
+    }
+
+    pub(crate) fn toolbar_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(items) = self.toolbars.get_mut(id) else { return };
+        let widgets = &mut self.widgets;
+        let mut picked = None;
+
+        let response = ui
+            .horizontal(|ui| {
+                for item in items.iter_mut() {
+                    if item.separator {
+                        ui.separator();
+                        continue;
+                    }
+                    let text = item.icon.as_deref().map_or_else(|| item.label.clone(), |icon| crate::images::icon(icon).to_string());
+                    let mut response = ui.add_enabled(item.enabled, egui::Button::new(text).selected(item.checked == Some(true)));
+                    let tooltip = match &item.shortcut {
+                        Some(shortcut) => format!("{} ({shortcut})", item.label),
+                        None => item.label.clone(),
+                    };
+                    if !tooltip.is_empty() {
+                        response = response.on_hover_text(tooltip);
+                    }
+                    track(widgets, format!("{id}/{}", item.id), &response, WidgetKind::Button);
+                    if response.clicked() {
+                        if let Some(checked) = &mut item.checked {
+                            *checked = !*checked;
+                        }
+                        picked = Some((item.id.clone(), item.checked));
+                    }
+                }
+            })
+            .response;
+        track(widgets, id, &response, WidgetKind::Container);
+
+        if let Some((command, checked)) = picked {
+            self.emit_command(&command, "toolbar", Some(id), checked);
+        }
+    }
+}
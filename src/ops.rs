@@ -1,5 +1,14 @@
 use deno_core::{op2, OpState, error::AnyError, Op};
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use crate::clipboard::ClipboardAccess;
+use crate::dialogs::{DialogKind, FileBrowser, FileMode};
+use crate::document::Widget;
+use crate::i18n::{Catalog, Script};
+use crate::menus::ShortcutOwner;
+use crate::windows::WindowCommand;
 use crate::EguiApp;
 
 #[op2]
@@ -10,10 +19,37 @@ pub fn op_set_label(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    app.label_text = args.text.clone();
+    app.set_widget(Widget::Label(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_load_image(
+    state: &mut OpState,
+    #[serde] args: crate::LoadImageArgs,
+) -> Result<(), AnyError> {
+    let image = match (args.path, args.bytes) {
+        (Some(path), None) => crate::images::ImageData::from_file(&path).map_err(AnyError::msg)?,
+        (None, Some(bytes)) => crate::images::ImageData::from_bytes(&args.id, bytes),
+        _ => return Err(AnyError::msg(format!("Image '{}' needs either a path or bytes", args.id))),
+    };
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.images.insert(args.id, image);
     Ok(())
 }
 
+#[op2]
+#[serde]
+pub fn op_set_image(
+    state: &mut OpState,
+    #[serde] args: crate::SetImageArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Image(args)).map_err(AnyError::msg)
+}
+
 #[op2]
 #[serde]
 pub fn op_set_slider(
@@ -22,8 +58,7 @@ pub fn op_set_slider(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    app.slider_value = args.value;
-    Ok(())
+    app.set_widget(Widget::Slider(args)).map_err(AnyError::msg)
 }
 
 #[op2]
@@ -34,10 +69,164 @@ pub fn op_set_input(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    app.input_text = args.text.clone();
+    app.set_widget(Widget::Input(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_button(
+    state: &mut OpState,
+    #[serde] args: crate::SetButtonArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Button(args)).map_err(AnyError::msg)
+}
+
+/// Replaces the columns and rows of table `id`, keeping its sort order and filter.
+#[op2]
+#[serde]
+pub fn op_set_table(
+    state: &mut OpState,
+    #[serde] args: crate::SetTableArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Table(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_append_table_rows(
+    state: &mut OpState,
+    #[serde] args: crate::AppendTableRowsArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let table = app.tables.get_mut(&args.id)
+        .ok_or_else(|| AnyError::msg(format!("No table with id '{}'", args.id)))?;
+    table.append(args.rows);
+    Ok(())
+}
+
+/// Creates plot `id` or updates its settings, keeping its series.
+#[op2]
+#[serde]
+pub fn op_set_plot(
+    state: &mut OpState,
+    #[serde] args: crate::SetPlotArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Plot(args)).map_err(AnyError::msg)
+}
+
+/// Creates or replaces series `name` of plot `id`.
+#[op2]
+#[serde]
+pub fn op_set_plot_series(
+    state: &mut OpState,
+    #[serde] args: crate::SetPlotSeriesArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let plot = app.plots.get_mut(&args.id)
+        .ok_or_else(|| AnyError::msg(format!("No plot with id '{}'", args.id)))?;
+    let mut series = crate::plot::Series::new(&args);
+    series.append(&args.points, plot.max_points);
+    plot.series.insert(args.name, series);
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_append_plot_points(
+    state: &mut OpState,
+    #[serde] args: crate::AppendPlotPointsArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let plot = app.plots.get_mut(&args.id)
+        .ok_or_else(|| AnyError::msg(format!("No plot with id '{}'", args.id)))?;
+    let max_points = plot.max_points;
+    let series = plot.series.get_mut(&args.series)
+        .ok_or_else(|| AnyError::msg(format!("Plot '{}' has no series '{}'", args.id, args.series)))?;
+    series.append(&args.points, max_points);
+    Ok(())
+}
+
+/// Replaces the nodes of tree `id`, keeping which nodes are expanded and selected.
+#[op2]
+#[serde]
+pub fn op_set_tree(
+    state: &mut OpState,
+    #[serde] args: crate::SetTreeArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Tree(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_tree_children(
+    state: &mut OpState,
+    #[serde] args: crate::SetTreeChildrenArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let tree = app.tree_views.get_mut(&args.id)
+        .ok_or_else(|| AnyError::msg(format!("No tree with id '{}'", args.id)))?;
+    if !tree.set_children(&args.node, args.children) {
+        return Err(AnyError::msg(format!("Tree '{}' has no node '{}'", args.id, args.node)));
+    }
     Ok(())
 }
 
+#[op2]
+#[serde]
+pub fn op_set_color_edit(
+    state: &mut OpState,
+    #[serde] args: crate::SetColorEditArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::ColorEdit(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_drag_value(
+    state: &mut OpState,
+    #[serde] args: crate::SetDragValueArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::DragValue(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_vector(
+    state: &mut OpState,
+    #[serde] args: crate::SetVectorArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Vector(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_date_time(
+    state: &mut OpState,
+    #[serde] args: crate::SetDateTimeArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::DateTime(args)).map_err(AnyError::msg)
+}
+
 #[op2]
 #[serde]
 pub fn op_set_checkbox(
@@ -46,8 +235,7 @@ pub fn op_set_checkbox(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    app.checkboxes.insert(args.id.clone(), args.checked);
-    Ok(())
+    app.set_widget(Widget::Checkbox(args)).map_err(AnyError::msg)
 }
 
 #[op2]
@@ -58,8 +246,7 @@ pub fn op_set_combo_box(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    app.combo_boxes.insert(args.id.clone(), (args.selected.clone(), args.options.clone()));
-    Ok(())
+    app.set_widget(Widget::ComboBox(args)).map_err(AnyError::msg)
 }
 
 #[op2]
@@ -70,8 +257,7 @@ pub fn op_set_radio(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    app.radio_groups.insert(args.id.clone(), args.selected.clone());
-    Ok(())
+    app.set_widget(Widget::Radio(args)).map_err(AnyError::msg)
 }
 
 #[op2]
@@ -82,8 +268,7 @@ pub fn op_set_progress(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    app.progress_bars.insert(args.id.clone(), args.value);
-    Ok(())
+    app.set_widget(Widget::Progress(args)).map_err(AnyError::msg)
 }
 
 #[op2]
@@ -101,10 +286,48 @@ pub fn op_rotate_3d(
 #[op2]
 #[serde]
 pub fn op_add_3d_object(
-    _state: &mut OpState,
-    #[serde] args: serde_json::Value,
+    state: &mut OpState,
+    #[serde] args: crate::Add3DObjectArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.scene_objects.insert(args.object_id, crate::scene::SceneObject {
+        scene_id: args.scene_id,
+        object_type: args.object_type,
+        size: args.size,
+        color: args.color.unwrap_or(crate::scene::WHITE),
+    });
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_set_object_color(
+    state: &mut OpState,
+    #[serde] args: crate::SetObjectColorArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let object = app.scene_objects.get_mut(&args.object_id)
+        .ok_or_else(|| AnyError::msg(format!("No 3D object with id '{}'", args.object_id)))?;
+    object.color = args.color;
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_select_object(
+    state: &mut OpState,
+    #[serde] args: crate::SelectObjectArgs,
 ) -> Result<(), AnyError> {
-    println!("Add 3D object request: {:?}", args);
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    if let Some(object_id) = &args.object_id {
+        if !app.scene_objects.contains_key(object_id) {
+            return Err(AnyError::msg(format!("No 3D object with id '{}'", object_id)));
+        }
+    }
+    app.selected_object = args.object_id;
     Ok(())
 }
 
@@ -117,9 +340,7 @@ pub fn op_start_recording(
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
     if !app.is_recording {
-        app.is_recording = true;
-        app.recorded_events.clear();
-        app.recording_start = Some(std::time::Instant::now());
+        app.start_recording();
         println!("Recording started.");
     }
     Ok(())
@@ -151,13 +372,7 @@ pub fn op_start_playback(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    if !app.is_playing && !app.recorded_events.is_empty() {
-        app.is_playing = true;
-        app.playback_index = 0;
-        app.playback_start = Some(std::time::Instant::now());
-        println!("Playback started.");
-    }
-    Ok(())
+    app.start_playback().map_err(AnyError::msg)
 }
 
 #[op2]
@@ -168,29 +383,962 @@ pub fn op_stop_playback(
 ) -> Result<(), AnyError> {
     let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
     let mut app = app.lock().unwrap();
-    if app.is_playing {
-        app.is_playing = false;
-        app.playback_index = 0;
-        app.playback_start = None;
-        println!("Playback stopped.");
+    app.stop_playback();
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_automation_perform(
+    state: &mut OpState,
+    #[serde] args: crate::automation::Action,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.automation.push(args);
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_automation_status(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let errors = std::mem::take(&mut app.automation.errors);
+    Ok(serde_json::json!({
+        "idle": app.automation.is_idle(),
+        "errors": errors,
+    }))
+}
+
+#[op2]
+#[serde]
+pub fn op_automation_query(
+    state: &mut OpState,
+    #[serde] args: crate::AutomationQueryArgs,
+) -> Result<Option<serde_json::Value>, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    Ok(app.widgets.get(&args.id).map(|widget| {
+        serde_json::json!({
+            "rect": [widget.rect.min.x, widget.rect.min.y, widget.rect.max.x, widget.rect.max.y],
+            "value": crate::automation::widget_value(&app, &args.id),
+        })
+    }))
+}
+
+#[op2]
+#[serde]
+pub fn op_set_container(
+    state: &mut OpState,
+    #[serde] args: crate::SetContainerArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Container(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_open_window(
+    state: &mut OpState,
+    #[serde] args: crate::OpenWindowArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.window_commands.push(WindowCommand::Open(args));
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_close_window(
+    state: &mut OpState,
+    #[serde] args: crate::WindowIdArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.window_commands.push(WindowCommand::Close { id: args.id });
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_set_window_title(
+    state: &mut OpState,
+    #[serde] args: crate::SetWindowTitleArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.window_commands.push(WindowCommand::SetTitle { id: args.id, title: args.title });
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_set_window_size(
+    state: &mut OpState,
+    #[serde] args: crate::SetWindowSizeArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.window_commands.push(WindowCommand::SetSize { id: args.id, width: args.width, height: args.height });
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_set_window_position(
+    state: &mut OpState,
+    #[serde] args: crate::SetWindowPositionArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.window_commands.push(WindowCommand::SetPosition { id: args.id, x: args.x, y: args.y });
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_set_menu_bar(
+    state: &mut OpState,
+    #[serde] args: crate::SetMenuBarArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let window = args.window.unwrap_or_else(|| crate::tree::MAIN_WINDOW.to_string());
+    // Shortcuts shown in the menus also trigger their commands.
+    app.set_item_shortcuts(ShortcutOwner::MenuBar(window.clone()), &args.menus).map_err(AnyError::msg)?;
+    app.menu_bars.insert(window, args.menus);
+    Ok(())
+}
+
+/// Sets the context menu of a widget or scene object. A scene object's menu
+/// opens on its outliner rows and, while the object is selected, on
+/// right-clicks over it in the 3D viewport.
+#[op2]
+#[serde]
+pub fn op_set_context_menu(
+    state: &mut OpState,
+    #[serde] args: crate::SetContextMenuArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_item_shortcuts(ShortcutOwner::ContextMenu(args.target.clone()), &args.items).map_err(AnyError::msg)?;
+    if args.items.is_empty() {
+        app.context_menus.remove(&args.target);
+    } else {
+        app.context_menus.insert(args.target, args.items);
     }
     Ok(())
 }
 
+#[op2]
+#[serde]
+pub fn op_set_toolbar(
+    state: &mut OpState,
+    #[serde] args: crate::SetToolbarArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Toolbar(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_register_shortcut(
+    state: &mut OpState,
+    #[serde] args: crate::RegisterShortcutArgs,
+) -> Result<(), AnyError> {
+    let shortcut = crate::menus::parse_shortcut(&args.keys).map_err(AnyError::msg)?;
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.shortcuts.insert(args.command, shortcut);
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_unregister_shortcut(
+    state: &mut OpState,
+    #[serde] args: crate::UnregisterShortcutArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.shortcuts.remove(&args.command);
+    Ok(())
+}
+
+/// Opens a dialog or toast with `open` and waits until the user closes it.
+async fn wait_for_answer(
+    state: &Rc<RefCell<OpState>>,
+    open: impl FnOnce(&mut EguiApp) -> oneshot::Receiver<serde_json::Value>,
+) -> Result<serde_json::Value, AnyError> {
+    let answer = {
+        let app = state.borrow().borrow::<Arc<Mutex<EguiApp>>>().clone();
+        let mut app = app.lock().unwrap();
+        open(&mut app)
+    };
+    answer.await.map_err(|_| AnyError::msg("The dialog was closed without an answer"))
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_confirm(
+    state: Rc<RefCell<OpState>>,
+    #[serde] args: crate::ConfirmArgs,
+) -> Result<serde_json::Value, AnyError> {
+    let buttons = if args.buttons.is_empty() {
+        vec!["OK".to_string(), "Cancel".to_string()]
+    } else {
+        args.buttons
+    };
+    wait_for_answer(&state, |app| {
+        app.open_dialog(&args.title, &args.message, DialogKind::Confirm { buttons })
+    })
+    .await
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_prompt(
+    state: Rc<RefCell<OpState>>,
+    #[serde] args: crate::PromptArgs,
+) -> Result<serde_json::Value, AnyError> {
+    wait_for_answer(&state, |app| {
+        app.open_dialog(&args.title, &args.message, DialogKind::Prompt { text: args.text, hint: args.hint })
+    })
+    .await
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_toast(
+    state: Rc<RefCell<OpState>>,
+    #[serde] args: crate::ToastArgs,
+) -> Result<serde_json::Value, AnyError> {
+    wait_for_answer(&state, |app| app.show_toast(&args.message, args.level, args.duration)).await
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_file_dialog(
+    state: Rc<RefCell<OpState>>,
+    #[serde] args: crate::FileDialogArgs,
+) -> Result<serde_json::Value, AnyError> {
+    let title = args.title.unwrap_or_else(|| match args.mode {
+        FileMode::Open => "Open File".to_string(),
+        FileMode::Save => "Save File".to_string(),
+    });
+    let browser = FileBrowser::new(args.mode, args.directory.map(Into::into), args.file_name, args.extensions);
+    wait_for_answer(&state, |app| app.open_dialog(&title, "", DialogKind::File(browser))).await
+}
+
+#[op2]
+#[serde]
+pub fn op_set_visuals(
+    state: &mut OpState,
+    #[serde] args: crate::SetVisualsArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let theme = &mut app.theme;
+    if let Some(base) = args.base {
+        theme.theme.base = base;
+    }
+    theme.theme.colors.merge(args.colors);
+    theme.theme.rounding = args.rounding.or(theme.theme.rounding);
+    theme.changed();
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_load_font(
+    state: &mut OpState,
+    #[serde] args: crate::theme::FontFace,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.theme.add_font(args).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_text_style(
+    state: &mut OpState,
+    #[serde] args: crate::SetTextStyleArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.theme.set_text_style(args.name, args.spec).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_spacing(
+    state: &mut OpState,
+    #[serde] args: crate::theme::SpacingSpec,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.theme.theme.spacing.merge(args);
+    app.theme.changed();
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_set_ui_scale(
+    state: &mut OpState,
+    #[serde] args: crate::SetUiScaleArgs,
+) -> Result<(), AnyError> {
+    use crate::theme::{MAX_UI_SCALE, MIN_UI_SCALE};
+    if !(MIN_UI_SCALE..=MAX_UI_SCALE).contains(&args.scale) {
+        return Err(AnyError::msg(format!(
+            "UI scale {} is outside {}..={}",
+            args.scale, MIN_UI_SCALE, MAX_UI_SCALE
+        )));
+    }
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.theme.theme.ui_scale = args.scale;
+    app.theme.changed();
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_get_theme(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<crate::theme::Theme, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    Ok(app.theme.theme.clone())
+}
+
+#[op2]
+#[serde]
+pub fn op_save_theme(
+    state: &mut OpState,
+    #[serde] args: crate::ThemeFileArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    app.theme.save(std::path::Path::new(&args.path)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_load_theme(
+    state: &mut OpState,
+    #[serde] args: crate::ThemeFileArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.theme.load(std::path::Path::new(&args.path)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_drop_target(
+    state: &mut OpState,
+    #[serde] args: crate::SetDropTargetArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    match args.accept {
+        Some(accept) => {
+            app.drop_targets.insert(args.id, crate::drag_drop::DropTarget { accept });
+        }
+        None => {
+            app.drop_targets.remove(&args.id);
+        }
+    }
+    Ok(())
+}
+
+/// Queues a clipboard access and waits until the event loop served it.
+async fn access_clipboard(
+    state: &Rc<RefCell<OpState>>,
+    access: ClipboardAccess,
+) -> Result<serde_json::Value, AnyError> {
+    let answer = {
+        let app = state.borrow().borrow::<Arc<Mutex<EguiApp>>>().clone();
+        let mut app = app.lock().unwrap();
+        app.request_clipboard(access)
+    };
+    let result = answer.await.map_err(|_| AnyError::msg("The clipboard request was dropped"))?;
+    result.map_err(AnyError::msg)
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_get_clipboard_text(
+    state: Rc<RefCell<OpState>>,
+    #[serde] _args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+    access_clipboard(&state, ClipboardAccess::GetText).await
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_set_clipboard_text(
+    state: Rc<RefCell<OpState>>,
+    #[serde] args: crate::SetClipboardTextArgs,
+) -> Result<serde_json::Value, AnyError> {
+    access_clipboard(&state, ClipboardAccess::SetText(args.text)).await
+}
+
+/// Answers `{ width, height, rgba }`, or null if the clipboard holds no image.
+#[op2(async)]
+#[serde]
+pub async fn op_get_clipboard_image(
+    state: Rc<RefCell<OpState>>,
+    #[serde] _args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+    access_clipboard(&state, ClipboardAccess::GetImage).await
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_set_clipboard_image(
+    state: Rc<RefCell<OpState>>,
+    #[serde] args: crate::clipboard::ClipboardImage,
+) -> Result<serde_json::Value, AnyError> {
+    args.check().map_err(AnyError::msg)?;
+    access_clipboard(&state, ClipboardAccess::SetImage(args)).await
+}
+
+#[op2]
+#[serde]
+pub fn op_set_accessibility(
+    state: &mut OpState,
+    #[serde] args: crate::SetAccessibilityArgs,
+) -> Result<(), AnyError> {
+    if let Some(role) = &args.info.role {
+        crate::accessibility::parse_role(role).map_err(AnyError::msg)?;
+    }
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.accessibility.insert(args.id, args.info);
+    Ok(())
+}
+
+/// Answers the accessibility tree of the main window as of the next frame.
+#[op2(async)]
+#[serde]
+pub async fn op_get_accessibility_tree(
+    state: Rc<RefCell<OpState>>,
+    #[serde] _args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+    let answer = {
+        let app = state.borrow().borrow::<Arc<Mutex<EguiApp>>>().clone();
+        let mut app = app.lock().unwrap();
+        app.request_accessibility_tree()
+    };
+    answer.await.map_err(|_| AnyError::msg("The accessibility tree request was dropped"))
+}
+
+#[op2]
+#[serde]
+pub fn op_load_catalog(
+    state: &mut OpState,
+    #[serde] args: crate::LoadCatalogArgs,
+) -> Result<(), AnyError> {
+    let catalog = Catalog::load(std::path::Path::new(&args.path)).map_err(AnyError::msg)?;
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.i18n.add_catalog(&args.locale, catalog);
+    Ok(())
+}
+
+/// Switches the locale labels and buttons are shown in, adding a fallback
+/// font for its script if needed.
+#[op2]
+#[serde]
+pub fn op_set_locale(
+    state: &mut OpState,
+    #[serde] args: crate::SetLocaleArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let script = Script::of_locale(&args.locale);
+    let name = script.font_name();
+    let path = match args.font {
+        Some(path) => Some(path),
+        None if script != Script::Latin && !app.theme.font_data.contains_key(name) => {
+            let found = script.system_font().map(str::to_string);
+            if found.is_none() {
+                eprintln!("No font found for locale '{}'; pass one to setLocale", args.locale);
+            }
+            found
+        }
+        None => None,
+    };
+    if let Some(path) = path {
+        let face = crate::theme::FontFace { name: name.to_string(), path, family: "proportional".to_string(), first: false };
+        app.theme.add_font(face).map_err(AnyError::msg)?;
+    }
+    app.i18n.set_locale(&args.locale);
+    let direction = if script.is_rtl() { "rtl" } else { "ltr" };
+    app.emit("locale_change", &args.locale, serde_json::json!({ "direction": direction }));
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_get_locale(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    let i18n = &app.i18n;
+    Ok(serde_json::json!({
+        "locale": i18n.locale,
+        "fallback": i18n.fallback,
+        "direction": if i18n.script().is_rtl() { "rtl" } else { "ltr" },
+        "locales": i18n.catalogs.keys().collect::<Vec<_>>(),
+    }))
+}
+
+#[op2]
+#[serde]
+pub fn op_translate(
+    state: &mut OpState,
+    #[serde] args: crate::TranslateArgs,
+) -> Result<String, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    Ok(app.i18n.translate(&args.key, &args.args))
+}
+
+#[op2]
+#[serde]
+pub fn op_set_persist(
+    state: &mut OpState,
+    #[serde] args: crate::SetPersistArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    match args.id {
+        Some(id) => app.persistence.set_persist(&id, args.persist),
+        None => app.persistence.persist_by_default = args.persist,
+    }
+    Ok(())
+}
+
+/// The store as it would be saved now: the state file, the persisted widget
+/// states and the windows with saved egui memory.
+#[op2]
+#[serde]
+pub fn op_get_persisted_state(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.capture_persisted();
+    let persistence = &app.persistence;
+    Ok(serde_json::json!({
+        "path": persistence.path,
+        "widgets": persistence.state.widgets,
+        "windows": persistence.state.memory.keys().collect::<Vec<_>>(),
+    }))
+}
+
+#[op2]
+#[serde]
+pub fn op_clear_persisted_state(
+    state: &mut OpState,
+    #[serde] args: crate::ClearPersistedArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.persistence.clear(args.ids.as_deref());
+    Ok(())
+}
+
+/// The stored value of `key`, or null.
+#[op2]
+#[serde]
+pub fn op_storage_get(
+    state: &mut OpState,
+    #[serde] args: crate::StorageKeyArgs,
+) -> Result<serde_json::Value, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    let value = app.storage.get(&args.namespace, &args.key).map_err(AnyError::msg)?;
+    Ok(value.unwrap_or_default())
+}
+
+#[op2]
+#[serde]
+pub fn op_storage_set(
+    state: &mut OpState,
+    #[serde] args: crate::StorageSetArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.storage.set(&args.namespace, &args.key, args.value).map_err(AnyError::msg)
+}
+
+/// Returns whether `key` was stored.
+#[op2]
+#[serde]
+pub fn op_storage_delete(
+    state: &mut OpState,
+    #[serde] args: crate::StorageKeyArgs,
+) -> Result<bool, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.storage.delete(&args.namespace, &args.key).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_storage_list(
+    state: &mut OpState,
+    #[serde] args: crate::StorageListArgs,
+) -> Result<Vec<String>, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.storage.list(&args.namespace, &args.prefix).map_err(AnyError::msg)
+}
+
+/// Declares an observable unless it exists and returns its current value.
+#[op2]
+#[serde]
+pub fn op_declare_observable(
+    state: &mut OpState,
+    #[serde] args: crate::ObservableArgs,
+) -> Result<serde_json::Value, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    Ok(app.bindings.declare(&args.name, args.value))
+}
+
+#[op2]
+#[serde]
+pub fn op_set_observable(
+    state: &mut OpState,
+    #[serde] args: crate::ObservableArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.bindings.set(&args.name, args.value);
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_get_observable(
+    state: &mut OpState,
+    #[serde] args: crate::ObservableNameArgs,
+) -> Result<serde_json::Value, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    Ok(app.bindings.values.get(&args.name).cloned().unwrap_or_default())
+}
+
+#[op2]
+#[serde]
+pub fn op_bind(
+    state: &mut OpState,
+    #[serde] args: crate::BindArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.bindings.bind(&args.widget, args.name.as_deref()).map_err(AnyError::msg)
+}
+
+/// Loads a UI document into the widget tree and returns the ids of its widgets.
+#[op2]
+#[serde]
+pub fn op_load_ui(
+    state: &mut OpState,
+    #[serde] args: crate::LoadUiArgs,
+) -> Result<Vec<String>, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.load_ui_document(std::path::Path::new(&args.path), args.watch).map_err(AnyError::msg)
+}
+
+/// Creates timeline `id` or updates its settings, keeping its view and selection.
+#[op2]
+#[serde]
+pub fn op_set_timeline(
+    state: &mut OpState,
+    #[serde] args: crate::SetTimelineArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_widget(Widget::Timeline(args)).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_get_recorded_events(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<Vec<crate::events::RecordedEvent>, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    Ok(app.recorded_events.clone())
+}
+
+/// Returns the indices of the edited events after the edit.
+#[op2]
+#[serde]
+pub fn op_edit_recording(
+    state: &mut OpState,
+    #[serde] args: crate::timeline::TimelineEdit,
+) -> Result<Vec<usize>, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.edit_recording(args).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_save_recorded_events(
+    state: &mut OpState,
+    #[serde] args: crate::RecordingFileArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    let recording = crate::events::Recording {
+        snapshots: app.recorded_snapshots.clone(),
+        events: app.recorded_events.clone(),
+    };
+    crate::events::save(std::path::Path::new(&args.filename), &recording).map_err(AnyError::msg)
+}
+
+/// Replaces the recorded events and snapshots with those saved in `filename`.
+#[op2]
+#[serde]
+pub fn op_load_recorded_events(
+    state: &mut OpState,
+    #[serde] args: crate::RecordingFileArgs,
+) -> Result<(), AnyError> {
+    let recording = crate::events::load(std::path::Path::new(&args.filename)).map_err(AnyError::msg)?;
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    if app.is_recording {
+        return Err(AnyError::msg("Cannot load a recording while recording"));
+    }
+    app.stop_playback();
+    app.recorded_events = recording.events;
+    app.recorded_snapshots = recording.snapshots;
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_pause_playback(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.pause_playback(true);
+    Ok(())
+}
+
+#[op2]
+#[serde]
+pub fn op_resume_playback(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.pause_playback(false);
+    Ok(())
+}
+
+/// Moves playback to `timestamp` ms, starting it paused if it is stopped.
+#[op2]
+#[serde]
+pub fn op_seek_playback(
+    state: &mut OpState,
+    #[serde] args: crate::SeekPlaybackArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.seek_playback(args.timestamp).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_step_playback(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.step_playback().map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_playback_rate(
+    state: &mut OpState,
+    #[serde] args: crate::PlaybackRateArgs,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_playback_rate(args.rate).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_set_playback_loop(
+    state: &mut OpState,
+    #[serde] args: crate::PlaybackLoopArgs,
+) -> Result<(), AnyError> {
+    let range = match (args.start, args.end) {
+        (Some(start), Some(end)) => Some((start, end)),
+        (None, None) => None,
+        _ => return Err(AnyError::msg("A loop needs both a start and an end")),
+    };
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.set_playback_loop(range).map_err(AnyError::msg)
+}
+
+#[op2]
+#[serde]
+pub fn op_get_playback_state(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<crate::playback::PlaybackStatus, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    Ok(app.playback_status())
+}
+
+#[op2]
+#[serde]
+pub fn op_get_recording_snapshots(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<Vec<crate::snapshot::Snapshot>, AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let app = app.lock().unwrap();
+    Ok(app.recorded_snapshots.clone())
+}
+
+/// Adds a keyframe with the current state to the recording.
+#[op2]
+#[serde]
+pub fn op_add_snapshot(
+    state: &mut OpState,
+    #[serde] _args: serde_json::Value,
+) -> Result<(), AnyError> {
+    let app = state.borrow::<Arc<Mutex<EguiApp>>>().clone();
+    let mut app = app.lock().unwrap();
+    app.add_snapshot().map_err(AnyError::msg)
+}
+
 pub fn init_ops() -> Vec<deno_core::OpDecl> {
     vec![
         op_set_label::DECL,
         op_set_slider::DECL,
         op_set_input::DECL,
+        op_load_image::DECL,
+        op_set_image::DECL,
+        op_set_button::DECL,
+        op_set_table::DECL,
+        op_append_table_rows::DECL,
+        op_set_plot::DECL,
+        op_set_plot_series::DECL,
+        op_append_plot_points::DECL,
+        op_set_tree::DECL,
+        op_set_tree_children::DECL,
+        op_set_color_edit::DECL,
+        op_set_drag_value::DECL,
+        op_set_vector::DECL,
+        op_set_date_time::DECL,
         op_set_checkbox::DECL,
         op_set_combo_box::DECL,
         op_set_radio::DECL,
         op_set_progress::DECL,
         op_rotate_3d::DECL,
         op_add_3d_object::DECL,
+        op_select_object::DECL,
+        op_set_object_color::DECL,
         op_start_recording::DECL,
         op_stop_recording::DECL,
         op_start_playback::DECL,
         op_stop_playback::DECL,
+        op_automation_perform::DECL,
+        op_automation_status::DECL,
+        op_automation_query::DECL,
+        op_set_container::DECL,
+        op_open_window::DECL,
+        op_close_window::DECL,
+        op_set_window_title::DECL,
+        op_set_window_size::DECL,
+        op_set_window_position::DECL,
+        op_set_menu_bar::DECL,
+        op_set_context_menu::DECL,
+        op_set_toolbar::DECL,
+        op_register_shortcut::DECL,
+        op_unregister_shortcut::DECL,
+        op_confirm::DECL,
+        op_prompt::DECL,
+        op_toast::DECL,
+        op_file_dialog::DECL,
+        op_set_visuals::DECL,
+        op_load_font::DECL,
+        op_set_text_style::DECL,
+        op_set_spacing::DECL,
+        op_set_ui_scale::DECL,
+        op_get_theme::DECL,
+        op_save_theme::DECL,
+        op_load_theme::DECL,
+        op_set_drop_target::DECL,
+        op_get_clipboard_text::DECL,
+        op_set_clipboard_text::DECL,
+        op_get_clipboard_image::DECL,
+        op_set_clipboard_image::DECL,
+        op_set_accessibility::DECL,
+        op_get_accessibility_tree::DECL,
+        op_load_catalog::DECL,
+        op_set_locale::DECL,
+        op_get_locale::DECL,
+        op_translate::DECL,
+        op_set_persist::DECL,
+        op_get_persisted_state::DECL,
+        op_clear_persisted_state::DECL,
+        op_storage_get::DECL,
+        op_storage_set::DECL,
+        op_storage_delete::DECL,
+        op_storage_list::DECL,
+        op_declare_observable::DECL,
+        op_set_observable::DECL,
+        op_get_observable::DECL,
+        op_bind::DECL,
+        op_load_ui::DECL,
+        op_set_timeline::DECL,
+        op_get_recorded_events::DECL,
+        op_edit_recording::DECL,
+        op_save_recorded_events::DECL,
+        op_load_recorded_events::DECL,
+        op_pause_playback::DECL,
+        op_resume_playback::DECL,
+        op_seek_playback::DECL,
+        op_step_playback::DECL,
+        op_set_playback_rate::DECL,
+        op_set_playback_loop::DECL,
+        op_get_playback_state::DECL,
+        op_get_recording_snapshots::DECL,
+        op_add_snapshot::DECL,
     ]
 }
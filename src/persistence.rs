@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::table::TableSort;
+use crate::EguiApp;
+
+/// Writes `bytes` to a temporary file next to `path` and renames it over
+/// `path`, so readers never see a half-written file.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+struct SortValue {
+    column: usize,
+    ascending: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+struct TableValue {
+    selected: BTreeSet<usize>,
+    sort: Option<SortValue>,
+    filter: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+struct TreeValue {
+    selected: BTreeSet<String>,
+    expanded: BTreeSet<String>,
+}
+
+/// The part of widget `id` its user can change, e.g. the value of a slider
+/// or the selection of a table. `None` for widgets without one.
+pub fn widget_state(app: &EguiApp, id: &str) -> Option<Value> {
+    let value = match id {
+        "slider" => serde_json::json!(app.slider_value),
+        "input" => serde_json::json!(app.input_text),
+        _ => {
+            if let Some(slider) = app.sliders.get(id) {
+                serde_json::json!(slider.value)
+            } else if let Some(input) = app.text_inputs.get(id) {
+                serde_json::json!(input.text)
+            } else if let Some(table) = app.tables.get(id) {
+                let sort = table.sort.map(|sort| SortValue { column: sort.column, ascending: sort.ascending });
+                serde_json::json!(TableValue { selected: table.selected.clone(), sort, filter: table.filter.clone() })
+            } else if let Some(tree) = app.tree_views.get(id) {
+                serde_json::json!(TreeValue { selected: tree.selected.clone(), expanded: tree.expanded.clone() })
+            } else if let Some(edit) = app.color_edits.get(id) {
+                serde_json::json!(edit.color)
+            } else if let Some(drag) = app.drag_values.get(id) {
+                serde_json::json!(drag.value)
+            } else if let Some(vector) = app.vectors.get(id) {
+                serde_json::json!(vector.values)
+            } else if let Some(picker) = app.date_times.get(id) {
+                serde_json::json!(picker.value())
+            } else if let Some(items) = app.toolbars.get(id) {
+                let toggles: BTreeMap<_, _> = items.iter().filter_map(|item| Some((item.id.clone(), item.checked?))).collect();
+                serde_json::json!(toggles)
+            } else if let Some(checked) = app.checkboxes.get(id) {
+                serde_json::json!(checked)
+            } else if let Some((selected, _)) = app.combo_boxes.get(id) {
+                serde_json::json!(selected)
+            } else if let Some((selected, _)) = app.radio_groups.get(id) {
+                serde_json::json!(selected)
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(value)
+}
+
+fn parse<T: DeserializeOwned>(id: &str, value: &Value) -> Result<T, String> {
+    serde_json::from_value(value.clone()).map_err(|e| format!("Invalid state for '{}': {}", id, e))
+}
+
+/// Sets the state of widget `id` from a value of [`widget_state`]. Values
+/// are clamped to the range of the widget, and options or rows that no
+/// longer exist are ignored.
+pub fn restore_widget_state(app: &mut EguiApp, id: &str, value: &Value) -> Result<(), String> {
+    match id {
+        "slider" => app.slider_value = parse(id, value)?,
+        "input" => app.input_text = parse(id, value)?,
+        _ => {
+            if let Some(slider) = app.sliders.get_mut(id) {
+                // Sliders accept reversed ranges.
+                let (min, max) = (slider.min.min(slider.max), slider.min.max(slider.max));
+                slider.value = parse::<f64>(id, value)?.clamp(min, max);
+            } else if let Some(input) = app.text_inputs.get_mut(id) {
+                input.text = parse(id, value)?;
+                input.error = input.validate().err();
+            } else if let Some(table) = app.tables.get_mut(id) {
+                let state: TableValue = parse(id, value)?;
+                let rows = table.rows.len();
+                table.selected = state.selected.into_iter().filter(|row| *row < rows).collect();
+                let columns = table.columns.len();
+                let sort = state.sort.filter(|sort| sort.column < columns);
+                table.set_sort(sort.map(|sort| TableSort { column: sort.column, ascending: sort.ascending }));
+                table.set_filter(state.filter);
+            } else if let Some(tree) = app.tree_views.get_mut(id) {
+                let state: TreeValue = parse(id, value)?;
+                tree.selected = state.selected;
+                tree.expanded = state.expanded;
+            } else if let Some(edit) = app.color_edits.get_mut(id) {
+                edit.color = parse(id, value)?;
+                if let Some(object) = edit.object.as_deref().and_then(|object| app.scene_objects.get_mut(object)) {
+                    object.color = edit.color;
+                }
+            } else if let Some(drag) = app.drag_values.get_mut(id) {
+                let value: f64 = parse(id, value)?;
+                drag.value = value.clamp(drag.min.unwrap_or(f64::NEG_INFINITY), drag.max.unwrap_or(f64::INFINITY));
+            } else if let Some(vector) = app.vectors.get_mut(id) {
+                let values: Vec<f64> = parse(id, value)?;
+                if values.len() != vector.values.len() {
+                    return Err(format!("'{}' has {} components, got {}", id, vector.values.len(), values.len()));
+                }
+                vector.values = values;
+            } else if let Some(picker) = app.date_times.get_mut(id) {
+                picker.set_value(&parse::<String>(id, value)?)?;
+            } else if let Some(items) = app.toolbars.get_mut(id) {
+                let toggles: BTreeMap<String, bool> = parse(id, value)?;
+                for item in items.iter_mut().filter(|item| item.checked.is_some()) {
+                    if let Some(checked) = toggles.get(&item.id) {
+                        item.checked = Some(*checked);
+                    }
+                }
+            } else if let Some(checked) = app.checkboxes.get_mut(id) {
+                *checked = parse(id, value)?;
+            } else if let Some((selected, options)) = app.combo_boxes.get_mut(id).or(app.radio_groups.get_mut(id)) {
+                let option: String = parse(id, value)?;
+                if options.contains(&option) {
+                    *selected = option;
+                }
+            } else {
+                return Err(format!("Widget '{}' has no state to restore", id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What is written to the state file.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct PersistedState {
+    /// [`widget_state`] of persisted widgets, keyed by widget id.
+    pub widgets: BTreeMap<String, Value>,
+    /// egui memory of each window as RON, keyed by window id. It holds the
+    /// positions and sizes of egui windows and panels, open collapsing
+    /// headers and scroll offsets.
+    pub memory: BTreeMap<String, String>,
+}
+
+/// UI state kept across restarts in a state file.
+#[derive(Debug, Clone)]
+pub struct Persistence {
+    /// `None` if nothing is persisted.
+    pub path: Option<PathBuf>,
+    /// Whether widgets without an explicit choice are persisted.
+    pub persist_by_default: bool,
+    /// Widgets scripts opted in or out, keyed by widget id.
+    pub overrides: BTreeMap<String, bool>,
+    pub state: PersistedState,
+    /// Saved widgets whose state was not restored yet, since scripts have
+    /// not created them yet.
+    pub pending: BTreeSet<String>,
+    /// Widgets whose state was cleared, so it is not saved again on exit.
+    pub cleared: BTreeSet<String>,
+    /// Unset by clearing the whole store, so the next start is a fresh one.
+    pub save_on_exit: bool,
+}
+
+impl Default for Persistence {
+    fn default() -> Self {
+        Self {
+            path: None,
+            persist_by_default: true,
+            overrides: BTreeMap::new(),
+            state: PersistedState::default(),
+            pending: BTreeSet::new(),
+            cleared: BTreeSet::new(),
+            save_on_exit: true,
+        }
+    }
+}
+
+impl Persistence {
+    /// Reads the state saved at `path`. A missing file is an empty state.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let state = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid state file '{}': {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PersistedState::default(),
+            Err(e) => return Err(format!("Cannot read state file '{}': {}", path.display(), e)),
+        };
+        let pending = state.widgets.keys().cloned().collect();
+        Ok(Self { path: Some(path), state, pending, ..Default::default() })
+    }
+
+    pub fn persists(&self, id: &str) -> bool {
+        !self.cleared.contains(id) && self.overrides.get(id).copied().unwrap_or(self.persist_by_default)
+    }
+
+    /// Opts widget `id` in or out.
+    pub fn set_persist(&mut self, id: &str, persist: bool) {
+        self.overrides.insert(id.to_string(), persist);
+        self.cleared.remove(id);
+    }
+
+    /// Restores the egui memory saved for `window`, keeping the options of
+    /// `ctx` since the theme is reapplied from [`crate::theme::ThemeState`].
+    pub fn restore_memory(&self, window: &str, ctx: &egui::Context) {
+        let Some(ron) = self.state.memory.get(window) else { return };
+        match ron::from_str::<egui::Memory>(ron) {
+            Ok(memory) => ctx.memory_mut(|current| {
+                let options = current.options.clone();
+                *current = memory;
+                current.options = options;
+            }),
+            Err(e) => eprintln!("Cannot restore the UI state of window '{}': {}", window, e),
+        }
+    }
+
+    pub fn remember_memory(&mut self, window: &str, ctx: &egui::Context) {
+        match ctx.memory(ron::to_string) {
+            Ok(ron) => {
+                self.state.memory.insert(window.to_string(), ron);
+            }
+            Err(e) => eprintln!("Cannot save the UI state of window '{}': {}", window, e),
+        }
+    }
+
+    /// Forgets the saved state of `ids`, or of everything, and keeps it
+    /// from being saved again on exit.
+    pub fn clear(&mut self, ids: Option<&[String]>) {
+        match ids {
+            Some(ids) => {
+                for id in ids {
+                    self.state.widgets.remove(id);
+                    self.pending.remove(id);
+                    self.cleared.insert(id.clone());
+                }
+            }
+            None => {
+                self.state = PersistedState::default();
+                self.pending.clear();
+                self.save_on_exit = false;
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if !self.save_on_exit {
+            return match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Cannot remove state file '{}': {}", path.display(), e)),
+                _ => Ok(()),
+            };
+        }
+        let json = serde_json::to_string_pretty(&self.state).map_err(|e| e.to_string())?;
+        write_atomic(path, json.as_bytes()).map_err(|e| format!("Cannot write state file '{}': {}", path.display(), e))
+    }
+}
+
+impl EguiApp {
+    /// Ids of the widgets that have a [`widget_state`].
+    pub fn stateful_widgets(&self) -> Vec<String> {
+        ["slider", "input"]
+            .iter()
+            .map(|id| id.to_string())
+            .chain(
+                self.sliders.keys()
+                    .chain(self.text_inputs.keys())
+                    .chain(self.tables.keys())
+                    .chain(self.tree_views.keys())
+                    .chain(self.color_edits.keys())
+                    .chain(self.drag_values.keys())
+                    .chain(self.vectors.keys())
+                    .chain(self.date_times.keys())
+                    .chain(self.toolbars.keys())
+                    .chain(self.checkboxes.keys())
+                    .chain(self.combo_boxes.keys())
+                    .chain(self.radio_groups.keys())
+                    .cloned(),
+            )
+            .collect()
+    }
+
+    /// Restores saved widget state once scripts have created the widget,
+    /// reporting each as a `state_restore` event.
+    pub(crate) fn restore_persisted(&mut self) {
+        if self.persistence.pending.is_empty() {
+            return;
+        }
+        let ready: Vec<String> = self
+            .persistence
+            .pending
+            .iter()
+            .filter(|id| widget_state(self, id).is_some())
+            .cloned()
+            .collect();
+        for id in ready {
+            self.persistence.pending.remove(&id);
+            if !self.persistence.persists(&id) {
+                continue;
+            }
+            let Some(value) = self.persistence.state.widgets.get(&id).cloned() else { continue };
+            match restore_widget_state(self, &id, &value) {
+                Ok(()) => self.emit("state_restore", &id, serde_json::json!({ "value": value })),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    }
+
+    /// Updates the saved state with the state of the current widgets.
+    /// Widgets opted out are dropped; saved widgets that scripts have not
+    /// created this run are kept.
+    pub fn capture_persisted(&mut self) {
+        let ids: Vec<String> = self.persistence.state.widgets.keys().cloned().collect();
+        for id in ids {
+            if !self.persistence.persists(&id) {
+                self.persistence.state.widgets.remove(&id);
+            }
+        }
+        let states: Vec<(String, Value)> = self
+            .stateful_widgets()
+            .into_iter()
+            .filter(|id| self.persistence.persists(id) && !self.persistence.pending.contains(id))
+            .filter_map(|id| Some((id.clone(), widget_state(self, &id)?)))
+            .collect();
+        self.persistence.state.widgets.extend(states);
+    }
+}
@@ -0,0 +1,290 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::events::RecordedEvent;
+use crate::persistence::restore_widget_state;
+use crate::snapshot::Snapshot;
+use crate::EguiApp;
+
+pub const MIN_RATE: f64 = 0.1;
+pub const MAX_RATE: f64 = 16.0;
+/// How often `playback_progress` is reported while playing.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where playback of `EguiApp::recorded_events` is. `EguiApp::is_playing`
+/// and `EguiApp::playback_index`, the next event to replay, complete it.
+#[derive(Debug, Clone)]
+pub struct Playback {
+    /// Position in the recording, in ms.
+    pub position: f64,
+    pub rate: f64,
+    pub paused: bool,
+    /// Start and end of the A-B loop, in ms.
+    pub loop_range: Option<(u64, u64)>,
+    /// The state from before playback started, restored to seek backwards
+    /// in recordings without snapshots.
+    origin: Snapshot,
+    last_tick: Option<Instant>,
+    last_report: Option<Instant>,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            position: 0.0,
+            rate: 1.0,
+            paused: false,
+            loop_range: None,
+            origin: Snapshot::default(),
+            last_tick: None,
+            last_report: None,
+        }
+    }
+}
+
+/// Playback as reported to scripts.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PlaybackStatus {
+    /// `"stopped"`, `"playing"` or `"paused"`.
+    pub state: &'static str,
+    pub position: u64,
+    pub duration: u64,
+    /// Number of events replayed so far.
+    pub index: usize,
+    pub events: usize,
+    pub rate: f64,
+    #[serde(rename = "loop")]
+    pub loop_range: Option<[u64; 2]>,
+}
+
+/// The widget value reported by an event that changes one.
+fn reported_value(event: &RecordedEvent) -> Option<&Value> {
+    let key = match event.event_type.as_str() {
+        "slider_change" | "drag_value_change" | "vector_change" | "datetime_change" => "value",
+        "color_change" => "color",
+        "input_change" => "text",
+        kind if kind.starts_with("checkbox_") => "checked",
+        kind if kind.starts_with("combo_") || kind.starts_with("radio_") => "selected",
+        _ => return None,
+    };
+    event.event_data.get(key)
+}
+
+impl EguiApp {
+    pub fn playback_status(&self) -> PlaybackStatus {
+        let state = match (self.is_playing, self.playback.paused) {
+            (false, _) => "stopped",
+            (true, false) => "playing",
+            (true, true) => "paused",
+        };
+        PlaybackStatus {
+            state,
+            position: self.playback.position.round() as u64,
+            duration: self.recorded_events.last().map_or(0, |event| event.timestamp),
+            index: self.playback_index,
+            events: self.recorded_events.len(),
+            rate: self.playback.rate,
+            loop_range: self.playback.loop_range.map(|(start, end)| [start, end]),
+        }
+    }
+
+    fn report_playback(&mut self) {
+        self.playback.last_report = Some(Instant::now());
+        let status = serde_json::to_value(self.playback_status()).unwrap_or_default();
+        self.emit("playback_progress", "playback", status);
+    }
+
+    /// Plays the recording from its start, restoring the state it began in.
+    /// Does nothing if there is nothing to play or playback is running.
+    pub fn start_playback(&mut self) -> Result<(), String> {
+        if self.is_recording {
+            return Err("Cannot play back while recording".to_string());
+        }
+        if self.is_playing || self.recorded_events.is_empty() {
+            return Ok(());
+        }
+        self.playback = Playback {
+            rate: self.playback.rate,
+            loop_range: self.playback.loop_range,
+            origin: self.snapshot(0, 0),
+            ..Default::default()
+        };
+        if let Some(initial) = self.recorded_snapshots.first().filter(|snapshot| snapshot.events == 0).cloned() {
+            self.restore_snapshot(&initial);
+        }
+        self.is_playing = true;
+        self.playback_index = 0;
+        self.report_playback();
+        Ok(())
+    }
+
+    /// Stops playback, leaving widgets as the replayed events set them.
+    pub fn stop_playback(&mut self) {
+        if !self.is_playing {
+            return;
+        }
+        self.is_playing = false;
+        self.playback_index = 0;
+        self.playback.position = 0.0;
+        self.playback.paused = false;
+        self.report_playback();
+    }
+
+    pub fn pause_playback(&mut self, paused: bool) {
+        if self.is_playing && self.playback.paused != paused {
+            self.playback.paused = paused;
+            self.playback.last_tick = None;
+            self.report_playback();
+        }
+    }
+
+    pub fn set_playback_rate(&mut self, rate: f64) -> Result<(), String> {
+        if !(MIN_RATE..=MAX_RATE).contains(&rate) {
+            return Err(format!("Playback rate must be between {}x and {}x, got {}x", MIN_RATE, MAX_RATE, rate));
+        }
+        self.playback.rate = rate;
+        if self.is_playing {
+            self.report_playback();
+        }
+        Ok(())
+    }
+
+    /// Loops playback between `start` and `end` ms, or stops looping.
+    pub fn set_playback_loop(&mut self, range: Option<(u64, u64)>) -> Result<(), String> {
+        if let Some((start, end)) = range.filter(|(start, end)| start >= end) {
+            return Err(format!("A loop must end after it starts, got {} to {} ms", start, end));
+        }
+        self.playback.loop_range = range;
+        if let Some((start, end)) = range.filter(|_| self.is_playing) {
+            if !(start as f64..end as f64).contains(&self.playback.position) {
+                self.seek_to(start as f64);
+            }
+        }
+        if self.is_playing {
+            self.report_playback();
+        }
+        Ok(())
+    }
+
+    /// Moves playback to `timestamp` ms, starting it paused if it is stopped.
+    pub fn seek_playback(&mut self, timestamp: u64) -> Result<(), String> {
+        if !self.is_playing {
+            self.start_playback()?;
+            if !self.is_playing {
+                return Ok(());
+            }
+            self.playback.paused = true;
+        }
+        self.seek_to(timestamp as f64);
+        self.report_playback();
+        Ok(())
+    }
+
+    /// Pauses playback and replays the next event.
+    pub fn step_playback(&mut self) -> Result<(), String> {
+        if !self.is_playing {
+            self.start_playback()?;
+        }
+        self.playback.paused = true;
+        let Some(event) = self.recorded_events.get(self.playback_index).cloned() else {
+            self.report_playback();
+            return Ok(());
+        };
+        self.playback.position = event.timestamp as f64;
+        self.playback_index += 1;
+        self.replay(&event, true);
+        self.report_playback();
+        Ok(())
+    }
+
+    /// Sets widgets to their state at `position` without reporting the
+    /// events in between. Seeking backwards, or forwards past a snapshot,
+    /// restores the nearest snapshot and replays the events after it.
+    fn seek_to(&mut self, position: f64) {
+        let position = position.max(0.0);
+        let due = self.recorded_events.partition_point(|event| event.timestamp as f64 <= position);
+        let snapshot = self
+            .recorded_snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.timestamp as f64 <= position && snapshot.events <= due)
+            .cloned();
+        let from = snapshot.as_ref().map_or(0, |snapshot| snapshot.events);
+        if self.playback_index > due || from > self.playback_index {
+            let snapshot = snapshot.unwrap_or_else(|| self.playback.origin.clone());
+            self.restore_snapshot(&snapshot);
+            self.playback_index = from;
+        }
+        self.playback.position = position;
+        self.replay_due(false);
+    }
+
+    /// Replays the events up to the current position.
+    fn replay_due(&mut self, report: bool) {
+        while let Some(event) = self
+            .recorded_events
+            .get(self.playback_index)
+            .filter(|event| event.timestamp as f64 <= self.playback.position)
+            .cloned()
+        {
+            self.playback_index += 1;
+            self.replay(&event, report);
+        }
+    }
+
+    /// Applies the widget change of `event` and, with `report`, sends it to
+    /// scripts as a `playback_event` so they can redo its effects.
+    fn replay(&mut self, event: &RecordedEvent, report: bool) {
+        if let Some(value) = reported_value(event) {
+            if let Err(e) = restore_widget_state(self, &event.component_id, value) {
+                self.emit("playback_error", &event.component_id, serde_json::json!({ "message": e }));
+            }
+        } else if event.event_type == "object_select" && self.scene_objects.contains_key(&event.component_id) {
+            self.selected_object = Some(event.component_id.clone());
+        }
+        if report {
+            self.emit("playback_event", &event.component_id, serde_json::json!({
+                "event_type": event.event_type,
+                "event_data": event.event_data,
+                "timestamp": event.timestamp,
+            }));
+        }
+    }
+
+    /// Moves playback on by `elapsed` wall time, scaled by the rate.
+    pub fn advance_playback(&mut self, elapsed: Duration) {
+        if !self.is_playing || self.playback.paused {
+            return;
+        }
+        self.playback.position += elapsed.as_secs_f64() * 1000.0 * self.playback.rate;
+        if let Some((start, end)) = self.playback.loop_range {
+            if self.playback.position >= end as f64 {
+                // Events up to the end of the loop still play.
+                let overshoot = self.playback.position - end as f64;
+                self.playback.position = end as f64;
+                self.replay_due(true);
+                self.seek_to(start as f64 + overshoot % (end - start) as f64);
+                self.report_playback();
+                return;
+            }
+        }
+        self.replay_due(true);
+
+        if self.playback_index >= self.recorded_events.len() && self.playback.loop_range.is_none() {
+            self.report_playback();
+            self.stop_playback();
+        } else if self.playback.last_report.is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL) {
+            self.report_playback();
+        }
+    }
+
+    /// Advances playback by the time since the last frame.
+    pub(crate) fn playback_ui(&mut self) {
+        let now = Instant::now();
+        let elapsed = self.playback.last_tick.map_or(Duration::ZERO, |last| now.duration_since(last));
+        self.playback.last_tick = Some(now);
+        self.advance_playback(elapsed);
+    }
+}
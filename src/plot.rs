@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints, Points};
+use serde::{Deserialize, Serialize};
+
+use crate::automation::WidgetKind;
+use crate::{track, EguiApp};
+
+const DEFAULT_HISTOGRAM_BINS: usize = 10;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesKind {
+    #[default]
+    Line,
+    Scatter,
+    Bar,
+    /// Bins the y values of the series; x values are ignored.
+    Histogram,
+}
+
+/// A sample sent by a script: either `[x, y]`, or a bare `y` placed at the
+/// next x after the last sample.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum PlotSample {
+    Point([f64; 2]),
+    Value(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub kind: SeriesKind,
+    pub points: VecDeque<[f64; 2]>,
+    pub color: Option<[u8; 3]>,
+    pub bins: usize,
+    next_x: f64,
+}
+
+impl Series {
+    pub fn new(args: &crate::SetPlotSeriesArgs) -> Self {
+        Self {
+            kind: args.kind,
+            points: VecDeque::new(),
+            color: args.color,
+            bins: args.bins.unwrap_or(DEFAULT_HISTOGRAM_BINS).max(1),
+            next_x: 0.0,
+        }
+    }
+
+    /// Appends `samples`, dropping the oldest points beyond `max_points`.
+    pub fn append(&mut self, samples: &[PlotSample], max_points: Option<usize>) {
+        for sample in samples {
+            let point = match *sample {
+                PlotSample::Point(point) => point,
+                PlotSample::Value(y) => [self.next_x, y],
+            };
+            self.next_x = point[0] + 1.0;
+            self.points.push_back(point);
+        }
+        if let Some(max_points) = max_points {
+            let excess = self.points.len().saturating_sub(max_points);
+            self.points.drain(..excess);
+        }
+    }
+
+    fn histogram(&self) -> Vec<Bar> {
+        let (min, max) = self.points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), [_, y]| {
+            (min.min(*y), max.max(*y))
+        });
+        if !min.is_finite() {
+            return Vec::new();
+        }
+        let width = if max > min { (max - min) / self.bins as f64 } else { 1.0 };
+        let mut counts = vec![0u32; self.bins];
+        for [_, y] in &self.points {
+            let bin = (((y - min) / width) as usize).min(self.bins - 1);
+            counts[bin] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(bin, count)| Bar::new(min + width * (bin as f64 + 0.5), count as f64).width(width))
+            .collect()
+    }
+}
+
+/// A script-defined chart holding any number of named series.
+#[derive(Debug, Clone)]
+pub struct PlotState {
+    pub series: BTreeMap<String, Series>,
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub legend: bool,
+    pub height: Option<f32>,
+    /// Ring-buffer size of every series; older points are dropped first.
+    pub max_points: Option<usize>,
+    pub allow_zoom: bool,
+    pub allow_drag: bool,
+}
+
+impl PlotState {
+    pub fn new(args: &crate::SetPlotArgs) -> Self {
+        Self {
+            series: BTreeMap::new(),
+            x_label: args.x_label.clone(),
+            y_label: args.y_label.clone(),
+            legend: args.legend,
+            height: args.height,
+            max_points: args.max_points,
+            allow_zoom: args.allow_zoom,
+            allow_drag: args.allow_drag,
+        }
+    }
+}
+
+impl EguiApp {
+    pub(crate) fn plot_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(plot) = self.plots.get(id) else { return };
+
+        let mut widget = Plot::new(("plot", id))
+            .allow_zoom(plot.allow_zoom)
+            .allow_scroll(plot.allow_zoom)
+            .allow_drag(plot.allow_drag)
+            .allow_boxed_zoom(plot.allow_zoom)
+            .label_formatter(|name, value| {
+                if name.is_empty() {
+                    format!("x: {:.3}\ny: {:.3}", value.x, value.y)
+                } else {
+                    format!("{name}\nx: {:.3}\ny: {:.3}", value.x, value.y)
+                }
+            });
+        if plot.legend {
+            widget = widget.legend(Legend::default());
+        }
+        if let Some(height) = plot.height {
+            widget = widget.height(height);
+        }
+        if let Some(label) = &plot.x_label {
+            widget = widget.x_axis_label(label.as_str());
+        }
+        if let Some(label) = &plot.y_label {
+            widget = widget.y_axis_label(label.as_str());
+        }
+
+        let response = widget.show(ui, |plot_ui| {
+            for (name, series) in &plot.series {
+                let color = series.color.map(|[r, g, b]| egui::Color32::from_rgb(r, g, b));
+                let points = || PlotPoints::from(series.points.iter().copied().collect::<Vec<_>>());
+                match series.kind {
+                    SeriesKind::Line => {
+                        let mut line = Line::new(points()).name(name);
+                        if let Some(color) = color {
+                            line = line.color(color);
+                        }
+                        plot_ui.line(line);
+                    }
+                    SeriesKind::Scatter => {
+                        let mut scatter = Points::new(points()).name(name).radius(2.5);
+                        if let Some(color) = color {
+                            scatter = scatter.color(color);
+                        }
+                        plot_ui.points(scatter);
+                    }
+                    SeriesKind::Bar | SeriesKind::Histogram => {
+                        let bars = if series.kind == SeriesKind::Bar {
+                            series.points.iter().map(|&[x, y]| Bar::new(x, y).width(0.8)).collect()
+                        } else {
+                            series.histogram()
+                        };
+                        let mut chart = BarChart::new(bars).name(name);
+                        if let Some(color) = color {
+                            chart = chart.color(color);
+                        }
+                        plot_ui.bar_chart(chart);
+                    }
+                }
+            }
+            plot_ui.pointer_coordinate()
+        });
+        track(&mut self.widgets, id, &response.response, WidgetKind::Plot);
+
+        if response.response.clicked() {
+            if let Some(point) = response.inner {
+                self.emit("plot_click", id, serde_json::json!({ "x": point.x, "y": point.y }));
+            }
+        }
+    }
+}
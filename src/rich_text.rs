@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::automation::{TrackedWidget, WidgetKind};
+use crate::i18n::{self, I18n, Message};
+use crate::images::{self, ImageData};
+use crate::{track, EguiApp};
+
+/// A run of text with one style, or an inline image or icon.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct TextSpan {
+    pub text: String,
+    /// sRGB text color.
+    pub color: Option<[u8; 3]>,
+    pub background: Option<[u8; 3]>,
+    /// egui's default fonts have no bold face, so bold text is drawn in the
+    /// strong text color.
+    pub bold: bool,
+    pub italic: bool,
+    pub monospace: bool,
+    /// Monospace on a code background.
+    pub code: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub size: Option<f32>,
+    /// Makes the span a link. Clicks are reported as `label_link` events
+    /// carrying this value; nothing is opened.
+    pub link: Option<String>,
+    /// Id of an image loaded with `op_load_image`, drawn instead of the text.
+    pub image: Option<String>,
+    /// Icon name or glyph, drawn before the text.
+    pub icon: Option<String>,
+}
+
+impl TextSpan {
+    fn plain(text: &str) -> Self {
+        Self { text: text.to_string(), ..Default::default() }
+    }
+
+    fn rich_text(&self) -> egui::RichText {
+        let text = i18n::visual_order(&self.text);
+        let text = match &self.icon {
+            Some(icon) if text.is_empty() => images::icon(icon).to_string(),
+            Some(icon) => format!("{} {}", images::icon(icon), text),
+            None => text.into_owned(),
+        };
+        let mut rich = egui::RichText::new(text);
+        if let Some([r, g, b]) = self.color {
+            rich = rich.color(egui::Color32::from_rgb(r, g, b));
+        }
+        if let Some([r, g, b]) = self.background {
+            rich = rich.background_color(egui::Color32::from_rgb(r, g, b));
+        }
+        if self.bold {
+            rich = rich.strong();
+        }
+        if self.italic {
+            rich = rich.italics();
+        }
+        if self.code {
+            rich = rich.code();
+        } else if self.monospace {
+            rich = rich.monospace();
+        }
+        if self.underline {
+            rich = rich.underline();
+        }
+        if self.strikethrough {
+            rich = rich.strikethrough();
+        }
+        if let Some(size) = self.size {
+            rich = rich.size(size);
+        }
+        rich
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineKind {
+    Paragraph,
+    /// Level 1 to 3.
+    Heading(u8),
+    Bullet,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub kind: LineKind,
+    pub spans: Vec<TextSpan>,
+}
+
+/// Parses the inline markup of one line: `**bold**`, `*italic*`, `` `code` ``,
+/// `[text](link)` and `![alt](image id)`.
+fn parse_inline(line: &str) -> Vec<TextSpan> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        let parsed = if let Some(after) = rest.strip_prefix("**") {
+            after.split_once("**").map(|(inner, after)| (TextSpan { bold: true, ..TextSpan::plain(inner) }, after))
+        } else if c == '*' {
+            rest[1..].split_once('*').map(|(inner, after)| (TextSpan { italic: true, ..TextSpan::plain(inner) }, after))
+        } else if c == '`' {
+            rest[1..].split_once('`').map(|(inner, after)| (TextSpan { code: true, ..TextSpan::plain(inner) }, after))
+        } else if let Some(after) = rest.strip_prefix("![") {
+            parse_link(after).map(|(alt, target, after)| (TextSpan { image: Some(target.to_string()), ..TextSpan::plain(alt) }, after))
+        } else if let Some(after) = rest.strip_prefix('[') {
+            parse_link(after).map(|(text, target, after)| (TextSpan { link: Some(target.to_string()), ..TextSpan::plain(text) }, after))
+        } else {
+            None
+        };
+
+        match parsed {
+            Some((span, after)) if !span.text.is_empty() || span.image.is_some() => {
+                if !plain.is_empty() {
+                    spans.push(TextSpan::plain(&std::mem::take(&mut plain)));
+                }
+                spans.push(span);
+                rest = after;
+            }
+            _ => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(TextSpan::plain(&plain));
+    }
+    spans
+}
+
+/// Splits `text](target)rest` into its parts.
+fn parse_link(after_bracket: &str) -> Option<(&str, &str, &str)> {
+    let (text, after) = after_bracket.split_once("](")?;
+    let (target, rest) = after.split_once(')')?;
+    Some((text, target, rest))
+}
+
+/// Parses a markdown-ish text: `#` to `###` headings, `-` or `*` bullets and
+/// inline markup. Blank lines are dropped.
+pub fn parse_markdown(text: &str) -> Vec<TextLine> {
+    text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+            if (1..=3).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+                let spans = parse_inline(trimmed[hashes..].trim_start());
+                TextLine { kind: LineKind::Heading(hashes as u8), spans }
+            } else if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+                TextLine { kind: LineKind::Bullet, spans: parse_inline(item) }
+            } else {
+                TextLine { kind: LineKind::Paragraph, spans: parse_inline(trimmed) }
+            }
+        })
+        .collect()
+}
+
+/// A label of rich text lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelState {
+    pub lines: Vec<TextLine>,
+    /// Message the text is translated from, if any.
+    pub message: Option<Message>,
+    pub markdown: bool,
+    /// [`I18n::generation`] the lines were translated for.
+    pub translated: Option<u64>,
+}
+
+fn text_lines(text: &str, markdown: bool) -> Vec<TextLine> {
+    if markdown {
+        parse_markdown(text)
+    } else {
+        vec![TextLine { kind: LineKind::Paragraph, spans: vec![TextSpan::plain(text)] }]
+    }
+}
+
+impl LabelState {
+    /// Uses `spans` if given, then the markdown or plain `text`. Labels with
+    /// a `message` get their text from [`Self::translate`].
+    pub fn new(args: &crate::SetLabelArgs) -> Self {
+        let lines = if !args.spans.is_empty() {
+            vec![TextLine { kind: LineKind::Paragraph, spans: args.spans.clone() }]
+        } else {
+            text_lines(&args.text, args.markdown)
+        };
+        Self { lines, message: args.message.clone(), markdown: args.markdown, translated: None }
+    }
+
+    /// Replaces the text, keeping whether it is parsed as markdown.
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text_lines(text, self.markdown);
+        self.message = None;
+    }
+
+    /// Retranslates the message of the label if the locale or the catalogs
+    /// changed since it was last translated.
+    pub fn translate(&mut self, i18n: &I18n) {
+        let Some(message) = &self.message else { return };
+        if self.translated == Some(i18n.generation) {
+            return;
+        }
+        self.lines = text_lines(&i18n.message(message), self.markdown);
+        self.translated = Some(i18n.generation);
+    }
+
+    /// The text without styling, as reported to automation.
+    pub fn plain_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.text.as_str()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Draws `span` and returns its link if it was clicked.
+fn span_ui(
+    ui: &mut egui::Ui,
+    span: &TextSpan,
+    images: &BTreeMap<String, ImageData>,
+    widgets: &mut BTreeMap<String, TrackedWidget>,
+    link_id: impl FnOnce() -> String,
+) -> Option<String> {
+    if let Some(image) = &span.image {
+        match images.get(image) {
+            Some(data) => {
+                let height = span.size.unwrap_or_else(|| ui.text_style_height(&egui::TextStyle::Body));
+                ui.add(egui::Image::new(data.source()).max_height(height));
+            }
+            None => {
+                ui.label(span.text.as_str());
+            }
+        }
+        return None;
+    }
+    match &span.link {
+        Some(link) => {
+            let response = ui.link(span.rich_text());
+            track(widgets, link_id(), &response, WidgetKind::Button);
+            response.clicked().then(|| link.clone())
+        }
+        None => {
+            ui.label(span.rich_text());
+            None
+        }
+    }
+}
+
+impl EguiApp {
+    pub(crate) fn label_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(label) = self.labels.get_mut(id) else { return };
+        label.translate(&self.i18n);
+        let label = &*label;
+        let images = &self.images;
+        let widgets = &mut self.widgets;
+        let mut clicked = None;
+        let mut links = 0;
+
+        let response = ui
+            .vertical(|ui| {
+                for line in &label.lines {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing.x = 0.0;
+                        if line.kind == LineKind::Bullet {
+                            ui.label("  • ");
+                        }
+                        for span in &line.spans {
+                            let span = match line.kind {
+                                LineKind::Heading(level) if span.size.is_none() => {
+                                    let heading = ui.text_style_height(&egui::TextStyle::Heading);
+                                    let size = heading * match level {
+                                        1 => 1.0,
+                                        2 => 0.85,
+                                        _ => 0.7,
+                                    };
+                                    TextSpan { size: Some(size), bold: true, ..span.clone() }
+                                }
+                                _ => span.clone(),
+                            };
+                            let link = span_ui(ui, &span, images, widgets, || {
+                                links += 1;
+                                format!("{id}/link/{}", links - 1)
+                            });
+                            clicked = clicked.take().or(link);
+                        }
+                    });
+                }
+            })
+            .response;
+        track(widgets, id, &response, WidgetKind::Label);
+
+        if let Some(link) = clicked {
+            self.emit("label_link", id, serde_json::json!({ "link": link }));
+        }
+    }
+}
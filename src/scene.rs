@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::EguiApp;
+
+pub const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+pub(crate) fn white() -> [f32; 4] {
+    WHITE
+}
+
+/// An object registered in a 3D scene by a script.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SceneObject {
+    pub scene_id: String,
+    pub object_type: String,
+    pub size: f32,
+    /// Linear RGBA material color.
+    #[serde(default = "white")]
+    pub color: [f32; 4],
+}
+
+impl SceneObject {
+    pub fn icon(&self) -> &'static str {
+        match self.object_type.as_str() {
+            "cube" => "⬛",
+            "sphere" => "⚫",
+            _ => "◆",
+        }
+    }
+}
+
+impl EguiApp {
+    /// The color scene object `id` is drawn with.
+    pub fn material_color(&self, id: &str) -> Option<[f32; 4]> {
+        self.scene_objects.get(id).map(|object| object.color)
+    }
+
+    /// The scene draws a single cube standing in for the scene objects. It
+    /// takes the material color of the selected object, or of the first object.
+    pub fn scene_tint(&self) -> [f32; 4] {
+        self.selected_object
+            .as_deref()
+            .and_then(|id| self.material_color(id))
+            .or_else(|| self.scene_objects.values().next().map(|object| object.color))
+            .unwrap_or(WHITE)
+    }
+}
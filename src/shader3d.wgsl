@@ -1,5 +1,6 @@
 struct Uniforms {
     view_proj: mat4x4<f32>,
+    tint: vec4<f32>,
 };
 
 @group(0) @binding(0)
@@ -25,5 +26,5 @@ fn vs_main_3d(input: VertexInput) -> VertexOutput {
 
 @fragment
 fn fs_main_3d(input: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(input.color, 1.0);
+    return vec4<f32>(input.color, 1.0) * uniforms.tint;
 }
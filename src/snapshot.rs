@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::persistence::{restore_widget_state, widget_state};
+use crate::scene::SceneObject;
+use crate::EguiApp;
+
+/// How often a keyframe is taken while recording, if events were recorded
+/// since the last one.
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Widget values and 3D scene state at a point in a recording. Playback
+/// restores one and replays only the events after it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub timestamp: u64,
+    /// Number of events recorded before the snapshot was taken.
+    pub events: usize,
+    pub widgets: BTreeMap<String, Value>,
+    pub rotation: f32,
+    pub scene_objects: BTreeMap<String, SceneObject>,
+    pub selected_object: Option<String>,
+}
+
+impl EguiApp {
+    /// The current state, taken `timestamp` ms into a recording of `events` events.
+    pub fn snapshot(&self, timestamp: u64, events: usize) -> Snapshot {
+        Snapshot {
+            timestamp,
+            events,
+            widgets: self.stateful_widgets().into_iter().filter_map(|id| Some((id.clone(), widget_state(self, &id)?))).collect(),
+            rotation: self.rotation,
+            scene_objects: self.scene_objects.clone(),
+            selected_object: self.selected_object.clone(),
+        }
+    }
+
+    /// Sets widgets and the 3D scene to `snapshot`, reporting each widget
+    /// that changes as a `state_restore` event. Widgets scripts have not
+    /// created are skipped.
+    pub fn restore_snapshot(&mut self, snapshot: &Snapshot) {
+        for (id, value) in &snapshot.widgets {
+            if widget_state(self, id).is_none_or(|current| current == *value) {
+                continue;
+            }
+            match restore_widget_state(self, id, value) {
+                Ok(()) => self.emit("state_restore", id, serde_json::json!({ "value": value })),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        self.rotation = snapshot.rotation;
+        self.scene_objects = snapshot.scene_objects.clone();
+        self.selected_object = snapshot.selected_object.clone();
+    }
+
+    /// Starts a new recording from a snapshot of the current state.
+    pub fn start_recording(&mut self) {
+        self.stop_playback();
+        self.is_recording = true;
+        self.recorded_events.clear();
+        self.recorded_snapshots = vec![self.snapshot(0, 0)];
+        self.recording_start = Some(Instant::now());
+    }
+
+    /// Adds a keyframe with the current state to the recording.
+    pub fn add_snapshot(&mut self) -> Result<(), String> {
+        let Some(start) = self.recording_start.filter(|_| self.is_recording) else {
+            return Err("Snapshots can only be taken while recording".to_string());
+        };
+        let snapshot = self.snapshot(start.elapsed().as_millis() as u64, self.recorded_events.len());
+        self.recorded_snapshots.push(snapshot);
+        Ok(())
+    }
+
+    /// Adds a keyframe to the recording every [`SNAPSHOT_INTERVAL`].
+    pub(crate) fn snapshot_recording(&mut self) {
+        let Some(start) = self.recording_start.filter(|_| self.is_recording) else { return };
+        let timestamp = start.elapsed().as_millis() as u64;
+        let events = self.recorded_events.len();
+        let due = self.recorded_snapshots.last().is_none_or(|last| {
+            last.events < events && timestamp >= last.timestamp + SNAPSHOT_INTERVAL.as_millis() as u64
+        });
+        if due {
+            // Cannot fail while recording.
+            let _ = self.add_snapshot();
+        }
+    }
+}
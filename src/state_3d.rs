@@ -1,6 +1,6 @@
 use winit::window::Window;
 use wgpu::util::DeviceExt;
-use cgmath::{Matrix4, Point3, Vector3, Deg, perspective, SquareMatrix};
+use cgmath::{Matrix4, Point3, Vector3, Vector4, Deg, perspective, SquareMatrix};
 
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -26,28 +26,58 @@ impl Vertex {
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Uniforms {
     pub view_proj: [[f32; 4]; 4],
+    /// Multiplies the vertex colors.
+    pub tint: [f32; 4],
 }
 
 impl Uniforms {
     fn new() -> Self {
         Self {
             view_proj: Matrix4::identity().into(),
+            tint: [1.0; 4],
         }
     }
 
     fn update_view_proj(&mut self, rotation: f32) {
-        let view = Matrix4::look_at_rh(
-            Point3::new(0.0, 0.0, 5.0),
-            Point3::new(0.0, 0.0, 0.0),
-            Vector3::unit_y(),
-        );
-        let proj = perspective(Deg(45.0), 1.0, 0.1, 100.0);
-        let rot = Matrix4::from_angle_y(Deg(rotation));
-        let vp = proj * view * rot;
-        self.view_proj = vp.into();
+        self.view_proj = view_proj(rotation).into();
     }
 }
 
+fn view_proj(rotation: f32) -> Matrix4<f32> {
+    let view = Matrix4::look_at_rh(
+        Point3::new(0.0, 0.0, 5.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vector3::unit_y(),
+    );
+    let proj = perspective(Deg(45.0), 1.0, 0.1, 100.0);
+    let rot = Matrix4::from_angle_y(Deg(rotation));
+    proj * view * rot
+}
+
+/// Whether `pos` is over the cube drawn with `rotation`, on a window whose
+/// whole area `screen` the scene fills.
+pub fn cube_contains(rotation: f32, screen: egui::Rect, pos: egui::Pos2) -> bool {
+    let view_proj = view_proj(rotation);
+    let corners: Vec<egui::Pos2> = VERTICES
+        .iter()
+        .map(|vertex| {
+            let [x, y, z] = vertex.position;
+            let clip = view_proj * Vector4::new(x, y, z, 1.0);
+            let (ndc_x, ndc_y) = (clip.x / clip.w, clip.y / clip.w);
+            egui::pos2(
+                screen.left() + (ndc_x + 1.0) / 2.0 * screen.width(),
+                screen.top() + (1.0 - ndc_y) / 2.0 * screen.height(),
+            )
+        })
+        .collect();
+    INDICES.chunks(3).any(|triangle| {
+        let [a, b, c] = [0, 1, 2].map(|i| corners[triangle[i] as usize]);
+        let side = |from: egui::Pos2, to: egui::Pos2| (to - from).x * (pos - from).y - (to - from).y * (pos - from).x;
+        let sides = [side(a, b), side(b, c), side(c, a)];
+        sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0)
+    })
+}
+
 const VERTICES: &[Vertex] = &[
     // front face
     Vertex { position: [-1.0, -1.0,  1.0], color: [1.0, 0.0, 0.0] },
@@ -71,9 +101,11 @@ const INDICES: &[u16] = &[
 ];
 
 pub struct State3D {
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
-    pub surface: wgpu::Surface,
+    pub surface: Option<wgpu::Surface>,
     pub config: wgpu::SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
     pub render_pipeline: wgpu::RenderPipeline,
@@ -102,17 +134,7 @@ impl State3D {
             .await
             .expect("Failed to find an adapter");
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("Device"),
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
-                },
-                None,
-            )
-            .await
-            .expect("Failed to create device");
+        let (device, queue) = request_device(&adapter).await;
 
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps.formats.iter()
@@ -131,6 +153,49 @@ impl State3D {
         };
         surface.configure(&device, &config);
 
+        Self::with_device(instance, adapter, device, queue, Some(surface), config)
+    }
+
+    /// Creates a state without a window surface, for rendering into offscreen
+    /// textures (tests, screenshots). Returns `None` when no adapter is available.
+    pub async fn new_headless(width: u32, height: u32) -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            dx12_shader_compiler: Default::default(),
+        });
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await?;
+
+        let (device, queue) = request_device(&adapter).await;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        };
+
+        Some(Self::with_device(instance, adapter, device, queue, None, config))
+    }
+
+    fn with_device(
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        surface: Option<wgpu::Surface>,
+        config: wgpu::SurfaceConfiguration,
+    ) -> Self {
+        let size = winit::dpi::PhysicalSize::new(config.width, config.height);
+
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("3D Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("shader3d.wgsl").into()),
@@ -141,7 +206,7 @@ impl State3D {
                 label: Some("3D Uniform BGL"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -221,6 +286,8 @@ impl State3D {
         });
 
         Self {
+            instance,
+            adapter,
             device,
             queue,
             surface,
@@ -241,12 +308,15 @@ impl State3D {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            if let Some(surface) = &self.surface {
+                surface.configure(&self.device, &self.config);
+            }
         }
     }
 
-    pub fn update_uniforms(&mut self, rotation: f32) {
+    pub fn update_uniforms(&mut self, rotation: f32, tint: [f32; 4]) {
         self.uniforms.update_view_proj(rotation);
+        self.uniforms.tint = tint;
         self.queue.write_buffer(
             &self.uniform_buffer,
             0,
@@ -254,7 +324,62 @@ impl State3D {
         );
     }
 
+    /// Clears `view` and draws the scene into it.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        // Clear pass
+        {
+            let _rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Clear Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        }
+
+        // 3D pass
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("3D Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            rpass.set_pipeline(&self.render_pipeline);
+            rpass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            rpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            rpass.draw_indexed(0..self.num_indices, 0, 0..1);
+        }
+    }
+
     pub fn input(&mut self, _event: &winit::event::WindowEvent) -> bool {
         false
     }
 }
+
+async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Device"),
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::default(),
+            },
+            None,
+        )
+        .await
+        .expect("Failed to create device")
+}
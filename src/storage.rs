@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::persistence::write_atomic;
+
+/// Checks that `namespace` can name a file in the data directory.
+pub fn check_namespace(namespace: &str) -> Result<(), String> {
+    let valid = !namespace.is_empty()
+        && !namespace.starts_with('.')
+        && namespace.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        return Err(format!(
+            "Invalid storage namespace '{}': use letters, digits, '_', '-' and '.', not starting with '.'",
+            namespace
+        ));
+    }
+    Ok(())
+}
+
+/// JSON values stored by scripts, one file per namespace in `dir`. Every
+/// change rewrites the file of its namespace atomically.
+#[derive(Debug, Clone)]
+pub struct Storage {
+    pub dir: PathBuf,
+    /// Namespaces read so far, keyed by namespace.
+    loaded: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+impl Storage {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, loaded: BTreeMap::new() }
+    }
+
+    fn path(&self, namespace: &str) -> PathBuf {
+        self.dir.join(format!("{namespace}.json"))
+    }
+
+    /// The entries of `namespace`, read from disk on first use.
+    fn entries(&mut self, namespace: &str) -> Result<&mut BTreeMap<String, Value>, String> {
+        check_namespace(namespace)?;
+        if !self.loaded.contains_key(namespace) {
+            let path = self.path(namespace);
+            let entries = match std::fs::read_to_string(&path) {
+                Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid storage file '{}': {}", path.display(), e))?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => return Err(format!("Cannot read storage file '{}': {}", path.display(), e)),
+            };
+            self.loaded.insert(namespace.to_string(), entries);
+        }
+        Ok(self.loaded.get_mut(namespace).expect("namespace was just loaded"))
+    }
+
+    /// Writes `entries` as the file of `namespace`, then keeps them as its
+    /// entries. A failed write leaves the entries as they were.
+    fn write(&mut self, namespace: &str, entries: BTreeMap<String, Value>) -> Result<(), String> {
+        let path = self.path(namespace);
+        let json = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+        write_atomic(&path, json.as_bytes()).map_err(|e| format!("Cannot write storage file '{}': {}", path.display(), e))?;
+        self.loaded.insert(namespace.to_string(), entries);
+        Ok(())
+    }
+
+    pub fn get(&mut self, namespace: &str, key: &str) -> Result<Option<Value>, String> {
+        Ok(self.entries(namespace)?.get(key).cloned())
+    }
+
+    pub fn set(&mut self, namespace: &str, key: &str, value: Value) -> Result<(), String> {
+        let entries = self.entries(namespace)?;
+        if entries.get(key) == Some(&value) {
+            return Ok(());
+        }
+        let mut entries = entries.clone();
+        entries.insert(key.to_string(), value);
+        self.write(namespace, entries)
+    }
+
+    /// Returns whether `key` was stored.
+    pub fn delete(&mut self, namespace: &str, key: &str) -> Result<bool, String> {
+        let entries = self.entries(namespace)?;
+        if !entries.contains_key(key) {
+            return Ok(false);
+        }
+        let mut entries = entries.clone();
+        entries.remove(key);
+        self.write(namespace, entries)?;
+        Ok(true)
+    }
+
+    /// Keys of `namespace` starting with `prefix`, in order.
+    pub fn list(&mut self, namespace: &str, prefix: &str) -> Result<Vec<String>, String> {
+        Ok(self.entries(namespace)?.keys().filter(|key| key.starts_with(prefix)).cloned().collect())
+    }
+}
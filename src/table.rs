@@ -0,0 +1,266 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use egui_extras::{Column, TableBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::automation::WidgetKind;
+use crate::{default_true, track, EguiApp};
+
+const HEADER_HEIGHT: f32 = 20.0;
+const DEFAULT_ROW_HEIGHT: f32 = 18.0;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TableColumn {
+    pub name: String,
+    /// Initial width; the column sizes to its content when unset.
+    #[serde(default)]
+    pub width: Option<f32>,
+    #[serde(default = "default_true")]
+    pub sortable: bool,
+    #[serde(default = "default_true")]
+    pub resizable: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionMode {
+    None,
+    #[default]
+    Single,
+    /// Ctrl/Cmd-click adds or removes rows from the selection.
+    Multi,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableSort {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+/// A script-defined data table. Rows are addressed by their index in `rows`,
+/// regardless of how the table is sorted or filtered.
+#[derive(Debug, Clone)]
+pub struct TableState {
+    pub columns: Vec<TableColumn>,
+    pub rows: Vec<Vec<Value>>,
+    pub selection: SelectionMode,
+    pub selected: BTreeSet<usize>,
+    pub sort: Option<TableSort>,
+    pub filter: String,
+    pub filterable: bool,
+    pub striped: bool,
+    pub row_height: f32,
+    /// Rows can be dragged onto drop targets.
+    pub draggable: bool,
+    /// Indices of the rows passing the filter, in display order.
+    view: Vec<usize>,
+    view_dirty: bool,
+}
+
+fn cell_text(value: &Value) -> Cow<'_, str> {
+    match value {
+        Value::String(text) => Cow::Borrowed(text),
+        Value::Null => Cow::Borrowed(""),
+        other => Cow::Owned(other.to_string()),
+    }
+}
+
+/// Numbers compare numerically, everything else by its text.
+fn compare_cells(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a.and_then(Value::as_f64), b.and_then(Value::as_f64)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        _ => {
+            let text = |value: Option<&Value>| value.map(cell_text).unwrap_or_default();
+            text(a).cmp(&text(b))
+        }
+    }
+}
+
+impl TableState {
+    pub fn new(args: &crate::SetTableArgs) -> Self {
+        Self {
+            columns: args.columns.clone(),
+            rows: args.rows.clone(),
+            selection: args.selection,
+            selected: BTreeSet::new(),
+            sort: None,
+            filter: String::new(),
+            filterable: args.filterable,
+            striped: args.striped,
+            row_height: args.row_height.unwrap_or(DEFAULT_ROW_HEIGHT),
+            draggable: args.draggable,
+            view: Vec::new(),
+            view_dirty: true,
+        }
+    }
+
+    pub fn append(&mut self, rows: Vec<Vec<Value>>) {
+        self.rows.extend(rows);
+        self.view_dirty = true;
+    }
+
+    /// Sorts by `column`, flipping the direction if it is already the sort column.
+    pub fn sort_by(&mut self, column: usize) {
+        self.sort = match self.sort {
+            Some(sort) if sort.column == column => Some(TableSort { column, ascending: !sort.ascending }),
+            _ => Some(TableSort { column, ascending: true }),
+        };
+        self.view_dirty = true;
+    }
+
+    pub fn set_sort(&mut self, sort: Option<TableSort>) {
+        self.sort = sort;
+        self.view_dirty = true;
+    }
+
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.view_dirty = true;
+    }
+
+    /// Indices of the visible rows in display order.
+    pub fn view(&mut self) -> &[usize] {
+        if self.view_dirty {
+            let filter = self.filter.trim().to_lowercase();
+            self.view = (0..self.rows.len())
+                .filter(|&index| {
+                    filter.is_empty()
+                        || self.rows[index]
+                            .iter()
+                            .any(|cell| cell_text(cell).to_lowercase().contains(&filter))
+                })
+                .collect();
+            if let Some(TableSort { column, ascending }) = self.sort {
+                let rows = &self.rows;
+                self.view.sort_by(|&a, &b| {
+                    let ordering = compare_cells(rows[a].get(column), rows[b].get(column));
+                    if ascending { ordering } else { ordering.reverse() }
+                });
+            }
+            self.view_dirty = false;
+        }
+        &self.view
+    }
+
+    /// Applies a click on row `index`. Returns whether the selection changed.
+    fn click_row(&mut self, index: usize, modifiers: egui::Modifiers) -> bool {
+        let before = self.selected.clone();
+        match self.selection {
+            SelectionMode::None => {}
+            SelectionMode::Multi if modifiers.command => {
+                if !self.selected.remove(&index) {
+                    self.selected.insert(index);
+                }
+            }
+            SelectionMode::Single | SelectionMode::Multi => {
+                self.selected = BTreeSet::from([index]);
+            }
+        }
+        self.selected != before
+    }
+}
+
+impl EguiApp {
+    pub(crate) fn table_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(table) = self.tables.get_mut(id) else { return };
+        let widgets = &mut self.widgets;
+
+        let mut clicked_header = None;
+        let mut clicked_row = None;
+        let mut dragged_row = None;
+        let response = ui.push_id(("table", id), |ui| {
+            if table.filterable {
+                let mut filter = table.filter.clone();
+                let response = ui.add(egui::TextEdit::singleline(&mut filter).hint_text("Filter"));
+                track(widgets, format!("{id}/filter"), &response, WidgetKind::TextInput);
+                if response.changed() {
+                    table.set_filter(filter);
+                }
+            }
+            table.view();
+
+            let mut builder = TableBuilder::new(ui)
+                .striped(table.striped)
+                .resizable(true)
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+            for column in &table.columns {
+                let size = column.width.map_or_else(Column::auto, Column::initial);
+                builder = builder.column(size.resizable(column.resizable).clip(true));
+            }
+
+            builder
+                .header(HEADER_HEIGHT, |mut header| {
+                    for (index, column) in table.columns.iter().enumerate() {
+                        header.col(|ui| {
+                            if !column.sortable {
+                                ui.strong(column.name.as_str());
+                                return;
+                            }
+                            let arrow = match table.sort {
+                                Some(TableSort { column: sorted, ascending: true }) if sorted == index => " ⬆",
+                                Some(TableSort { column: sorted, ascending: false }) if sorted == index => " ⬇",
+                                _ => "",
+                            };
+                            let text = egui::RichText::new(format!("{}{}", column.name, arrow)).strong();
+                            let response = ui.add(egui::Button::new(text).frame(false));
+                            track(widgets, format!("{id}/column/{}", column.name), &response, WidgetKind::TableHeader);
+                            if response.clicked() {
+                                clicked_header = Some(index);
+                            }
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(table.row_height, table.view.len(), |index, mut row| {
+                        let row_index = table.view[index];
+                        let selected = table.selected.contains(&row_index);
+                        for column in 0..table.columns.len() {
+                            row.col(|ui| {
+                                let text = table.rows[row_index].get(column).map(cell_text).unwrap_or_default();
+                                let mut response = ui.add(egui::SelectableLabel::new(selected, text.as_ref()));
+                                if table.draggable {
+                                    response = response.interact(egui::Sense::click_and_drag());
+                                }
+                                if response.dragged() {
+                                    dragged_row = Some(row_index);
+                                }
+                                if column == 0 {
+                                    track(widgets, format!("{id}/row/{row_index}"), &response, WidgetKind::TableRow);
+                                }
+                                if response.clicked() {
+                                    clicked_row = Some((row_index, ui.input(|i| i.modifiers)));
+                                }
+                            });
+                        }
+                    });
+                });
+        }).response;
+        track(widgets, id, &response, WidgetKind::Container);
+
+        if let Some(column) = clicked_header {
+            table.sort_by(column);
+        }
+        let selection = match clicked_row {
+            Some((row, modifiers)) if table.click_row(row, modifiers) => Some((
+                table.selected.iter().copied().collect::<Vec<_>>(),
+                table.selected.iter().map(|&index| table.rows[index].clone()).collect::<Vec<_>>(),
+            )),
+            _ => None,
+        };
+
+        let dragged = dragged_row.map(|row| {
+            let label = table.rows[row].first().map(cell_text).unwrap_or_default().into_owned();
+            (row, label)
+        });
+
+        if let Some((selected, rows)) = selection {
+            self.emit("table_select", id, serde_json::json!({ "selected": selected, "rows": rows }));
+        }
+        if let Some((row, label)) = dragged {
+            self.drag_item(id, &row.to_string(), label);
+        }
+    }
+}
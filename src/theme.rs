@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::EguiApp;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeBase {
+    #[default]
+    Dark,
+    Light,
+    /// Follows the light or dark preference of the OS.
+    System,
+}
+
+/// Colors that replace those of the base visuals. sRGB, unset keeps the base color.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ThemeColors {
+    pub panel_fill: Option<[u8; 3]>,
+    pub window_fill: Option<[u8; 3]>,
+    /// Background of text edits and scroll bars.
+    pub extreme_bg: Option<[u8; 3]>,
+    /// Background of striped rows.
+    pub faint_bg: Option<[u8; 3]>,
+    pub text: Option<[u8; 3]>,
+    pub hyperlink: Option<[u8; 3]>,
+    /// Selected text and selected widgets.
+    pub selection: Option<[u8; 3]>,
+    pub warn: Option<[u8; 3]>,
+    pub error: Option<[u8; 3]>,
+}
+
+impl ThemeColors {
+    /// Takes over the colors `other` sets.
+    pub fn merge(&mut self, other: ThemeColors) {
+        let ThemeColors { panel_fill, window_fill, extreme_bg, faint_bg, text, hyperlink, selection, warn, error } = other;
+        self.panel_fill = panel_fill.or(self.panel_fill);
+        self.window_fill = window_fill.or(self.window_fill);
+        self.extreme_bg = extreme_bg.or(self.extreme_bg);
+        self.faint_bg = faint_bg.or(self.faint_bg);
+        self.text = text.or(self.text);
+        self.hyperlink = hyperlink.or(self.hyperlink);
+        self.selection = selection.or(self.selection);
+        self.warn = warn.or(self.warn);
+        self.error = error.or(self.error);
+    }
+}
+
+fn color([r, g, b]: [u8; 3]) -> egui::Color32 {
+    egui::Color32::from_rgb(r, g, b)
+}
+
+/// `"proportional"`, `"monospace"`, or the name of a custom family.
+fn font_family(name: &str) -> egui::FontFamily {
+    match name {
+        "proportional" => egui::FontFamily::Proportional,
+        "monospace" => egui::FontFamily::Monospace,
+        custom => egui::FontFamily::Name(custom.into()),
+    }
+}
+
+fn default_family() -> String {
+    "proportional".to_string()
+}
+
+/// A TTF or OTF font file added to a font family.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct FontFace {
+    pub name: String,
+    pub path: String,
+    #[serde(default = "default_family")]
+    pub family: String,
+    /// Puts the font before the built-in ones instead of using it as a fallback.
+    #[serde(default = "crate::default_true")]
+    pub first: bool,
+}
+
+/// Size and family of a text style such as `"body"` or `"heading"`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TextStyleSpec {
+    pub size: f32,
+    #[serde(default = "default_family")]
+    pub family: String,
+}
+
+fn text_style(name: &str) -> egui::TextStyle {
+    match name {
+        "small" => egui::TextStyle::Small,
+        "body" => egui::TextStyle::Body,
+        "monospace" => egui::TextStyle::Monospace,
+        "button" => egui::TextStyle::Button,
+        "heading" => egui::TextStyle::Heading,
+        custom => egui::TextStyle::Name(custom.into()),
+    }
+}
+
+/// Spacing in points; unset keeps egui's default.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SpacingSpec {
+    pub item_spacing: Option<[f32; 2]>,
+    pub button_padding: Option<[f32; 2]>,
+    pub window_margin: Option<f32>,
+    pub indent: Option<f32>,
+    pub slider_width: Option<f32>,
+}
+
+impl SpacingSpec {
+    pub fn merge(&mut self, other: SpacingSpec) {
+        let SpacingSpec { item_spacing, button_padding, window_margin, indent, slider_width } = other;
+        self.item_spacing = item_spacing.or(self.item_spacing);
+        self.button_padding = button_padding.or(self.button_padding);
+        self.window_margin = window_margin.or(self.window_margin);
+        self.indent = indent.or(self.indent);
+        self.slider_width = slider_width.or(self.slider_width);
+    }
+}
+
+/// Everything scripts can change about the look of the UI, as saved in theme files.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    pub base: ThemeBase,
+    pub colors: ThemeColors,
+    pub fonts: Vec<FontFace>,
+    pub text_styles: BTreeMap<String, TextStyleSpec>,
+    pub spacing: SpacingSpec,
+    /// Corner radius of widgets, windows and menus.
+    pub rounding: Option<f32>,
+    /// Multiplies the scale factor of the OS.
+    pub ui_scale: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            base: ThemeBase::default(),
+            colors: ThemeColors::default(),
+            fonts: Vec::new(),
+            text_styles: BTreeMap::new(),
+            spacing: SpacingSpec::default(),
+            rounding: None,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl Theme {
+    /// Rejects sizes egui cannot lay out and families with no font, which
+    /// egui panics on.
+    fn check_text_style(&self, name: &str, spec: &TextStyleSpec) -> Result<(), String> {
+        if !spec.size.is_finite() || spec.size <= 0.0 {
+            return Err(format!("Text style '{}' needs a positive size, got {}", name, spec.size));
+        }
+        let built_in = matches!(spec.family.as_str(), "proportional" | "monospace");
+        if !built_in && !self.fonts.iter().any(|face| face.family == spec.family) {
+            return Err(format!("Text style '{}' uses font family '{}', which has no fonts", name, spec.family));
+        }
+        Ok(())
+    }
+}
+
+pub const MIN_UI_SCALE: f32 = 0.25;
+pub const MAX_UI_SCALE: f32 = 4.0;
+
+/// Reads a font file, rejecting files that are not TrueType or OpenType
+/// fonts since egui panics on them.
+pub fn read_font(path: &str) -> Result<Vec<u8>, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Cannot read font '{}': {}", path, e))?;
+    let known = [&[0x00, 0x01, 0x00, 0x00][..], b"OTTO", b"true", b"ttcf"];
+    if !known.iter().any(|magic| bytes.starts_with(magic)) {
+        return Err(format!("'{}' is not a TTF or OTF font", path));
+    }
+    Ok(bytes)
+}
+
+/// The theme and the data needed to apply it.
+#[derive(Debug, Clone)]
+pub struct ThemeState {
+    pub theme: Theme,
+    /// Contents of the font files in `theme.fonts`, keyed by font name.
+    pub font_data: BTreeMap<String, Vec<u8>>,
+    /// Whether the OS prefers dark mode, for [`ThemeBase::System`].
+    pub system_dark: bool,
+    /// Bumped on every change, so each egui context knows when to reapply.
+    pub generation: u64,
+}
+
+impl Default for ThemeState {
+    fn default() -> Self {
+        Self { theme: Theme::default(), font_data: BTreeMap::new(), system_dark: true, generation: 0 }
+    }
+}
+
+impl ThemeState {
+    pub fn changed(&mut self) {
+        self.generation += 1;
+    }
+
+    pub fn add_font(&mut self, face: FontFace) -> Result<(), String> {
+        let bytes = read_font(&face.path)?;
+        self.font_data.insert(face.name.clone(), bytes);
+        self.theme.fonts.retain(|existing| existing.name != face.name);
+        self.theme.fonts.push(face);
+        self.changed();
+        Ok(())
+    }
+
+    pub fn set_text_style(&mut self, name: String, spec: TextStyleSpec) -> Result<(), String> {
+        self.theme.check_text_style(&name, &spec)?;
+        self.theme.text_styles.insert(name, spec);
+        self.changed();
+        Ok(())
+    }
+
+    /// Replaces the theme, checking its text styles and loading its fonts
+    /// first so a bad theme changes nothing.
+    pub fn set_theme(&mut self, theme: Theme) -> Result<(), String> {
+        for (name, spec) in &theme.text_styles {
+            theme.check_text_style(name, spec)?;
+        }
+        let font_data = theme
+            .fonts
+            .iter()
+            .map(|face| Ok((face.name.clone(), read_font(&face.path)?)))
+            .collect::<Result<_, String>>()?;
+        self.theme = Theme { ui_scale: theme.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE), ..theme };
+        self.font_data = font_data;
+        self.changed();
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.theme).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Cannot write theme '{}': {}", path.display(), e))
+    }
+
+    pub fn load(&mut self, path: &Path) -> Result<(), String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read theme '{}': {}", path.display(), e))?;
+        let theme = serde_json::from_str(&json).map_err(|e| format!("Invalid theme '{}': {}", path.display(), e))?;
+        self.set_theme(theme)
+    }
+
+    pub fn visuals(&self) -> egui::Visuals {
+        let dark = match self.theme.base {
+            ThemeBase::Dark => true,
+            ThemeBase::Light => false,
+            ThemeBase::System => self.system_dark,
+        };
+        let mut visuals = if dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+
+        let colors = &self.theme.colors;
+        if let Some(fill) = colors.panel_fill {
+            visuals.panel_fill = color(fill);
+        }
+        if let Some(fill) = colors.window_fill {
+            visuals.window_fill = color(fill);
+        }
+        if let Some(bg) = colors.extreme_bg {
+            visuals.extreme_bg_color = color(bg);
+        }
+        if let Some(bg) = colors.faint_bg {
+            visuals.faint_bg_color = color(bg);
+        }
+        visuals.override_text_color = colors.text.map(color);
+        if let Some(link) = colors.hyperlink {
+            visuals.hyperlink_color = color(link);
+        }
+        if let Some(selection) = colors.selection {
+            visuals.selection.bg_fill = color(selection);
+        }
+        if let Some(warn) = colors.warn {
+            visuals.warn_fg_color = color(warn);
+        }
+        if let Some(error) = colors.error {
+            visuals.error_fg_color = color(error);
+        }
+
+        if let Some(radius) = self.theme.rounding {
+            let rounding = egui::Rounding::same(radius);
+            let widgets = &mut visuals.widgets;
+            for widget in [&mut widgets.noninteractive, &mut widgets.inactive, &mut widgets.hovered, &mut widgets.active, &mut widgets.open] {
+                widget.rounding = rounding;
+            }
+            visuals.window_rounding = rounding;
+            visuals.menu_rounding = rounding;
+        }
+        visuals
+    }
+
+    pub fn font_definitions(&self) -> egui::FontDefinitions {
+        let mut fonts = egui::FontDefinitions::default();
+        for face in &self.theme.fonts {
+            let Some(bytes) = self.font_data.get(&face.name) else { continue };
+            fonts.font_data.insert(face.name.clone(), egui::FontData::from_owned(bytes.clone()));
+            let family = fonts.families.entry(font_family(&face.family)).or_default();
+            if face.first {
+                family.insert(0, face.name.clone());
+            } else {
+                family.push(face.name.clone());
+            }
+        }
+        fonts
+    }
+
+    pub fn style(&self) -> egui::Style {
+        let mut style = egui::Style { visuals: self.visuals(), ..Default::default() };
+        for (name, spec) in &self.theme.text_styles {
+            style.text_styles.insert(text_style(name), egui::FontId::new(spec.size, font_family(&spec.family)));
+        }
+        let spacing = &self.theme.spacing;
+        if let Some([x, y]) = spacing.item_spacing {
+            style.spacing.item_spacing = egui::vec2(x, y);
+        }
+        if let Some([x, y]) = spacing.button_padding {
+            style.spacing.button_padding = egui::vec2(x, y);
+        }
+        if let Some(margin) = spacing.window_margin {
+            style.spacing.window_margin = egui::Margin::same(margin);
+        }
+        if let Some(indent) = spacing.indent {
+            style.spacing.indent = indent;
+        }
+        if let Some(width) = spacing.slider_width {
+            style.spacing.slider_width = width;
+        }
+        style
+    }
+}
+
+impl EguiApp {
+    /// Applies the theme to `ctx` if it changed since `ctx` last saw it.
+    /// Contexts that never saw a change keep egui's defaults.
+    pub(crate) fn theme_ui(&self, ctx: &egui::Context) {
+        let id = egui::Id::new("theme_generation");
+        let applied = ctx.data_mut(|data| data.get_temp::<u64>(id)).unwrap_or(0);
+        if applied == self.theme.generation {
+            return;
+        }
+        ctx.set_fonts(self.theme.font_definitions());
+        ctx.set_style(self.theme.style());
+        ctx.data_mut(|data| data.insert_temp(id, self.theme.generation));
+    }
+
+    /// Points are `native` physical pixels times the UI scale.
+    pub fn pixels_per_point(&self, native: f32) -> f32 {
+        native * self.theme.theme.ui_scale
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::automation::WidgetKind;
+use crate::events::RecordedEvent;
+use crate::{track, EguiApp};
+
+/// Width of the column with the component names.
+const GUTTER: f32 = 120.0;
+const RULER_HEIGHT: f32 = 20.0;
+const MARKER_RADIUS: f32 = 5.0;
+/// Zoom limits in points per second.
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 20_000.0;
+const ZOOM_STEP: f32 = 1.5;
+/// Gap between duplicated events and the events they copy.
+const DUPLICATE_GAP: i64 = 100;
+
+/// A change to `EguiApp::recorded_events`. Events are addressed by index
+/// and kept in timestamp order, so moving them may reorder them.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "edit", rename_all = "snake_case")]
+pub enum TimelineEdit {
+    Delete { indices: Vec<usize> },
+    /// Shifts the events by `delta` ms, stopping at the start of the recording.
+    Move { indices: Vec<usize>, delta: i64 },
+    /// Inserts copies of the events `delta` ms after them.
+    Duplicate {
+        indices: Vec<usize>,
+        #[serde(default)]
+        delta: i64,
+    },
+    Replace { index: usize, event: RecordedEvent },
+}
+
+/// Sorts `events` by timestamp, keeping the order of equal timestamps, and
+/// returns the new indices of the events marked in `marked`.
+fn sort_marked(events: &mut Vec<RecordedEvent>, marked: Vec<bool>) -> Vec<usize> {
+    let mut tagged: Vec<(RecordedEvent, bool)> = events.drain(..).zip(marked).collect();
+    tagged.sort_by_key(|(event, _)| event.timestamp);
+    let indices = tagged.iter().enumerate().filter(|(_, (_, marked))| *marked).map(|(index, _)| index).collect();
+    events.extend(tagged.into_iter().map(|(event, _)| event));
+    indices
+}
+
+fn shift(timestamp: u64, delta: i64) -> u64 {
+    timestamp.saturating_add_signed(delta)
+}
+
+/// Applies `edit` and returns the indices of the moved, copied or replaced
+/// events, or nothing for deletions.
+pub fn apply_edit(events: &mut Vec<RecordedEvent>, edit: &TimelineEdit) -> Result<Vec<usize>, String> {
+    let indices: BTreeSet<usize> = match edit {
+        TimelineEdit::Delete { indices } | TimelineEdit::Move { indices, .. } | TimelineEdit::Duplicate { indices, .. } => {
+            indices.iter().copied().collect()
+        }
+        TimelineEdit::Replace { index, .. } => BTreeSet::from([*index]),
+    };
+    if let Some(index) = indices.iter().find(|index| **index >= events.len()) {
+        return Err(format!("No recorded event at index {}", index));
+    }
+
+    let mut marked = vec![false; events.len()];
+    match edit {
+        TimelineEdit::Delete { .. } => {
+            let mut index = 0;
+            events.retain(|_| {
+                index += 1;
+                !indices.contains(&(index - 1))
+            });
+            return Ok(Vec::new());
+        }
+        TimelineEdit::Move { delta, .. } => {
+            for &index in &indices {
+                events[index].timestamp = shift(events[index].timestamp, *delta);
+                marked[index] = true;
+            }
+        }
+        TimelineEdit::Duplicate { delta, .. } => {
+            for &index in &indices {
+                let mut copy = events[index].clone();
+                copy.timestamp = shift(copy.timestamp, *delta);
+                events.push(copy);
+                marked.push(true);
+            }
+        }
+        TimelineEdit::Replace { index, event } => {
+            events[*index] = event.clone();
+            marked[*index] = true;
+        }
+    }
+    Ok(sort_marked(events, marked))
+}
+
+/// `m:ss.mmm`
+pub fn format_time(ms: u64) -> String {
+    format!("{}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
+
+/// The smallest tick step in ms that keeps ruler labels `min_spacing` apart.
+fn tick_step(zoom: f32, min_spacing: f32) -> f64 {
+    const STEPS: [f64; 13] = [10.0, 20.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2000.0, 5000.0, 10_000.0, 30_000.0, 60_000.0, 300_000.0];
+    STEPS.into_iter().find(|step| (*step / 1000.0) as f32 * zoom >= min_spacing).unwrap_or(600_000.0)
+}
+
+/// A stable color for each event type.
+fn event_color(event_type: &str) -> egui::Color32 {
+    let hash = event_type.bytes().fold(5381u32, |hash, byte| hash.wrapping_mul(33) ^ byte as u32);
+    egui::epaint::Hsva::new((hash % 360) as f32 / 360.0, 0.6, 0.9, 1.0).into()
+}
+
+/// A view of the recorded events: one row per component on a zoomable time
+/// axis, with a scrubber and a selection.
+#[derive(Debug, Clone)]
+pub struct TimelineState {
+    /// Points per second.
+    pub zoom: f32,
+    /// Time at the left edge of the rows, in ms.
+    pub offset: f64,
+    /// Scrubber position in ms.
+    pub cursor: u64,
+    /// Indices into `EguiApp::recorded_events`.
+    pub selected: BTreeSet<usize>,
+    pub row_height: f32,
+    /// How far the selected events are being dragged, in ms.
+    drag: Option<f64>,
+}
+
+impl Default for TimelineState {
+    fn default() -> Self {
+        Self { zoom: 100.0, offset: 0.0, cursor: 0, selected: BTreeSet::new(), row_height: 22.0, drag: None }
+    }
+}
+
+impl TimelineState {
+    pub fn new(args: &crate::SetTimelineArgs) -> Self {
+        Self { row_height: args.row_height.unwrap_or(22.0).max(2.0 * MARKER_RADIUS + 4.0), ..Default::default() }
+    }
+
+    /// Zooms so that `duration` ms fill `width` points.
+    fn fit(&mut self, duration: u64, width: f32) {
+        let seconds = (duration as f32 / 1000.0).max(1.0);
+        self.zoom = ((width - 2.0 * MARKER_RADIUS) / seconds).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = 0.0;
+    }
+}
+
+impl EguiApp {
+    /// Applies `edit` to the recorded events, drops the snapshots it
+    /// invalidates, selects the edited events in every timeline and reports
+    /// it as a `recording_edit` event.
+    pub fn edit_recording(&mut self, edit: TimelineEdit) -> Result<Vec<usize>, String> {
+        if self.is_recording {
+            return Err("Cannot edit the recording while recording".to_string());
+        }
+        let before = self.recorded_events.clone();
+        let indices = apply_edit(&mut self.recorded_events, &edit)?;
+        // Snapshots taken after the first changed event no longer match the
+        // events before them.
+        let changed = before
+            .iter()
+            .zip(&self.recorded_events)
+            .position(|(old, new)| old != new)
+            .unwrap_or(before.len().min(self.recorded_events.len()));
+        self.recorded_snapshots.retain(|snapshot| snapshot.events <= changed);
+        for timeline in self.timelines.values_mut() {
+            timeline.selected = indices.iter().copied().collect();
+        }
+        self.playback_index = self.playback_index.min(self.recorded_events.len());
+        let mut data = serde_json::to_value(&edit).map_err(|e| e.to_string())?;
+        data["result"] = serde_json::json!(indices);
+        self.emit("recording_edit", "recording", data);
+        Ok(indices)
+    }
+
+    pub(crate) fn timeline_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(timeline) = self.timelines.get_mut(id) else { return };
+        if self.is_playing {
+            timeline.cursor = self.playback.position.round() as u64;
+        }
+        let events = &self.recorded_events;
+        let snapshots = &self.recorded_snapshots;
+        let widgets = &mut self.widgets;
+        let editable = !self.is_recording;
+        timeline.selected.retain(|index| *index < events.len());
+        let selection_before = timeline.selected.clone();
+        let mut edit = None;
+        let mut seek = None;
+
+        ui.push_id(("timeline", id), |ui| {
+            let duration = events.last().map_or(0, |event| event.timestamp);
+            let selected: Vec<usize> = timeline.selected.iter().copied().collect();
+            let width = ui.available_width();
+            ui.horizontal(|ui| {
+                let response = ui.button("➖").on_hover_text("Zoom out");
+                track(widgets, format!("{id}/zoom_out"), &response, WidgetKind::Button);
+                if response.clicked() {
+                    timeline.zoom = (timeline.zoom / ZOOM_STEP).max(MIN_ZOOM);
+                }
+                let response = ui.button("➕").on_hover_text("Zoom in");
+                track(widgets, format!("{id}/zoom_in"), &response, WidgetKind::Button);
+                if response.clicked() {
+                    timeline.zoom = (timeline.zoom * ZOOM_STEP).min(MAX_ZOOM);
+                }
+                let response = ui.button("Fit");
+                track(widgets, format!("{id}/fit"), &response, WidgetKind::Button);
+                if response.clicked() {
+                    timeline.fit(duration, width - GUTTER);
+                }
+                ui.separator();
+                let response = ui.add_enabled(editable && !selected.is_empty(), egui::Button::new("🗐 Duplicate"));
+                track(widgets, format!("{id}/duplicate"), &response, WidgetKind::Button);
+                if response.clicked() {
+                    let times = selected.iter().map(|index| events[*index].timestamp as i64);
+                    let (first, last) = times.fold((i64::MAX, i64::MIN), |(first, last), t| (first.min(t), last.max(t)));
+                    edit = Some(TimelineEdit::Duplicate { indices: selected.clone(), delta: last - first + DUPLICATE_GAP });
+                }
+                let response = ui.add_enabled(editable && !selected.is_empty(), egui::Button::new("🗑 Delete"));
+                track(widgets, format!("{id}/delete"), &response, WidgetKind::Button);
+                if response.clicked() {
+                    edit = Some(TimelineEdit::Delete { indices: selected.clone() });
+                }
+                ui.separator();
+                ui.label(format!("{} events · {}", events.len(), format_time(timeline.cursor)));
+            });
+
+            let rows: Vec<&str> = events.iter().map(|event| event.component_id.as_str()).collect::<BTreeSet<_>>().into_iter().collect();
+            let height = RULER_HEIGHT + rows.len().max(1) as f32 * timeline.row_height;
+            let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), height), egui::Sense::hover());
+            track(widgets, id, &response, WidgetKind::Container);
+            // Empty rather than inverted when narrower than the gutter.
+            let left = (rect.left() + GUTTER).min(rect.right());
+            let area = egui::Rect::from_min_max(egui::pos2(left, rect.top()), rect.max);
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals().clone();
+
+            // Ctrl+wheel zooms around the pointer, horizontal scrolling pans.
+            if let Some(pointer) = response.hover_pos() {
+                let (zoom, scroll) = ui.input(|i| (i.zoom_delta(), i.scroll_delta));
+                if zoom != 1.0 {
+                    let anchor = timeline.offset + ((pointer.x - area.left()) / timeline.zoom * 1000.0) as f64;
+                    timeline.zoom = (timeline.zoom * zoom).clamp(MIN_ZOOM, MAX_ZOOM);
+                    timeline.offset = anchor - ((pointer.x - area.left()) / timeline.zoom * 1000.0) as f64;
+                }
+                timeline.offset -= (scroll.x / timeline.zoom * 1000.0) as f64;
+                timeline.offset = timeline.offset.max(0.0);
+            }
+            let (zoom, offset) = (timeline.zoom, timeline.offset);
+            let to_x = |ms: f64| area.left() + ((ms - offset) / 1000.0) as f32 * zoom;
+            let to_ms = |x: f32| offset + ((x - area.left()) / zoom * 1000.0) as f64;
+
+            painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+            for (row, component) in rows.iter().enumerate() {
+                let top = rect.top() + RULER_HEIGHT + row as f32 * timeline.row_height;
+                let row_rect = egui::Rect::from_min_size(egui::pos2(rect.left(), top), egui::vec2(rect.width(), timeline.row_height));
+                if row % 2 == 1 {
+                    painter.rect_filled(row_rect, 0.0, visuals.faint_bg_color);
+                }
+                painter.text(
+                    egui::pos2(rect.left() + 4.0, row_rect.center().y),
+                    egui::Align2::LEFT_CENTER,
+                    *component,
+                    egui::FontId::proportional(12.0),
+                    visuals.text_color(),
+                );
+            }
+
+            let step = tick_step(zoom, 70.0);
+            let mut tick = (offset / step).floor() * step;
+            while to_x(tick) <= area.right() {
+                let x = to_x(tick);
+                if x >= area.left() {
+                    painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], visuals.widgets.noninteractive.bg_stroke);
+                    painter.text(
+                        egui::pos2(x + 3.0, rect.top() + 2.0),
+                        egui::Align2::LEFT_TOP,
+                        format_time(tick as u64),
+                        egui::FontId::monospace(10.0),
+                        visuals.weak_text_color(),
+                    );
+                }
+                tick += step;
+            }
+            // Snapshots are marked on the ruler as keyframes.
+            for snapshot in snapshots {
+                let x = to_x(snapshot.timestamp as f64);
+                if (area.left()..=area.right()).contains(&x) {
+                    let bottom = rect.top() + RULER_HEIGHT;
+                    let points = vec![egui::pos2(x, bottom - 6.0), egui::pos2(x + 4.0, bottom), egui::pos2(x - 4.0, bottom)];
+                    painter.add(egui::Shape::convex_polygon(points, visuals.widgets.inactive.fg_stroke.color, egui::Stroke::NONE));
+                }
+            }
+
+            // The ruler is the scrubber; it is tracked as a slider over the
+            // visible times, widened by the handle inset automation expects.
+            let ruler_rect = egui::Rect::from_min_max(area.min, egui::pos2(area.right(), area.top() + RULER_HEIGHT));
+            let ruler = ui.interact(ruler_rect, ui.id().with("ruler"), egui::Sense::click_and_drag());
+            let inset = RULER_HEIGHT / 2.5;
+            track(widgets, format!("{id}/ruler"), &ruler, WidgetKind::Slider {
+                rail: ruler_rect.expand2(egui::vec2(inset, 0.0)),
+                range: offset..=to_ms(area.right()),
+                logarithmic: false,
+            });
+            if ruler.clicked() || ruler.dragged() {
+                if let Some(pointer) = ruler.interact_pointer_pos() {
+                    seek = Some(to_ms(pointer.x.clamp(area.left(), area.right())).max(0.0).round() as u64);
+                }
+            }
+
+            let mut clicked = None;
+            for (index, event) in events.iter().enumerate() {
+                let is_selected = timeline.selected.contains(&index);
+                let dragged_by = if is_selected { timeline.drag.unwrap_or(0.0) } else { 0.0 };
+                let x = to_x(event.timestamp as f64 + dragged_by);
+                if x < area.left() - MARKER_RADIUS || x > area.right() + MARKER_RADIUS {
+                    continue;
+                }
+                let row = rows.iter().position(|component| *component == event.component_id).unwrap_or(0);
+                let center = egui::pos2(x, rect.top() + RULER_HEIGHT + (row as f32 + 0.5) * timeline.row_height);
+                let marker_rect = egui::Rect::from_center_size(center, egui::Vec2::splat(2.0 * MARKER_RADIUS + 4.0));
+                let marker = ui
+                    .interact(marker_rect, ui.id().with(("event", index)), egui::Sense::click_and_drag())
+                    .on_hover_text(format!("{}\n{}", event.event_type, format_time(event.timestamp)));
+                track(widgets, format!("{id}/event/{index}"), &marker, WidgetKind::TimelineEvent);
+
+                painter.circle_filled(center, MARKER_RADIUS, event_color(&event.event_type));
+                if is_selected {
+                    painter.circle_stroke(center, MARKER_RADIUS + 2.0, visuals.selection.stroke);
+                }
+                if marker.clicked() {
+                    clicked = Some(index);
+                }
+                if editable && marker.drag_started() {
+                    if !is_selected {
+                        timeline.selected = BTreeSet::from([index]);
+                    }
+                    timeline.drag = Some(0.0);
+                }
+                if let Some(drag) = timeline.drag.as_mut().filter(|_| marker.dragged()) {
+                    *drag += (marker.drag_delta().x / zoom * 1000.0) as f64;
+                }
+                if marker.drag_released() {
+                    let delta = timeline.drag.take().unwrap_or(0.0).round() as i64;
+                    if delta != 0 {
+                        edit = Some(TimelineEdit::Move { indices: timeline.selected.iter().copied().collect(), delta });
+                    }
+                }
+            }
+
+            // Created after the markers so that they get clicks first.
+            let background = ui.interact(area, ui.id().with("background"), egui::Sense::click());
+            match clicked {
+                Some(index) => {
+                    let modifiers = ui.input(|i| i.modifiers);
+                    if modifiers.shift || modifiers.command {
+                        if !timeline.selected.remove(&index) {
+                            timeline.selected.insert(index);
+                        }
+                    } else {
+                        timeline.selected = BTreeSet::from([index]);
+                    }
+                }
+                None if background.clicked() => timeline.selected.clear(),
+                None => {}
+            }
+            if editable && !timeline.selected.is_empty() && response.hovered() && ui.input(|i| i.key_pressed(egui::Key::Delete)) {
+                edit = Some(TimelineEdit::Delete { indices: timeline.selected.iter().copied().collect() });
+            }
+
+            let cursor_x = to_x(seek.unwrap_or(timeline.cursor) as f64);
+            if (area.left()..=area.right()).contains(&cursor_x) {
+                let color = visuals.warn_fg_color;
+                painter.line_segment([egui::pos2(cursor_x, rect.top()), egui::pos2(cursor_x, rect.bottom())], egui::Stroke::new(2.0, color));
+            }
+
+            // Inspector
+            match timeline.selected.iter().collect::<Vec<_>>().as_slice() {
+                [] => {}
+                [index] => {
+                    let event = &events[**index];
+                    egui::Grid::new("inspector").num_columns(2).show(ui, |ui| {
+                        ui.label("Type");
+                        ui.label(&event.event_type);
+                        ui.end_row();
+                        ui.label("Component");
+                        ui.label(&event.component_id);
+                        ui.end_row();
+                        ui.label("Time");
+                        ui.label(format_time(event.timestamp));
+                        ui.end_row();
+                    });
+                    let data = serde_json::to_string_pretty(&event.event_data).unwrap_or_default();
+                    ui.label(egui::RichText::new(data).monospace());
+                }
+                selected => {
+                    ui.label(format!("{} events selected", selected.len()));
+                }
+            }
+        });
+
+        let selection = timeline.selected.clone();
+        if let Some(timestamp) = seek.filter(|timestamp| *timestamp != timeline.cursor) {
+            timeline.cursor = timestamp;
+            self.emit("timeline_seek", id, serde_json::json!({ "timestamp": timestamp }));
+            // Scrubbing while playing moves playback along.
+            if self.is_playing {
+                if let Err(e) = self.seek_playback(timestamp) {
+                    self.emit("playback_error", "playback", serde_json::json!({ "message": e }));
+                }
+            }
+        }
+        if selection != selection_before {
+            self.emit("timeline_select", id, serde_json::json!({ "selected": selection }));
+        }
+        if let Some(edit) = edit {
+            if let Err(e) = self.edit_recording(edit) {
+                self.emit("recording_error", id, serde_json::json!({ "message": e }));
+            }
+        }
+    }
+}
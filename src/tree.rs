@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+/// Id of the primary OS window created at startup.
+pub const MAIN_WINDOW: &str = "main";
+
+/// Parent/child relations between widget ids. A parent is a native window id
+/// or a container id; children are kept in the order they were attached.
+#[derive(Default, Debug, Clone)]
+pub struct WidgetTree {
+    parents: BTreeMap<String, String>,
+    children: BTreeMap<String, Vec<String>>,
+}
+
+impl WidgetTree {
+    /// Attaches `id` under `parent`. Re-attaching to the same parent keeps the
+    /// current position; attaching elsewhere moves it to the end of the new parent.
+    /// Returns `false` and leaves the tree unchanged if `parent` is `id` itself
+    /// or one of its descendants.
+    pub fn attach(&mut self, id: &str, parent: &str) -> bool {
+        if self.parent(id) == Some(parent) {
+            return true;
+        }
+        if self.is_ancestor_or_self(id, parent) {
+            return false;
+        }
+        self.detach(id);
+        self.parents.insert(id.to_string(), parent.to_string());
+        self.children
+            .entry(parent.to_string())
+            .or_default()
+            .push(id.to_string());
+        true
+    }
+
+    fn is_ancestor_or_self<'a>(&'a self, ancestor: &str, mut id: &'a str) -> bool {
+        loop {
+            if id == ancestor {
+                return true;
+            }
+            match self.parent(id) {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+
+    pub fn detach(&mut self, id: &str) {
+        if let Some(parent) = self.parents.remove(id) {
+            if let Some(siblings) = self.children.get_mut(&parent) {
+                siblings.retain(|child| child != id);
+            }
+        }
+    }
+
+    pub fn parent(&self, id: &str) -> Option<&str> {
+        self.parents.get(id).map(String::as_str)
+    }
+
+    pub fn children(&self, parent: &str) -> &[String] {
+        self.children.get(parent).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.parents.contains_key(id)
+    }
+
+    /// The top-most ancestor of `id`, normally a native window id.
+    pub fn root_of<'a>(&'a self, mut id: &'a str) -> &'a str {
+        while let Some(parent) = self.parent(id) {
+            id = parent;
+        }
+        id
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::automation::{TrackedWidget, WidgetKind};
+use crate::scene::SceneObject;
+use crate::{track, EguiApp};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TreeNode {
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub children: Vec<TreeNode>,
+    /// The node has children the script sends when it is first expanded.
+    #[serde(default)]
+    pub lazy: bool,
+}
+
+impl TreeNode {
+    fn has_children(&self) -> bool {
+        self.lazy || !self.children.is_empty()
+    }
+}
+
+fn find<'a>(nodes: &'a [TreeNode], id: &str) -> Option<&'a TreeNode> {
+    nodes.iter().find_map(|node| {
+        if node.id == id { Some(node) } else { find(&node.children, id) }
+    })
+}
+
+fn find_mut<'a>(nodes: &'a mut [TreeNode], id: &str) -> Option<&'a mut TreeNode> {
+    for node in nodes {
+        if node.id == id {
+            return Some(node);
+        }
+        if let Some(found) = find_mut(&mut node.children, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn remove(nodes: &mut Vec<TreeNode>, id: &str) -> Option<TreeNode> {
+    if let Some(index) = nodes.iter().position(|node| node.id == id) {
+        return Some(nodes.remove(index));
+    }
+    nodes.iter_mut().find_map(|node| remove(&mut node.children, id))
+}
+
+/// A script-defined tree, or the outliner of the 3D scene objects.
+#[derive(Debug, Clone, Default)]
+pub struct TreeViewState {
+    pub roots: Vec<TreeNode>,
+    pub expanded: BTreeSet<String>,
+    pub selected: BTreeSet<String>,
+    pub multi_select: bool,
+    /// Nodes can be dragged onto other nodes to reparent them, or onto drop
+    /// targets.
+    pub draggable: bool,
+    /// Mirrors `EguiApp::scene_objects` instead of showing `roots`.
+    pub outliner: bool,
+    /// Lazy nodes whose children were requested but not received yet.
+    pub loading: BTreeSet<String>,
+}
+
+impl TreeViewState {
+    pub fn new(args: &crate::SetTreeArgs) -> Self {
+        Self {
+            roots: args.nodes.clone(),
+            multi_select: args.multi_select,
+            draggable: args.draggable && !args.outliner,
+            outliner: args.outliner,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the children of `node`, e.g. in reply to a `tree_expand` event.
+    pub fn set_children(&mut self, node: &str, children: Vec<TreeNode>) -> bool {
+        let Some(found) = find_mut(&mut self.roots, node) else { return false };
+        found.children = children;
+        found.lazy = false;
+        self.loading.remove(node);
+        true
+    }
+
+    /// Moves `node` to the end of `parent`'s children. Fails if either is
+    /// missing or `parent` is `node` or one of its descendants.
+    pub fn reparent(&mut self, node: &str, parent: &str) -> bool {
+        let Some(moving) = find(&self.roots, node) else { return false };
+        if find(std::slice::from_ref(moving), parent).is_some() || find(&self.roots, parent).is_none() {
+            return false;
+        }
+        let moving = remove(&mut self.roots, node).expect("found above");
+        let target = find_mut(&mut self.roots, parent).expect("found above");
+        target.children.push(moving);
+        self.expanded.insert(parent.to_string());
+        true
+    }
+}
+
+/// Groups scene objects under one root node per scene.
+fn outliner_nodes(objects: &BTreeMap<String, SceneObject>) -> Vec<TreeNode> {
+    let mut scenes: BTreeMap<&str, Vec<TreeNode>> = BTreeMap::new();
+    for (id, object) in objects {
+        scenes.entry(object.scene_id.as_str()).or_default().push(TreeNode {
+            id: id.clone(),
+            label: id.clone(),
+            icon: Some(object.icon().to_string()),
+            children: Vec::new(),
+            lazy: false,
+        });
+    }
+    scenes
+        .into_iter()
+        .map(|(scene, children)| TreeNode {
+            id: scene.to_string(),
+            label: scene.to_string(),
+            icon: Some("🎬".to_string()),
+            children,
+            lazy: false,
+        })
+        .collect()
+}
+
+/// What happened to the rows of a tree during one frame.
+struct TreeFrame<'a> {
+    tree_id: &'a str,
+    state: &'a TreeViewState,
+    widgets: &'a mut BTreeMap<String, TrackedWidget>,
+    rows: Vec<(String, egui::Rect)>,
+    toggled: Option<String>,
+    clicked: Option<(String, egui::Modifiers)>,
+    dragging: Option<String>,
+    dropped: Option<String>,
+}
+
+fn node_ui(ui: &mut egui::Ui, frame: &mut TreeFrame<'_>, node: &TreeNode, depth: usize) {
+    let expanded = frame.state.expanded.contains(&node.id);
+    ui.horizontal(|ui| {
+        ui.add_space(depth as f32 * ui.spacing().indent);
+        if node.has_children() {
+            let arrow = if expanded { "⏷" } else { "⏵" };
+            let response = ui.add(egui::Button::new(arrow).frame(false).small());
+            track(frame.widgets, format!("{}/{}/toggle", frame.tree_id, node.id), &response, WidgetKind::Button);
+            if response.clicked() {
+                frame.toggled = Some(node.id.clone());
+            }
+        } else {
+            ui.add_space(ui.spacing().icon_width);
+        }
+
+        let text = match &node.icon {
+            Some(icon) => format!("{icon} {}", node.label),
+            None => node.label.clone(),
+        };
+        let selected = frame.state.selected.contains(&node.id);
+        let mut response = ui.add(egui::SelectableLabel::new(selected, text));
+        if frame.state.draggable {
+            response = response.interact(egui::Sense::click_and_drag());
+        }
+        track(frame.widgets, format!("{}/{}", frame.tree_id, node.id), &response, WidgetKind::TreeNode);
+        if response.clicked() {
+            frame.clicked = Some((node.id.clone(), ui.input(|i| i.modifiers)));
+        }
+        if response.dragged() {
+            frame.dragging = Some(node.id.clone());
+        }
+        if response.drag_released() {
+            frame.dropped = Some(node.id.clone());
+        }
+        frame.rows.push((node.id.clone(), response.rect));
+    });
+
+    if expanded {
+        if frame.state.loading.contains(&node.id) {
+            ui.horizontal(|ui| {
+                ui.add_space((depth + 1) as f32 * ui.spacing().indent + ui.spacing().icon_width);
+                ui.spinner();
+            });
+        }
+        for child in &node.children {
+            node_ui(ui, frame, child, depth + 1);
+        }
+    }
+}
+
+impl EguiApp {
+    pub(crate) fn tree_view_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(tree) = self.tree_views.get_mut(id) else { return };
+        if tree.outliner {
+            tree.roots = outliner_nodes(&self.scene_objects);
+            tree.selected = self.selected_object.iter().cloned().collect();
+        }
+
+        let mut frame = TreeFrame {
+            tree_id: id,
+            state: tree,
+            widgets: &mut self.widgets,
+            rows: Vec::new(),
+            toggled: None,
+            clicked: None,
+            dragging: None,
+            dropped: None,
+        };
+        let response = ui
+            .push_id(("tree", id), |ui| {
+                let state = frame.state;
+                for node in &state.roots {
+                    node_ui(ui, &mut frame, node, 0);
+                }
+            })
+            .response;
+        track(frame.widgets, id, &response, WidgetKind::Container);
+
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        let drop_target = |frame: &TreeFrame<'_>| {
+            let pointer = pointer?;
+            frame.rows.iter().find(|(_, rect)| rect.contains(pointer)).map(|(node, _)| node.clone())
+        };
+        let dropped = frame.dropped.take().and_then(|node| Some((node, drop_target(&frame)?)));
+        let TreeFrame { toggled, clicked, dragging, .. } = frame;
+
+        let mut events = Vec::new();
+        if let Some(node) = toggled {
+            let expanded = !tree.expanded.remove(&node);
+            if expanded {
+                tree.expanded.insert(node.clone());
+                let lazy = find(&tree.roots, &node).is_some_and(|found| found.lazy);
+                if lazy && tree.loading.insert(node.clone()) {
+                    events.push(("tree_load", serde_json::json!({ "node": node })));
+                }
+            }
+            events.push(("tree_expand", serde_json::json!({ "node": node, "expanded": expanded })));
+        }
+        if let Some((node, modifiers)) = clicked {
+            if tree.multi_select && modifiers.command {
+                if !tree.selected.remove(&node) {
+                    tree.selected.insert(node);
+                }
+            } else {
+                tree.selected = BTreeSet::from([node]);
+            }
+            events.push(("tree_select", serde_json::json!({ "selected": tree.selected })));
+        }
+        if let Some((node, parent)) = dropped {
+            if node != parent && tree.reparent(&node, &parent) {
+                events.push(("tree_reparent", serde_json::json!({ "node": node, "parent": parent })));
+            }
+        }
+
+        if tree.outliner {
+            // Scene rows only group objects and cannot be selected.
+            let selected = tree.selected.iter().find(|node| self.scene_objects.contains_key(*node)).cloned();
+            if let Some(object_id) = selected.filter(|object_id| self.selected_object.as_ref() != Some(object_id)) {
+                let scene_id = self.scene_objects[&object_id].scene_id.clone();
+                self.selected_object = Some(object_id.clone());
+                self.emit("object_select", &object_id, serde_json::json!({ "scene_id": scene_id }));
+            }
+        }
+        let dragging = dragging.map(|node| {
+            let label = find(&tree.roots, &node).map_or_else(|| node.clone(), |found| found.label.clone());
+            (node, format!("Move {label}"))
+        });
+        for (event_type, data) in events {
+            self.emit(event_type, id, data);
+        }
+        if let Some((node, label)) = dragging {
+            self.drag_item(id, &node, label);
+        }
+    }
+}
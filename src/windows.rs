@@ -0,0 +1,247 @@
+use egui_wgpu::renderer::ScreenDescriptor;
+use egui_winit::State as EguiWinitState;
+use winit::event::WindowEvent;
+use winit::event_loop::{EventLoopProxy, EventLoopWindowTarget};
+use winit::window::{Window, WindowBuilder};
+
+use crate::accessibility::UserEvent;
+use crate::state_3d::State3D;
+use crate::EguiApp;
+
+/// Window changes requested by scripts, applied by the event loop since only
+/// it can create and modify OS windows.
+#[derive(Debug, Clone)]
+pub enum WindowCommand {
+    Open(crate::OpenWindowArgs),
+    Close { id: String },
+    SetTitle { id: String, title: String },
+    SetSize { id: String, width: f64, height: f64 },
+    SetPosition { id: String, x: f64, y: f64 },
+}
+
+impl WindowCommand {
+    pub fn id(&self) -> &str {
+        match self {
+            WindowCommand::Open(args) => &args.id,
+            WindowCommand::Close { id }
+            | WindowCommand::SetTitle { id, .. }
+            | WindowCommand::SetSize { id, .. }
+            | WindowCommand::SetPosition { id, .. } => id,
+        }
+    }
+
+    /// Applies title, size and position changes to `window`. `Open` re-applies
+    /// its settings to an already open window.
+    pub fn apply(&self, window: &Window) {
+        match self {
+            WindowCommand::Open(args) => {
+                window.set_title(&args.title);
+                if let (Some(width), Some(height)) = (args.width, args.height) {
+                    window.set_inner_size(winit::dpi::LogicalSize::new(width, height));
+                }
+                if let (Some(x), Some(y)) = (args.x, args.y) {
+                    window.set_outer_position(winit::dpi::LogicalPosition::new(x, y));
+                }
+            }
+            WindowCommand::SetTitle { title, .. } => window.set_title(title),
+            WindowCommand::SetSize { width, height, .. } => {
+                window.set_inner_size(winit::dpi::LogicalSize::new(*width, *height));
+            }
+            WindowCommand::SetPosition { x, y, .. } => {
+                window.set_outer_position(winit::dpi::LogicalPosition::new(*x, *y));
+            }
+            WindowCommand::Close { .. } => {}
+        }
+    }
+}
+
+/// Reports close, resize, move and focus changes of window `id` to scripts.
+pub fn report_event(app: &mut EguiApp, id: &str, event: &WindowEvent) {
+    match event {
+        WindowEvent::CloseRequested => app.emit("window_close", id, serde_json::json!({})),
+        WindowEvent::Resized(size) => app.emit(
+            "window_resize",
+            id,
+            serde_json::json!({ "width": size.width, "height": size.height }),
+        ),
+        WindowEvent::Moved(position) => app.emit(
+            "window_move",
+            id,
+            serde_json::json!({ "x": position.x, "y": position.y }),
+        ),
+        WindowEvent::Focused(focused) => app.emit(
+            "window_focus",
+            id,
+            serde_json::json!({ "focused": focused }),
+        ),
+        _ => {}
+    }
+}
+
+/// An additional OS window opened by a script. It has its own surface, egui
+/// context and renderer, and shares the wgpu device of the main window.
+pub struct NativeWindow {
+    pub id: String,
+    pub window: Window,
+    surface: wgpu::Surface,
+    config: wgpu::SurfaceConfiguration,
+    egui_ctx: egui::Context,
+    egui_state: EguiWinitState,
+    egui_renderer: egui_wgpu::Renderer,
+}
+
+impl NativeWindow {
+    pub fn open<T>(
+        target: &EventLoopWindowTarget<T>,
+        proxy: &EventLoopProxy<UserEvent>,
+        state_3d: &State3D,
+        args: &crate::OpenWindowArgs,
+    ) -> Result<Self, String> {
+        let mut builder = WindowBuilder::new()
+            .with_title(&args.title)
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                args.width.unwrap_or(640.0),
+                args.height.unwrap_or(480.0),
+            ))
+            .with_visible(false);
+        if let (Some(x), Some(y)) = (args.x, args.y) {
+            builder = builder.with_position(winit::dpi::LogicalPosition::new(x, y));
+        }
+        let window = builder.build(target).map_err(|e| e.to_string())?;
+
+        let surface = unsafe { state_3d.instance.create_surface(&window) }.map_err(|e| e.to_string())?;
+        let surface_caps = surface.get_capabilities(&state_3d.adapter);
+        let surface_format = surface_caps.formats.iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .or_else(|| surface_caps.formats.first().copied())
+            .ok_or("Surface is not supported by the adapter")?;
+
+        let size = window.inner_size();
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&state_3d.device, &config);
+
+        let egui_renderer = egui_wgpu::Renderer::new(&state_3d.device, config.format, None, 1);
+
+        // AccessKit must be set up before the window is shown.
+        let egui_ctx = egui::Context::default();
+        egui_ctx.enable_accesskit();
+        let mut egui_state = EguiWinitState::new(target);
+        let placeholder_ctx = egui_ctx.clone();
+        egui_state.init_accesskit(&window, proxy.clone(), move || placeholder_ctx.accesskit_placeholder_tree_update());
+        window.set_visible(true);
+
+        Ok(Self {
+            id: args.id.clone(),
+            window,
+            surface,
+            config,
+            egui_ctx,
+            egui_state,
+            egui_renderer,
+        })
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(device, &self.config);
+        }
+    }
+
+    pub fn on_event(&mut self, device: &wgpu::Device, event: &WindowEvent) {
+        match event {
+            WindowEvent::Resized(new_size) => self.resize(device, *new_size),
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => self.resize(device, **new_inner_size),
+            _ => {}
+        }
+
+        let _ = self.egui_state.on_event(&self.egui_ctx, event);
+    }
+
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    pub fn on_accesskit_action_request(&mut self, request: egui::accesskit::ActionRequest) {
+        self.egui_state.on_accesskit_action_request(request);
+    }
+
+    pub fn redraw(&mut self, app: &mut EguiApp, state_3d: &State3D) {
+        let pixels_per_point = app.pixels_per_point(self.window.scale_factor() as f32);
+        self.egui_state.set_pixels_per_point(pixels_per_point);
+        let raw_input = self.egui_state.take_egui_input(&self.window);
+        self.egui_ctx.begin_frame(raw_input);
+        app.window_ui(&self.egui_ctx, &self.id);
+        let output = self.egui_ctx.end_frame();
+        self.egui_state.handle_platform_output(&self.window, &self.egui_ctx, output.platform_output);
+        let paint_jobs = self.egui_ctx.tessellate(output.shapes);
+
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::SurfaceError::Lost) => {
+                self.surface.configure(&state_3d.device, &self.config);
+                return;
+            }
+            Err(e) => {
+                eprintln!("Failed to acquire next swap chain texture for window '{}': {:?}", self.id, e);
+                return;
+            }
+        };
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = state_3d.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Window Encoder"),
+        });
+
+        let screen_descriptor = ScreenDescriptor {
+            size_in_pixels: [self.config.width, self.config.height],
+            pixels_per_point,
+        };
+
+        for (id, image_delta) in &output.textures_delta.set {
+            self.egui_renderer.update_texture(&state_3d.device, &state_3d.queue, *id, image_delta);
+        }
+
+        self.egui_renderer.update_buffers(
+            &state_3d.device,
+            &state_3d.queue,
+            &mut encoder,
+            &paint_jobs,
+            &screen_descriptor,
+        );
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("eGUI Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            self.egui_renderer.render(&mut rpass, &paint_jobs, &screen_descriptor);
+        }
+
+        state_3d.queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+
+        for id in &output.textures_delta.free {
+            self.egui_renderer.free_texture(id);
+        }
+    }
+}
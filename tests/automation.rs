@@ -0,0 +1,1021 @@
+use pioneer_egui::accessibility::{parse_role, AccessInfo};
+use pioneer_egui::automation::{widget_value, Action, Driver};
+use pioneer_egui::clipboard::{ClipboardAccess, ClipboardImage};
+use pioneer_egui::containers::{Container, ContainerKind, PanelSide};
+use pioneer_egui::dialogs::{DialogKind, FileBrowser, FileMode, ToastLevel};
+use pioneer_egui::drag_drop::DropTarget;
+use pioneer_egui::events::{self, RecordedEvent};
+use pioneer_egui::editors::{DragValueState, VectorState};
+use pioneer_egui::i18n::{visual_order, Catalog, Message, Script};
+use pioneer_egui::images::{ImageData, ImageView};
+use pioneer_egui::inputs::{SliderState, TextInputState};
+use pioneer_egui::menus::{MenuItem, ShortcutOwner};
+use pioneer_egui::persistence::{restore_widget_state, Persistence};
+use pioneer_egui::plot::{PlotSample, PlotState, Series};
+use pioneer_egui::rich_text::{parse_markdown, LabelState, LineKind};
+use pioneer_egui::scene::{SceneObject, WHITE};
+use pioneer_egui::state_3d::cube_contains;
+use pioneer_egui::storage::Storage;
+use pioneer_egui::table::TableState;
+use pioneer_egui::theme::{FontFace, TextStyleSpec, ThemeBase, ThemeState};
+use pioneer_egui::timeline::{apply_edit, TimelineEdit, TimelineState};
+use pioneer_egui::tree::MAIN_WINDOW;
+use pioneer_egui::tree_view::TreeViewState;
+use pioneer_egui::{ButtonState, EguiApp};
+
+fn app_with_widgets() -> EguiApp {
+    let mut app = EguiApp::default();
+    app.checkboxes.insert("notifications".into(), false);
+    app.combo_boxes.insert(
+        "theme".into(),
+        ("Light".into(), vec!["Light".into(), "Dark".into(), "System".into()]),
+    );
+    app.radio_groups.insert(
+        "language".into(),
+        ("English".into(), vec!["English".into(), "Spanish".into(), "French".into()]),
+    );
+    app
+}
+
+#[test]
+fn click_button_starts_recording() {
+    let mut driver = Driver::new(app_with_widgets());
+    driver.click("startRecording");
+    assert!(driver.app.is_recording);
+
+    driver.click("stopRecording");
+    assert!(!driver.app.is_recording);
+}
+
+#[test]
+fn toggle_checkbox_emits_event() {
+    let mut driver = Driver::new(app_with_widgets());
+    driver.click("notifications");
+
+    assert!(driver.app.checkboxes["notifications"]);
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "checkbox_notifications" && e["checked"] == true));
+}
+
+#[test]
+fn type_text_replaces_input() {
+    let mut driver = Driver::new(EguiApp {
+        input_text: "John Doe".into(),
+        ..app_with_widgets()
+    });
+    driver.type_text("input", "Jane Roe");
+
+    assert_eq!(driver.app.input_text, "Jane Roe");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "input_change" && e["text"] == "Jane Roe"));
+}
+
+#[test]
+fn drag_slider_sets_value() {
+    let mut driver = Driver::new(app_with_widgets());
+    driver.drag_slider("slider", 75.0);
+
+    assert!((driver.app.slider_value - 75.0).abs() <= 2.0, "got {}", driver.app.slider_value);
+}
+
+#[test]
+fn select_combo_and_radio_entries() {
+    let mut driver = Driver::new(app_with_widgets());
+    driver.select_combo("theme", "Dark").select_radio("language", "French");
+
+    assert_eq!(driver.app.combo_boxes["theme"].0, "Dark");
+    assert_eq!(driver.app.radio_groups["language"].0, "French");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "combo_theme" && e["selected"] == "Dark"));
+    assert!(events.iter().any(|e| e["type"] == "radio_language" && e["selected"] == "French"));
+}
+
+#[test]
+fn wait_until_sees_script_updates() {
+    let mut driver = Driver::new(app_with_widgets());
+    driver.app.progress_bars.insert("upload".into(), 30.0);
+
+    assert!(driver.wait_until(5, |app| app.widgets.contains_key("upload")));
+    assert!(driver.find("upload").is_some());
+}
+
+#[test]
+fn widgets_in_other_windows_stay_out_of_main() {
+    let mut app = app_with_widgets();
+    app.place("notifications", Some("settings"));
+    let driver = Driver::new(app);
+
+    assert!(driver.find("notifications").is_none());
+    assert!(driver.find("theme").is_some());
+}
+
+#[test]
+fn tabs_show_the_selected_child() {
+    let mut app = app_with_widgets();
+    for (id, title, kind) in [
+        ("settings", "Settings", ContainerKind::Tabs { selected: None }),
+        ("general", "General", ContainerKind::Vertical),
+        ("advanced", "Advanced", ContainerKind::Vertical),
+    ] {
+        app.containers.insert(id.into(), Container { title: title.into(), kind });
+    }
+    app.place("settings", None);
+    app.place("general", Some("settings"));
+    app.place("advanced", Some("settings"));
+    app.place("notifications", Some("general"));
+    app.place("theme", Some("advanced"));
+
+    let mut driver = Driver::new(app);
+    assert!(driver.find("notifications").is_some());
+    assert!(driver.find("theme").is_none());
+
+    driver.click("settings/advanced");
+    assert!(driver.find("theme").is_some());
+    assert!(driver.find("notifications").is_none());
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "tab_change" && e["selected"] == "advanced"));
+}
+
+#[test]
+fn panels_and_scroll_areas_are_tracked_as_containers() {
+    let mut app = app_with_widgets();
+    for (id, kind) in [
+        ("sidebar", ContainerKind::Panel { side: PanelSide::Left, resizable: true, default_size: Some(200.0) }),
+        ("list", ContainerKind::ScrollArea { horizontal: false, vertical: true, max_height: None }),
+    ] {
+        app.containers.insert(id.into(), Container { title: String::new(), kind });
+    }
+    app.place("sidebar", None);
+    app.place("list", Some("sidebar"));
+    app.place("notifications", Some("list"));
+
+    let mut driver = Driver::new(app);
+    let sidebar = driver.find("sidebar").expect("panel is tracked").rect;
+    let list = driver.find("list").expect("scroll area is tracked").rect;
+    assert!(sidebar.contains_rect(list));
+    driver.click("notifications");
+    assert!(driver.app.checkboxes["notifications"]);
+}
+
+#[test]
+fn button_clicks_carry_the_button_id() {
+    let mut app = app_with_widgets();
+    for (id, enabled) in [("save", true), ("delete", false)] {
+        app.buttons.insert(
+            id.into(),
+            ButtonState { label: id.into(), enabled, tooltip: None, icon: None, message: None },
+        );
+    }
+    let mut driver = Driver::new(app);
+
+    driver.click("save").click("delete");
+    let clicks: Vec<_> = driver
+        .take_events()
+        .into_iter()
+        .filter(|e| e["type"] == "button_click")
+        .collect();
+    assert_eq!(clicks.len(), 1);
+    assert_eq!(clicks[0]["id"], "save");
+}
+
+#[test]
+fn sliders_and_inputs_are_keyed_by_id() {
+    let mut app = app_with_widgets();
+    let slider = serde_json::json!({ "id": "volume", "value": 1.0, "min": 1.0, "max": 1000.0, "logarithmic": true });
+    app.sliders.insert("volume".into(), SliderState::new(&serde_json::from_value(slider).unwrap()));
+    let input = serde_json::json!({ "id": "age", "text": "", "validation": { "kind": "numeric", "integer": true } });
+    app.text_inputs.insert("age".into(), TextInputState::new(&serde_json::from_value(input).unwrap()).unwrap());
+    let mut driver = Driver::new(app);
+
+    driver.drag_slider("volume", 100.0);
+    let value = driver.app.sliders["volume"].value;
+    assert!((value - 100.0).abs() <= 10.0, "got {}", value);
+
+    driver.type_text("age", "4x");
+    assert!(driver.app.text_inputs["age"].error.is_some());
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "slider_change" && e["id"] == "volume"));
+    assert!(events.iter().any(|e| e["type"] == "input_invalid" && e["id"] == "age" && e["text"] == "4x"));
+
+    driver.type_text("age", "42");
+    assert!(driver.app.text_inputs["age"].error.is_none());
+
+    for text in ["NaN", "inf", "-infinity"] {
+        let input = serde_json::json!({ "id": "ratio", "text": text, "validation": { "kind": "numeric", "min": 0.0, "max": 1.0 } });
+        let input = TextInputState::new(&serde_json::from_value(input).unwrap()).unwrap();
+        assert_eq!(input.error.as_deref(), Some("Expected a number"), "accepted {}", text);
+    }
+}
+
+#[test]
+fn table_sorts_selects_and_only_lays_out_visible_rows() {
+    let mut app = app_with_widgets();
+    let mut rows: Vec<Vec<serde_json::Value>> = (0..100_000)
+        .map(|i| vec![serde_json::json!(format!("item {i}")), serde_json::json!(i)])
+        .collect();
+    rows.push(vec![serde_json::json!("last"), serde_json::json!(-1)]);
+    let args = serde_json::json!({ "id": "items", "columns": [{ "name": "Name" }, { "name": "Count" }] });
+    let mut table = TableState::new(&serde_json::from_value(args).unwrap());
+    table.append(rows);
+    app.tables.insert("items".into(), table);
+    let mut driver = Driver::new(app);
+
+    assert!(driver.find("items/row/0").is_some());
+    assert!(driver.find("items/row/99999").is_none());
+
+    driver.click("items/column/Count");
+    driver.click("items/row/100000");
+    assert_eq!(driver.app.tables["items"].selected.iter().copied().collect::<Vec<_>>(), vec![100_000]);
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "table_select" && e["rows"][0][0] == "last"));
+}
+
+#[test]
+fn plot_series_keep_only_the_latest_points() {
+    let mut app = app_with_widgets();
+    let args = serde_json::json!({ "id": "fps", "max_points": 3 });
+    app.plots.insert("fps".into(), PlotState::new(&serde_json::from_value(args).unwrap()));
+    let args: pioneer_egui::SetPlotSeriesArgs =
+        serde_json::from_value(serde_json::json!({ "id": "fps", "name": "frame time", "points": [[10.0, 1.0]] })).unwrap();
+    let mut series = Series::new(&args);
+    series.append(&args.points, Some(3));
+    series.append(&serde_json::from_value::<Vec<PlotSample>>(serde_json::json!([2.0, 3.0, 4.0])).unwrap(), Some(3));
+    assert_eq!(series.points.iter().copied().collect::<Vec<_>>(), vec![[11.0, 2.0], [12.0, 3.0], [13.0, 4.0]]);
+    app.plots.get_mut("fps").unwrap().series.insert("frame time".into(), series);
+
+    let driver = Driver::new(app);
+    assert!(driver.find("fps").is_some());
+}
+
+#[test]
+fn tree_expands_lazily_and_reparents_by_drag() {
+    let mut app = app_with_widgets();
+    let args = serde_json::json!({
+        "id": "files",
+        "draggable": true,
+        "nodes": [
+            { "id": "src", "label": "src", "lazy": true },
+            { "id": "docs", "label": "docs", "children": [{ "id": "readme", "label": "README.md" }] },
+        ],
+    });
+    app.tree_views.insert("files".into(), TreeViewState::new(&serde_json::from_value(args).unwrap()));
+    let mut driver = Driver::new(app);
+
+    driver.click("files/src/toggle");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "tree_load" && e["node"] == "src"));
+    let children = serde_json::from_value(serde_json::json!([{ "id": "lib", "label": "lib.rs" }])).unwrap();
+    assert!(driver.app.tree_views.get_mut("files").unwrap().set_children("src", children));
+
+    driver.step();
+    driver.drag("files/lib", "files/docs");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "tree_reparent" && e["node"] == "lib" && e["parent"] == "docs"));
+}
+
+#[test]
+fn outliner_selects_scene_objects() {
+    let mut app = app_with_widgets();
+    for (id, object_type) in [("cube1", "cube"), ("ball", "sphere")] {
+        app.scene_objects.insert(
+            id.into(),
+            SceneObject { scene_id: "mainScene".into(), object_type: object_type.into(), size: 1.0, color: WHITE },
+        );
+    }
+    let args = serde_json::json!({ "id": "outliner", "outliner": true });
+    app.tree_views.insert("outliner".into(), TreeViewState::new(&serde_json::from_value(args).unwrap()));
+    let mut driver = Driver::new(app);
+
+    driver.click("outliner/mainScene/toggle").click("outliner/ball");
+    assert_eq!(driver.app.selected_object.as_deref(), Some("ball"));
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "object_select" && e["id"] == "ball" && e["scene_id"] == "mainScene"));
+}
+
+#[test]
+fn property_editors_report_changes_with_their_id() {
+    let mut app = app_with_widgets();
+    app.drag_values.insert(
+        "distance".into(),
+        DragValueState {
+            label: None,
+            value: 1.0,
+            speed: 0.1,
+            min: Some(0.0),
+            max: Some(10.0),
+            decimals: Some(1),
+            prefix: String::new(),
+            suffix: " m".into(),
+        },
+    );
+    app.vectors.insert(
+        "position".into(),
+        VectorState { label: None, values: vec![0.0, 0.0, 0.0], speed: 0.1, suffix: String::new() },
+    );
+    let mut driver = Driver::new(app);
+
+    driver.type_text("distance", "42");
+    driver.perform(Action::PressKey { key: "Enter".into(), command: false });
+    assert_eq!(driver.app.drag_values["distance"].value, 10.0);
+
+    driver.type_text("position/y", "2.5");
+    driver.perform(Action::PressKey { key: "Enter".into(), command: false });
+    assert_eq!(driver.app.vectors["position"].values, vec![0.0, 2.5, 0.0]);
+
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "drag_value_change" && e["id"] == "distance"));
+    assert!(events.iter().any(|e| e["type"] == "vector_change" && e["id"] == "position"));
+}
+
+#[test]
+fn selected_object_tints_the_scene() {
+    let mut app = EguiApp::default();
+    assert_eq!(app.scene_tint(), WHITE);
+    for (id, color) in [("a", [1.0, 0.0, 0.0, 1.0]), ("b", [0.0, 0.0, 1.0, 1.0])] {
+        app.scene_objects.insert(
+            id.into(),
+            SceneObject { scene_id: "scene".into(), object_type: "cube".into(), size: 1.0, color },
+        );
+    }
+    assert_eq!(app.scene_tint(), [1.0, 0.0, 0.0, 1.0]);
+    app.selected_object = Some("b".into());
+    assert_eq!(app.scene_tint(), [0.0, 0.0, 1.0, 1.0]);
+}
+
+#[test]
+fn menus_toolbars_and_shortcuts_report_commands() {
+    let mut app = app_with_widgets();
+    let items = |value: serde_json::Value| serde_json::from_value::<Vec<MenuItem>>(value).unwrap();
+    app.menu_bars.insert(
+        "main".into(),
+        items(serde_json::json!([
+            { "id": "file", "label": "File", "children": [{ "id": "save", "label": "Save", "shortcut": "Ctrl+S" }] },
+        ])),
+    );
+    let menus = app.menu_bars["main"].clone();
+    app.set_item_shortcuts(ShortcutOwner::MenuBar("main".into()), &menus).unwrap();
+    app.context_menus.insert("notifications".into(), items(serde_json::json!([{ "id": "reset", "label": "Reset" }])));
+    app.toolbars.insert("tools".into(), items(serde_json::json!([{ "id": "grid", "icon": "#", "checked": false }])));
+    let mut driver = Driver::new(app);
+
+    driver.click("menu/file").click("menu/save");
+    driver.perform(Action::PressKey { key: "S".into(), command: true });
+    let events = driver.take_events();
+    let sources: Vec<_> = events.iter().filter(|e| e["type"] == "command" && e["id"] == "save").map(|e| &e["source"]).collect();
+    assert_eq!(sources, ["menu", "shortcut"]);
+
+    driver.context_click("notifications").click("menu/reset");
+    driver.click("tools/grid");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["id"] == "reset" && e["source"] == "context_menu" && e["target"] == "notifications"));
+    assert!(events.iter().any(|e| e["id"] == "grid" && e["source"] == "toolbar" && e["checked"] == true));
+    assert!(!driver.app.checkboxes["notifications"]);
+
+    // Item shortcuts go away with the menu bar or toolbar showing them.
+    let grid = items(serde_json::json!([{ "id": "grid", "icon": "#", "shortcut": "Ctrl+G" }]));
+    driver.app.set_item_shortcuts(ShortcutOwner::Toolbar("tools".into()), &grid).unwrap();
+    driver.app.set_item_shortcuts(ShortcutOwner::MenuBar("main".into()), &[]).unwrap();
+    driver.take_events();
+    driver.perform(Action::PressKey { key: "S".into(), command: true });
+    driver.perform(Action::PressKey { key: "G".into(), command: true });
+    let commands: Vec<_> = driver.take_events().into_iter().filter(|e| e["type"] == "command").map(|e| e["id"].clone()).collect();
+    assert_eq!(commands, ["grid"]);
+    driver.app.remove_widget("tools");
+    driver.perform(Action::PressKey { key: "G".into(), command: true });
+    assert!(!driver.take_events().iter().any(|e| e["type"] == "command"));
+
+    let unnamed = items(serde_json::json!([{ "label": "Undo", "shortcut": "Ctrl+Z" }]));
+    assert!(driver.app.set_item_shortcuts(ShortcutOwner::ContextMenu("notifications".into()), &unnamed).is_err());
+}
+
+#[test]
+fn the_viewport_menu_opens_only_over_the_drawn_object() {
+    let screen = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(800.0, 600.0));
+    assert!(cube_contains(0.0, screen, screen.center()));
+    assert!(!cube_contains(0.0, screen, egui::pos2(5.0, 5.0)));
+    // A corner of the rotated cube reaches past the edge of the unrotated one.
+    let beside = egui::pos2(660.0, 300.0);
+    assert!(!cube_contains(0.0, screen, beside));
+    assert!(cube_contains(45.0, screen, beside));
+}
+
+#[test]
+fn dialogs_answer_with_the_user_choice() {
+    let mut driver = Driver::new(app_with_widgets());
+    let buttons = vec!["Delete".to_string(), "Keep".to_string()];
+    let mut confirm = driver.app.open_dialog("Delete?", "", DialogKind::Confirm { buttons });
+    let mut prompt = driver.app.open_dialog("Name", "", DialogKind::Prompt { text: String::new(), hint: String::new() });
+    driver.step();
+
+    // The backdrop blocks the widgets behind the dialog.
+    driver.click("startRecording");
+    assert!(!driver.app.is_recording);
+
+    driver.click("dialog/button/Delete");
+    assert_eq!(confirm.try_recv().unwrap(), "Delete");
+    driver.type_text("dialog/input", "Ada").click("dialog/ok");
+    assert_eq!(prompt.try_recv().unwrap(), "Ada");
+    assert!(driver.app.dialogs.is_empty());
+}
+
+#[test]
+fn file_dialog_browses_directories() {
+    let root = std::env::temp_dir().join(format!("pioneer_egui_dialog_{}", std::process::id()));
+    std::fs::create_dir_all(root.join("recordings")).unwrap();
+    std::fs::write(root.join("recordings/take1.json"), "[]").unwrap();
+    std::fs::write(root.join("recordings/notes.txt"), "").unwrap();
+
+    let mut driver = Driver::new(app_with_widgets());
+    let browser = FileBrowser::new(FileMode::Open, Some(root.clone()), String::new(), vec!["json".into()]);
+    let mut answer = driver.app.open_dialog("Open", "", DialogKind::File(browser));
+    driver.step();
+    driver.click("dialog/entry/recordings");
+    assert!(driver.find("dialog/entry/notes.txt").is_none());
+    driver.click("dialog/entry/take1.json").click("dialog/ok");
+
+    let path = answer.try_recv().unwrap();
+    assert_eq!(path, root.join("recordings/take1.json").display().to_string());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn toasts_expire_or_are_dismissed() {
+    let mut driver = Driver::new(app_with_widgets());
+    let mut expiring = driver.app.show_toast("Saved", ToastLevel::Success, 0.5);
+    let mut sticky = driver.app.show_toast("Failed", ToastLevel::Error, 0.0);
+    assert!(driver.wait_until(60, |app| app.toasts.len() == 1));
+    assert_eq!(expiring.try_recv().unwrap()["dismissed"], false);
+
+    driver.click("toast/2");
+    assert_eq!(sticky.try_recv().unwrap()["dismissed"], true);
+    assert!(driver.app.toasts.is_empty());
+}
+
+#[test]
+fn theme_applies_visuals_text_styles_and_scale() {
+    let mut driver = Driver::new(app_with_widgets());
+    assert!(driver.ctx.style().visuals.dark_mode);
+
+    let theme = &mut driver.app.theme;
+    theme.theme.base = ThemeBase::Light;
+    theme.theme.colors.panel_fill = Some([250, 240, 230]);
+    theme.theme.text_styles.insert("body".into(), TextStyleSpec { size: 20.0, family: "proportional".into() });
+    theme.theme.ui_scale = 1.5;
+    theme.changed();
+    driver.step();
+
+    let style = driver.ctx.style();
+    assert!(!style.visuals.dark_mode);
+    assert_eq!(style.visuals.panel_fill, egui::Color32::from_rgb(250, 240, 230));
+    assert_eq!(style.text_styles[&egui::TextStyle::Body].size, 20.0);
+    assert_eq!(driver.app.pixels_per_point(2.0), 3.0);
+
+    let path = std::env::temp_dir().join(format!("pioneer_egui_theme_{}.json", std::process::id()));
+    driver.app.theme.save(&path).unwrap();
+    let mut loaded = ThemeState::default();
+    loaded.load(&path).unwrap();
+    assert_eq!(loaded.theme, driver.app.theme.theme);
+
+    // egui panics on families without fonts and cannot lay out NaN sizes.
+    assert!(loaded.set_text_style("body".into(), TextStyleSpec { size: f32::NAN, family: "proportional".into() }).is_err());
+    assert!(loaded.set_text_style("body".into(), TextStyleSpec { size: 14.0, family: "serif".into() }).is_err());
+    let mut bad = loaded.theme.clone();
+    bad.text_styles.insert("heading".into(), TextStyleSpec { size: 30.0, family: "serif".into() });
+    assert!(loaded.set_theme(bad).is_err());
+    assert_eq!(loaded.theme, driver.app.theme.theme);
+
+    // Not a font file, so it must not reach egui.
+    std::fs::write(&path, "{}").unwrap();
+    let face = FontFace { name: "bad".into(), path: path.display().to_string(), family: "proportional".into(), first: true };
+    assert!(loaded.add_font(face).is_err());
+    assert!(loaded.theme.fonts.is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn rich_labels_report_links_and_images_report_clicks() {
+    let lines = parse_markdown("# Status\n- **Recording** to `take1.json`\n\nSee [docs](help)");
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].kind, LineKind::Heading(1));
+    assert_eq!(lines[1].kind, LineKind::Bullet);
+    assert!(lines[1].spans[0].bold && lines[1].spans[2].code);
+    assert_eq!(lines[2].spans[1].link.as_deref(), Some("help"));
+
+    let mut app = app_with_widgets();
+    let args = serde_json::json!({ "id": "help", "text": "See [docs](help) or [faq](faq)", "markdown": true });
+    app.labels.insert("help".into(), LabelState::new(&serde_json::from_value(args).unwrap()));
+    let mut png = Vec::new();
+    image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageOutputFormat::Png)
+        .unwrap();
+    app.images.insert("logo".into(), ImageData::from_bytes("logo.png", png));
+    app.image_views.insert(
+        "logo".into(),
+        ImageView { image: "logo".into(), max_size: None, tooltip: None, clickable: true },
+    );
+    let mut driver = Driver::new(app);
+
+    assert_eq!(widget_value(&driver.app, "help"), Some(serde_json::json!("See docs or faq")));
+    driver.click("help/link/1").click("logo");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "label_link" && e["id"] == "help" && e["link"] == "faq"));
+    assert!(events.iter().any(|e| e["type"] == "image_click" && e["id"] == "logo"));
+}
+
+#[test]
+fn items_and_files_are_dropped_on_accepting_widgets() {
+    let mut app = app_with_widgets();
+    let args = serde_json::json!({
+        "id": "files",
+        "draggable": true,
+        "nodes": [{ "id": "scene", "label": "scene.glb" }, { "id": "take1", "label": "take1.json" }],
+    });
+    app.tree_views.insert("files".into(), TreeViewState::new(&serde_json::from_value(args).unwrap()));
+    for id in ["queue", "locked"] {
+        let args = serde_json::json!({ "id": id, "columns": [{ "name": "File" }], "rows": [["empty"]] });
+        app.tables.insert(id.into(), TableState::new(&serde_json::from_value(args).unwrap()));
+    }
+    app.drop_targets.insert("queue".into(), DropTarget { accept: vec!["files".into()] });
+    app.drop_targets.insert("locked".into(), DropTarget { accept: vec!["outliner".into()] });
+    let mut driver = Driver::new(app);
+
+    driver.drag("files/take1", "queue/row/0");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "drag_start" && e["id"] == "files" && e["item"] == "take1"));
+    assert!(events.iter().any(|e| e["type"] == "item_drop"
+        && e["id"] == "queue"
+        && e["source"] == "files"
+        && e["item"] == "take1"
+        && e["over"] == "row/0"));
+    assert!(driver.app.dragging.is_none());
+
+    driver.drag("files/scene", "locked/row/0");
+    assert!(!driver.take_events().iter().any(|e| e["type"] == "item_drop"));
+
+    driver.drop_files("queue/row/0", &["/tmp/take2.json"]);
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "file_drop"
+        && e["id"] == "main"
+        && e["paths"][0] == "/tmp/take2.json"
+        && e["target"] == "queue/row/0"));
+}
+
+#[test]
+fn clipboard_shortcuts_report_copy_and_paste() {
+    let mut driver = Driver::new(app_with_widgets());
+    driver.type_text("input", "hello");
+    driver.take_events();
+
+    driver.copy("input");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "copy" && e["widget"] == "input" && e["text"] == "hello" && e["cut"] == false));
+
+    driver.paste("input", " world");
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "paste" && e["widget"] == "input" && e["text"] == " world"));
+    assert!(driver.app.input_text.contains("world"));
+
+    // Scripts' clipboard accesses wait for the event loop.
+    let mut answer = driver.app.request_clipboard(ClipboardAccess::GetText);
+    assert!(answer.try_recv().is_err());
+    assert_eq!(driver.app.clipboard_requests.len(), 1);
+    let image = ClipboardImage { width: 2, height: 2, rgba: vec![0; 12] };
+    assert!(image.check().is_err());
+    let image = ClipboardImage { width: usize::MAX, height: 2, rgba: Vec::new() };
+    assert!(image.check().is_err());
+}
+
+fn find_node<'a>(node: &'a serde_json::Value, id: &str) -> Option<&'a serde_json::Value> {
+    if node["id"] == id {
+        return Some(node);
+    }
+    node["children"].as_array()?.iter().find_map(|child| find_node(child, id))
+}
+
+#[test]
+fn accessibility_tree_carries_script_labels_and_roles() {
+    let mut app = app_with_widgets();
+    app.accessibility.insert(
+        "startRecording".into(),
+        AccessInfo { description: Some("Records UI events".into()), ..Default::default() },
+    );
+    app.accessibility.insert(
+        "input".into(),
+        AccessInfo { label: Some("Event filter".into()), role: Some("text_field".into()), ..Default::default() },
+    );
+    assert!(parse_role("spaceship").is_err());
+    let mut driver = Driver::new(app);
+
+    let tree = driver.accessibility_tree();
+    assert_eq!(tree["role"], "window");
+    let button = find_node(&tree, "startRecording").expect("button node");
+    assert_eq!(button["role"], "button");
+    assert_eq!(button["name"], "Start Recording");
+    assert_eq!(button["description"], "Records UI events");
+    let input = find_node(&tree, "input").expect("input node");
+    assert_eq!(input["role"], "text_field");
+    assert_eq!(input["name"], "Event filter");
+}
+
+#[test]
+fn labels_follow_the_locale_and_rtl_text_is_reordered() {
+    let en = Catalog::parse_fluent("-app = Pioneer\n# Greeting\nwelcome = Welcome to { -app }, { $user }!\nrecord = Record\n    .tooltip = Starts recording\n");
+    assert_eq!(en.messages["record.tooltip"], "Starts recording");
+    let es = Catalog::parse_json(r#"{ "welcome": "¡Hola { $user }!", "menu": { "file": "Archivo" } }"#).unwrap();
+    assert_eq!(es.messages["menu.file"], "Archivo");
+
+    let mut app = app_with_widgets();
+    app.i18n.add_catalog("en", en);
+    app.i18n.add_catalog("es", es);
+    let args = serde_json::json!({ "id": "welcome", "message": { "key": "welcome", "args": { "user": "Ana" } } });
+    app.labels.insert("welcome".into(), LabelState::new(&serde_json::from_value(args).unwrap()));
+    let message = Message { key: "record".into(), args: Default::default() };
+    app.buttons.insert(
+        "record".into(),
+        ButtonState { label: String::new(), enabled: true, tooltip: None, icon: None, message: Some(message) },
+    );
+    let mut driver = Driver::new(app);
+
+    driver.step();
+    assert_eq!(widget_value(&driver.app, "welcome"), Some(serde_json::json!("Welcome to Pioneer, Ana!")));
+    driver.app.i18n.set_locale("es-MX");
+    driver.step();
+    assert_eq!(widget_value(&driver.app, "welcome"), Some(serde_json::json!("¡Hola Ana!")));
+    assert_eq!(widget_value(&driver.app, "record").unwrap()["label"], "Record");
+    assert_eq!(driver.app.i18n.translate("missing", &Default::default()), "missing");
+
+    assert_eq!(Script::of_locale("ja"), Script::Cjk);
+    assert!(Script::of_locale("ar_EG").is_rtl());
+    assert_eq!(visual_order("שלום 42"), "42 םולש");
+    assert_eq!(visual_order("Plain text"), "Plain text");
+}
+
+#[test]
+fn widget_values_and_egui_memory_survive_a_restart() {
+    let path = std::env::temp_dir().join(format!("pioneer_egui_state_{}.json", std::process::id()));
+    let mut driver = Driver::new(app_with_widgets());
+    driver.click("notifications").select_radio("language", "Spanish");
+    driver.ctx.data_mut(|data| data.insert_persisted(egui::Id::new("panel_width"), 240.0f32));
+    driver.app.persistence.path = Some(path.clone());
+    driver.app.persistence.set_persist("notifications", false);
+    driver.app.persistence.remember_memory(MAIN_WINDOW, &driver.ctx);
+    driver.app.capture_persisted();
+    driver.app.persistence.save().unwrap();
+
+    let persistence = Persistence::open(path.clone()).unwrap();
+    assert_eq!(persistence.state.widgets["language"], "Spanish");
+    assert!(!persistence.state.widgets.contains_key("notifications"));
+    let mut app = app_with_widgets();
+    app.persistence = persistence;
+    let mut driver = Driver::new(app);
+    driver.app.persistence.restore_memory(MAIN_WINDOW, &driver.ctx);
+
+    assert_eq!(widget_value(&driver.app, "language"), Some(serde_json::json!("Spanish")));
+    assert_eq!(widget_value(&driver.app, "notifications"), Some(serde_json::json!(false)));
+    assert_eq!(driver.ctx.data_mut(|data| data.get_persisted::<f32>(egui::Id::new("panel_width"))), Some(240.0));
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "state_restore" && e["id"] == "language" && e["value"] == "Spanish"));
+
+    driver.app.persistence.clear(None);
+    driver.app.persistence.save().unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn restoring_into_reversed_ranges_does_not_panic() {
+    let mut app = app_with_widgets();
+    let slider = serde_json::json!({ "type": "slider", "id": "gain", "value": 5.0, "min": 10.0, "max": 0.0 });
+    app.set_widget(serde_json::from_value(slider).unwrap()).unwrap();
+    restore_widget_state(&mut app, "gain", &serde_json::json!(50.0)).unwrap();
+    assert_eq!(app.sliders["gain"].value, 10.0);
+
+    let drag = serde_json::json!({ "type": "drag_value", "id": "offset", "value": 0.0, "min": 1.0, "max": -1.0 });
+    assert!(app.set_widget(serde_json::from_value(drag).unwrap()).is_err());
+    assert!(!app.drag_values.contains_key("offset"));
+}
+
+#[test]
+fn script_storage_is_namespaced_and_survives_reopening() {
+    let dir = std::env::temp_dir().join(format!("pioneer_egui_storage_{}", std::process::id()));
+    let mut storage = Storage::new(dir.clone());
+    storage.set("dashboard", "prefs.theme", serde_json::json!("dark")).unwrap();
+    storage.set("dashboard", "prefs.volume", serde_json::json!(40)).unwrap();
+    storage.set("dashboard", "recent", serde_json::json!(["a.json", "b.json"])).unwrap();
+    storage.set("importer", "prefs.theme", serde_json::json!("light")).unwrap();
+    assert!(storage.set("../escape", "key", serde_json::json!(1)).is_err());
+    assert!(storage.delete("dashboard", "recent").unwrap());
+    assert!(!storage.delete("dashboard", "recent").unwrap());
+
+    let mut reopened = Storage::new(dir.clone());
+    assert_eq!(reopened.get("dashboard", "prefs.theme").unwrap(), Some(serde_json::json!("dark")));
+    assert_eq!(reopened.get("importer", "prefs.theme").unwrap(), Some(serde_json::json!("light")));
+    assert_eq!(reopened.get("dashboard", "recent").unwrap(), None);
+    assert_eq!(reopened.list("dashboard", "prefs.").unwrap(), vec!["prefs.theme", "prefs.volume"]);
+    assert!(!dir.join("dashboard.json.tmp").exists());
+
+    // A failed write keeps what is on disk in memory too.
+    reopened.dir = dir.join("importer.json");
+    assert!(reopened.set("dashboard", "prefs.theme", serde_json::json!("light")).is_err());
+    assert!(reopened.delete("dashboard", "prefs.volume").is_err());
+    assert_eq!(reopened.get("dashboard", "prefs.theme").unwrap(), Some(serde_json::json!("dark")));
+    assert_eq!(reopened.list("dashboard", "prefs.").unwrap(), vec!["prefs.theme", "prefs.volume"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn bound_widgets_follow_script_writes_and_report_edits() {
+    let mut app = app_with_widgets();
+    app.labels.insert("volumeLabel".into(), LabelState::new(&serde_json::from_value(serde_json::json!({ "text": "" })).unwrap()));
+    app.bindings.declare("volume", serde_json::json!(20.0));
+    app.bindings.declare("lang", serde_json::json!("French"));
+    app.bindings.bind("slider", Some("volume")).unwrap();
+    app.bindings.bind("volumeLabel", Some("volume")).unwrap();
+    app.bindings.bind("language", Some("lang")).unwrap();
+    assert!(app.bindings.bind("notifications", Some("missing")).is_err());
+    let mut driver = Driver::new(app);
+
+    assert_eq!(widget_value(&driver.app, "slider"), Some(serde_json::json!(20.0)));
+    assert_eq!(widget_value(&driver.app, "volumeLabel"), Some(serde_json::json!("20.0")));
+    assert_eq!(widget_value(&driver.app, "language"), Some(serde_json::json!("French")));
+
+    driver.app.bindings.set("volume", serde_json::json!(35.0));
+    driver.step();
+    assert_eq!(widget_value(&driver.app, "slider"), Some(serde_json::json!(35.0)));
+    driver.take_events();
+
+    driver.select_radio("language", "Spanish");
+    assert_eq!(driver.app.bindings.values["lang"], "Spanish");
+    let changes: Vec<_> = driver.take_events().into_iter().filter(|e| e["type"] == "observable_change").collect();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["id"], "lang");
+    assert_eq!(changes[0]["source"], "language");
+
+    // The slider reads back 50.0, which is not an edit of 50.
+    driver.app.bindings.set("volume", serde_json::json!(50));
+    driver.step();
+    driver.step();
+    assert_eq!(widget_value(&driver.app, "slider"), Some(serde_json::json!(50.0)));
+    assert_eq!(driver.app.bindings.values["volume"], serde_json::json!(50));
+    let changes: Vec<_> = driver.take_events().into_iter().filter(|e| e["type"] == "observable_change").collect();
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0]["source"].is_null());
+}
+
+#[test]
+fn ui_documents_build_the_widget_tree_and_reload_when_changed() {
+    let path = std::env::temp_dir().join(format!("pioneer_egui_ui_{}.json", std::process::id()));
+    let write = |document: serde_json::Value, offset: u64| {
+        std::fs::write(&path, document.to_string()).unwrap();
+        let modified = std::time::SystemTime::now() + std::time::Duration::from_secs(offset);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+    };
+    write(serde_json::json!({
+        "observables": { "volume": 30.0 },
+        "widgets": [{
+            "type": "container", "id": "audio", "kind": "panel", "side": "right",
+            "children": [
+                { "type": "slider", "id": "volume", "value": 50.0, "min": 0.0, "max": 100.0, "bind": "volume" },
+                { "type": "checkbox", "id": "mute", "checked": true, "persist": false },
+                { "type": "label", "id": "hint", "text": "Drag to change" },
+            ],
+        }],
+    }), 0);
+
+    let mut driver = Driver::new(app_with_widgets());
+    let ids = driver.app.load_ui_document(&path, true).unwrap();
+    assert_eq!(ids, vec!["audio", "volume", "mute", "hint"]);
+    driver.step();
+    assert_eq!(driver.app.tree.children("audio"), ["volume", "mute", "hint"]);
+    assert_eq!(widget_value(&driver.app, "volume"), Some(serde_json::json!(30.0)));
+    assert!(!driver.app.persistence.persists("mute"));
+    assert!(driver.take_events().iter().any(|e| e["type"] == "ui_load" && e["widgets"].as_array().unwrap().len() == 4));
+
+    driver.click("mute");
+    write(serde_json::json!({
+        "observables": { "volume": 30.0 },
+        "widgets": [{
+            "type": "container", "id": "audio", "kind": "panel", "side": "right",
+            "children": [
+                { "type": "checkbox", "id": "mute", "checked": true },
+                { "type": "slider", "id": "volume", "value": 50.0, "bind": "volume" },
+            ],
+        }],
+    }), 10);
+    driver.app.reload_changed_ui_documents();
+    driver.step();
+    assert_eq!(driver.app.tree.children("audio"), ["mute", "volume"]);
+    assert!(!driver.app.tree.contains("hint"));
+    assert!(driver.find("hint").is_none());
+    assert!(!driver.app.labels.contains_key("hint"));
+    assert_eq!(widget_value(&driver.app, "mute"), Some(serde_json::json!(false)));
+
+    // A widget that fails to load leaves the earlier layout as it was.
+    write(serde_json::json!({
+        "widgets": [{
+            "type": "container", "id": "audio", "kind": "panel", "side": "right",
+            "children": [
+                { "type": "label", "id": "extra", "text": "New" },
+                { "type": "slider", "id": "volume", "value": 50.0, "bind": "volume" },
+                { "type": "checkbox", "id": "broken", "checked": false, "bind": "missing" },
+                { "type": "checkbox", "id": "mute", "checked": true },
+            ],
+        }],
+    }), 15);
+    driver.take_events();
+    driver.app.reload_changed_ui_documents();
+    driver.step();
+    assert!(driver.take_events().iter().any(|e| e["type"] == "ui_load_error"));
+    assert_eq!(driver.app.tree.children("audio"), ["mute", "volume"]);
+    assert!(driver.find("extra").is_none() && driver.find("broken").is_none());
+    assert!(!driver.app.labels.contains_key("extra") && !driver.app.checkboxes.contains_key("broken"));
+
+    std::fs::write(&path, "{ \"widgets\": [").unwrap();
+    std::fs::File::options().write(true).open(&path).unwrap()
+        .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(20)).unwrap();
+    driver.take_events();
+    driver.app.reload_changed_ui_documents();
+    assert_eq!(driver.app.tree.children("audio"), ["mute", "volume"]);
+    assert!(driver.take_events().iter().any(|e| e["type"] == "ui_load_error"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn a_ui_document_that_fails_halfway_leaves_every_widget_as_it_was() {
+    let path = std::env::temp_dir().join(format!("pioneer_egui_rollback_{}.json", std::process::id()));
+    std::fs::write(&path, serde_json::json!({
+        "widgets": [
+            { "type": "label", "id": "title", "text": "Audio" },
+            { "type": "combo_box", "id": "size", "selected": "S", "options": ["S", "M", "L"] },
+        ],
+    }).to_string()).unwrap();
+    let mut driver = Driver::new(app_with_widgets());
+    driver.app.load_ui_document(&path, true).unwrap();
+    restore_widget_state(&mut driver.app, "size", &serde_json::json!("M")).unwrap();
+    driver.click("notifications");
+    let title = driver.app.labels["title"].clone();
+
+    // Reconfigures both earlier widgets and the script's checkbox before a bad bind.
+    std::fs::write(&path, serde_json::json!({
+        "widgets": [
+            { "type": "label", "id": "title", "text": "Video" },
+            { "type": "combo_box", "id": "size", "selected": "XS", "options": ["XS", "XL"] },
+            { "type": "checkbox", "id": "notifications", "checked": false },
+            { "type": "checkbox", "id": "broken", "checked": false, "bind": "missing" },
+        ],
+    }).to_string()).unwrap();
+    assert!(driver.app.load_ui_document(&path, true).is_err());
+    driver.step();
+    assert_eq!(driver.app.labels["title"], title);
+    assert_eq!(driver.app.combo_boxes["size"], ("M".to_string(), vec!["S".to_string(), "M".to_string(), "L".to_string()]));
+    assert!(driver.app.checkboxes["notifications"]);
+    assert!(driver.find("notifications").is_some());
+    assert!(!driver.app.checkboxes.contains_key("broken"));
+    std::fs::remove_file(path).unwrap();
+}
+
+fn recorded(event_type: &str, component_id: &str, timestamp: u64) -> RecordedEvent {
+    RecordedEvent {
+        event_type: event_type.into(),
+        component_id: component_id.into(),
+        event_data: serde_json::json!({ "value": timestamp }),
+        timestamp,
+    }
+}
+
+#[test]
+fn timeline_edits_write_back_to_the_recording() {
+    let mut events = vec![recorded("button_click", "save", 0), recorded("slider_change", "slider", 500), recorded("button_click", "save", 900)];
+    assert_eq!(apply_edit(&mut events, &TimelineEdit::Move { indices: vec![0], delta: 700 }).unwrap(), vec![1]);
+    assert_eq!(events.iter().map(|e| e.timestamp).collect::<Vec<_>>(), vec![500, 700, 900]);
+    assert!(apply_edit(&mut events, &TimelineEdit::Delete { indices: vec![3] }).is_err());
+
+    let mut app = app_with_widgets();
+    app.recorded_events = events;
+    app.timelines.insert("timeline".into(), TimelineState::default());
+    let mut driver = Driver::new(app);
+
+    driver.click("timeline/event/1");
+    assert_eq!(widget_value(&driver.app, "timeline").unwrap()["selected"], serde_json::json!([1]));
+    assert!(driver.take_events().iter().any(|e| e["type"] == "timeline_select" && e["selected"] == serde_json::json!([1])));
+
+    driver.click("timeline/duplicate");
+    assert_eq!(driver.app.recorded_events.len(), 4);
+    assert_eq!(driver.app.recorded_events[2].timestamp, 800);
+    assert_eq!(driver.app.timelines["timeline"].selected.iter().copied().collect::<Vec<_>>(), vec![2]);
+
+    driver.click("timeline/delete");
+    assert_eq!(driver.app.recorded_events.iter().map(|e| e.timestamp).collect::<Vec<_>>(), vec![500, 700, 900]);
+    let edits: Vec<_> = driver.take_events().into_iter().filter(|e| e["type"] == "recording_edit").collect();
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0]["edit"], "duplicate");
+
+    driver.drag_slider("timeline/ruler", 1500.0);
+    let cursor = driver.app.timelines["timeline"].cursor as f64;
+    assert!((cursor - 1500.0).abs() <= 20.0, "got {}", cursor);
+    assert!(driver.take_events().iter().any(|e| e["type"] == "timeline_seek"));
+}
+
+#[test]
+fn playback_seeks_steps_and_loops_over_the_recording() {
+    let mut app = app_with_widgets();
+    app.slider_value = 5.0;
+    app.recorded_events = vec![
+        RecordedEvent { event_data: serde_json::json!({ "value": 20.0 }), ..recorded("slider_change", "slider", 100) },
+        RecordedEvent { event_data: serde_json::json!({ "checked": true }), ..recorded("checkbox_notifications", "notifications", 200) },
+        RecordedEvent { event_data: serde_json::json!({ "value": 40.0 }), ..recorded("slider_change", "slider", 300) },
+    ];
+    let mut driver = Driver::new(app);
+    assert!(driver.app.set_playback_rate(32.0).is_err());
+    assert!(driver.app.set_playback_loop(Some((300, 100))).is_err());
+
+    driver.app.seek_playback(250).unwrap();
+    assert_eq!(driver.app.playback_status().state, "paused");
+    assert_eq!(driver.app.slider_value, 20.0);
+    assert!(driver.app.checkboxes["notifications"]);
+
+    // Seeking backwards rebuilds the state from before playback.
+    driver.app.seek_playback(50).unwrap();
+    assert_eq!(driver.app.slider_value, 5.0);
+    assert!(!driver.app.checkboxes["notifications"]);
+
+    driver.app.step_playback().unwrap();
+    assert_eq!(driver.app.playback_status().position, 100);
+    assert_eq!(driver.app.slider_value, 20.0);
+    driver.step();
+    let events = driver.take_events();
+    assert!(events.iter().any(|e| e["type"] == "playback_event" && e["id"] == "slider" && e["timestamp"] == 100));
+    assert!(events.iter().any(|e| e["type"] == "playback_progress" && e["state"] == "paused" && e["index"] == 1));
+
+    // Playback outside a new loop jumps to its start.
+    driver.app.set_playback_rate(2.0).unwrap();
+    driver.app.set_playback_loop(Some((150, 250))).unwrap();
+    assert_eq!(driver.app.playback_status().position, 150);
+    driver.app.pause_playback(false);
+    driver.app.advance_playback(std::time::Duration::from_millis(40));
+    assert_eq!(driver.app.playback_status().position, 230);
+    assert!(driver.app.checkboxes["notifications"]);
+    driver.app.advance_playback(std::time::Duration::from_millis(20));
+    assert_eq!(driver.app.playback_status().position, 170);
+    assert!(!driver.app.checkboxes["notifications"]);
+    assert_eq!(driver.app.slider_value, 20.0);
+
+    driver.app.set_playback_loop(None).unwrap();
+    driver.app.advance_playback(std::time::Duration::from_millis(100));
+    assert_eq!(driver.app.playback_status().state, "stopped");
+    assert_eq!(driver.app.slider_value, 40.0);
+}
+
+#[test]
+fn recordings_replay_from_their_snapshots_on_a_fresh_app() {
+    let mut app = app_with_widgets();
+    app.slider_value = 30.0;
+    app.checkboxes.insert("notifications".into(), true);
+    app.rotation = 45.0;
+    app.scene_objects.insert(
+        "cube1".into(),
+        SceneObject { scene_id: "main".into(), object_type: "cube".into(), size: 1.0, color: WHITE },
+    );
+    app.start_recording();
+    app.recorded_events = vec![
+        RecordedEvent { event_data: serde_json::json!({ "value": 60.0 }), ..recorded("slider_change", "slider", 100) },
+        RecordedEvent { event_data: serde_json::json!({ "checked": false }), ..recorded("checkbox_notifications", "notifications", 200) },
+    ];
+    app.slider_value = 60.0;
+    app.rotation = 90.0;
+    let keyframe = app.snapshot(150, 1);
+    app.recorded_snapshots.push(keyframe);
+    app.is_recording = false;
+
+    let path = std::env::temp_dir().join(format!("pioneer_egui_recording_{}.json", std::process::id()));
+    events::save(&path, &events::Recording { snapshots: app.recorded_snapshots, events: app.recorded_events }).unwrap();
+    let recording = events::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!((recording.snapshots.len(), recording.events.len()), (2, 2));
+
+    let mut fresh = app_with_widgets();
+    fresh.recorded_events = recording.events;
+    fresh.recorded_snapshots = recording.snapshots;
+    let mut driver = Driver::new(fresh);
+
+    driver.app.start_playback().unwrap();
+    driver.app.pause_playback(true);
+    assert_eq!(driver.app.slider_value, 30.0);
+    assert!(driver.app.checkboxes["notifications"]);
+    assert_eq!(driver.app.rotation, 45.0);
+    assert!(driver.app.scene_objects.contains_key("cube1"));
+    driver.step();
+    assert!(driver.take_events().iter().any(|e| e["type"] == "state_restore" && e["id"] == "notifications"));
+
+    // The keyframe is restored instead of replaying the events before it.
+    driver.app.seek_playback(170).unwrap();
+    assert_eq!(driver.app.rotation, 90.0);
+    driver.app.seek_playback(250).unwrap();
+    assert!(!driver.app.checkboxes["notifications"]);
+    driver.app.seek_playback(50).unwrap();
+    assert_eq!((driver.app.slider_value, driver.app.rotation), (30.0, 45.0));
+    assert!(driver.app.checkboxes["notifications"]);
+
+    // Editing drops the keyframes after the first changed event.
+    driver.app.edit_recording(TimelineEdit::Delete { indices: vec![0] }).unwrap();
+    assert_eq!(driver.app.recorded_snapshots.len(), 1);
+}
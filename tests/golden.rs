@@ -0,0 +1,39 @@
+mod support;
+
+use pioneer_egui::EguiApp;
+
+#[test]
+#[ignore = "needs a wgpu adapter"]
+fn scene_default_rotation() {
+    let mut renderer = support::renderer();
+    let image = renderer.render_scene(0.0);
+    support::assert_golden("scene_default_rotation", &image);
+}
+
+#[test]
+#[ignore = "needs a wgpu adapter"]
+fn scene_rotated() {
+    let mut renderer = support::renderer();
+    let image = renderer.render_scene(45.0);
+    support::assert_golden("scene_rotated", &image);
+}
+
+#[test]
+#[ignore = "needs a wgpu adapter"]
+fn ui_default_widgets() {
+    let mut renderer = support::renderer();
+    let mut app = EguiApp {
+        label_text: "Golden image".to_string(),
+        slider_value: 42.0,
+        input_text: "John Doe".to_string(),
+        ..Default::default()
+    };
+
+    // The first frame only uploads the font atlas and lays out.
+    let input = renderer.raw_input();
+    renderer.run_frame(&mut app, input);
+
+    let input = renderer.raw_input();
+    let image = renderer.render(&mut app, input);
+    support::assert_golden("ui_default_widgets", &image);
+}
@@ -0,0 +1,136 @@
+//! Golden-image comparison for the headless renderer.
+//!
+//! References live in `tests/golden/<name>.png`. Set `PIONEER_BLESS=1` to
+//! (re)write them from the current output. On mismatch the actual image and a
+//! diff image are written to `target/golden-diffs/`.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use pioneer_egui::headless::HeadlessRenderer;
+
+pub const WIDTH: u32 = 320;
+pub const HEIGHT: u32 = 240;
+pub const PIXELS_PER_POINT: f32 = 1.0;
+
+/// Max YIQ distance between two pixels before they count as different, as a
+/// fraction of the largest possible distance.
+const PIXEL_THRESHOLD: f32 = 0.1;
+/// Fraction of differing pixels tolerated before the comparison fails.
+const MAX_DIFF_RATIO: f32 = 0.001;
+/// Squared YIQ distance between black and white.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+/// Golden-image tests are `#[ignore]`d since they need a GPU; run them with
+/// `cargo test -- --ignored`. Panics when no wgpu adapter is available.
+pub fn renderer() -> HeadlessRenderer {
+    HeadlessRenderer::new(WIDTH, HEIGHT, PIXELS_PER_POINT)
+        .expect("No wgpu adapter available for golden-image tests")
+}
+
+fn reference_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+fn diff_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden-diffs")
+}
+
+fn bless_requested() -> bool {
+    std::env::var("PIONEER_BLESS").map_or(false, |v| v == "1")
+}
+
+fn save_png(path: &PathBuf, image: &egui::ColorImage) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    image::save_buffer(
+        path,
+        image.as_raw(),
+        image.size[0] as u32,
+        image.size[1] as u32,
+        image::ColorType::Rgba8,
+    )
+    .unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e));
+}
+
+fn load_png(path: &PathBuf) -> Option<egui::ColorImage> {
+    let image = image::open(path).ok()?.to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+fn yiq_delta(a: egui::Color32, b: egui::Color32) -> f32 {
+    // Blend against white so transparent pixels compare sensibly.
+    let blend = |c: egui::Color32| {
+        let alpha = c.a() as f32 / 255.0;
+        let channel = |v: u8| 255.0 + (v as f32 - 255.0) * alpha;
+        (channel(c.r()), channel(c.g()), channel(c.b()))
+    };
+    let (r1, g1, b1) = blend(a);
+    let (r2, g2, b2) = blend(b);
+
+    let y = |r: f32, g: f32, b: f32| r * 0.29889531 + g * 0.58662247 + b * 0.11448223;
+    let i = |r: f32, g: f32, b: f32| r * 0.59597799 - g * 0.27417610 - b * 0.32180189;
+    let q = |r: f32, g: f32, b: f32| r * 0.21147017 - g * 0.52261711 + b * 0.31114694;
+
+    let dy = y(r1, g1, b1) - y(r2, g2, b2);
+    let di = i(r1, g1, b1) - i(r2, g2, b2);
+    let dq = q(r1, g1, b1) - q(r2, g2, b2);
+    0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq
+}
+
+/// Compares `actual` against the stored reference `name`, panicking with the
+/// number of differing pixels when it is outside the tolerance.
+pub fn assert_golden(name: &str, actual: &egui::ColorImage) {
+    let reference_path = reference_path(name);
+
+    if bless_requested() {
+        save_png(&reference_path, actual);
+        eprintln!("Blessed {}", reference_path.display());
+        return;
+    }
+
+    let Some(expected) = load_png(&reference_path) else {
+        panic!(
+            "No reference for '{}' at {}. Run with PIONEER_BLESS=1 to write it.",
+            name,
+            reference_path.display()
+        );
+    };
+
+    assert_eq!(
+        expected.size, actual.size,
+        "'{}' has size {:?}, reference is {:?}",
+        name, actual.size, expected.size
+    );
+
+    let max_delta = MAX_YIQ_DELTA * PIXEL_THRESHOLD * PIXEL_THRESHOLD;
+    let mut diff = egui::ColorImage::new(actual.size, egui::Color32::TRANSPARENT);
+    let mut differing = 0usize;
+    for (index, (a, e)) in actual.pixels.iter().zip(&expected.pixels).enumerate() {
+        if yiq_delta(*a, *e) > max_delta {
+            differing += 1;
+            diff.pixels[index] = egui::Color32::RED;
+        } else {
+            let gray = (e.r() as u16 + e.g() as u16 + e.b() as u16) / 3;
+            diff.pixels[index] = egui::Color32::from_gray(128 + (gray / 2) as u8);
+        }
+    }
+
+    let ratio = differing as f32 / actual.pixels.len() as f32;
+    if ratio > MAX_DIFF_RATIO {
+        let dir = diff_dir();
+        save_png(&dir.join(format!("{name}-actual.png")), actual);
+        save_png(&dir.join(format!("{name}-diff.png")), &diff);
+        panic!(
+            "'{}' differs from its reference in {} pixels ({:.3}%), see {}. \
+             Rerun with PIONEER_BLESS=1 if the change is intended.",
+            name,
+            differing,
+            ratio * 100.0,
+            dir.display()
+        );
+    }
+}